@@ -22,6 +22,7 @@ namespace Flowtime {
     public class Application : Adw.Application {
         private Window main_window;
         private Xdp.Portal session_monitor = new Xdp.Portal ();
+        private uint dbus_object_id = 0;
 
         private const ActionEntry[] APP_ENTRIES = {
             { "quit", action_close },
@@ -43,6 +44,20 @@ namespace Flowtime {
             Intl.textdomain (Config.GETTEXT_PACKAGE);
         }
 
+        public override bool dbus_register (DBusConnection connection, string object_path) throws Error {
+            base.dbus_register (connection, object_path);
+            dbus_object_id = connection.register_object (object_path, new Services.DBusService ());
+            return true;
+        }
+
+        public override void dbus_unregister (DBusConnection connection, string object_path) {
+            if (dbus_object_id != 0) {
+                connection.unregister_object (dbus_object_id);
+                dbus_object_id = 0;
+            }
+            base.dbus_unregister (connection, object_path);
+        }
+
         private async void query_monitor_session () {
             try {
                 bool success = yield session_monitor.session_monitor_start (null, NONE, null);
@@ -87,16 +102,23 @@ namespace Flowtime {
         }
 
         protected override void activate () {
-            if (main_window == null) {
+            bool first_activation = main_window == null;
+            if (first_activation) {
                 main_window = new Window (this);
                 query_monitor_session.begin ();
             }
             main_window.present ();
+
+            if (first_activation) {
+                main_window.start_on_launch ();
+            }
         }
 
         protected override void shutdown () {
             base.shutdown ();
 
+            new Services.Statistics ().auto_export ();
+
             var settings = new Services.Settings ();
             settings.save ();
             quit ();
@@ -0,0 +1,23 @@
+/* DashboardSnapshot.vala
+ *
+ * Copyright 2023 Diego Iván <diegoivan.mae@gmail.com>
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+// Everything the overview shows, read at once so it is consistent
+public struct Flowtime.Models.DashboardSnapshot {
+    public int today_worktime;
+    public int today_breaktime;
+    public uint current_streak;
+    public uint longest_streak;
+    public int week_worktime;
+    // How much more worktime this week has than the last one
+    public int week_trend;
+    public string productive_day;
+    // The fraction of the daily goal reached today
+    public double goal_progress;
+    // The day with the most worktime, null without any saved day
+    public DateTime? best_day;
+    public int best_day_worktime;
+}
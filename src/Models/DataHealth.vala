@@ -0,0 +1,14 @@
+/* DataHealth.vala
+ *
+ * Copyright 2023 Diego Iván <diegoivan.mae@gmail.com>
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+public class Flowtime.Models.DataHealth : Object {
+    public int64 file_size { get; set; default = 0; }
+    public uint day_count { get; set; default = 0; }
+    public DateTime? earliest_date { get; set; default = null; }
+    public DateTime? latest_date { get; set; default = null; }
+    public uint warning_count { get; set; default = 0; }
+}
@@ -9,6 +9,13 @@ public class Flowtime.Models.Day : Object, InformationHolder {
     public Xml.Node* node { get; private set; }
     private Xml.Node* worktime_node;
     private Xml.Node* breaktime_node;
+    private Xml.Node* pausetime_node;
+    private Xml.Node* breaks_node;
+    private Xml.Node* note_node = null;
+    // Set when the node is not part of a document, so the day has to free it
+    private bool owns_node = false;
+
+    public List<Session> sessions = new List<Session> ();
 
     private DateTime _date;
     public DateTime date {
@@ -21,6 +28,57 @@ public class Flowtime.Models.Day : Object, InformationHolder {
         }
     }
 
+    // False for a day saved with a date that cannot be read, which must not be counted
+    public bool valid_date { get; private set; default = true; }
+
+    // Intentional rest days do not break streaks
+    private bool _rest_day = false;
+    public bool rest_day {
+        get {
+            return _rest_day;
+        }
+        set {
+            _rest_day = value;
+            if (value) {
+                node->set_prop ("rest", "true");
+            } else {
+                node->unset_prop ("rest");
+            }
+        }
+    }
+
+    // How the user felt during the day, from 1 to 5, or 0 if it was not rated
+    private uint _energy = 0;
+    public uint energy {
+        get {
+            return _energy;
+        }
+        set {
+            _energy = value;
+            if (value > 0) {
+                node->set_prop ("energy", value.to_string ());
+            } else {
+                node->unset_prop ("energy");
+            }
+        }
+    }
+
+    // A streak freeze was spent on the day, so missing it does not break the streak
+    private bool _frozen = false;
+    public bool frozen {
+        get {
+            return _frozen;
+        }
+        set {
+            _frozen = value;
+            if (value) {
+                node->set_prop ("frozen", "true");
+            } else {
+                node->unset_prop ("frozen");
+            }
+        }
+    }
+
     private int _worktime = 0;
     internal int worktime {
         get {
@@ -43,6 +101,87 @@ public class Flowtime.Models.Day : Object, InformationHolder {
         }
     }
 
+    /*
+     * Time the timer was paused in the middle of a work stage. It is kept apart from breaktime so
+     * that only the breaks suggested by Flowtime are counted as such.
+     */
+    private int _pausetime = 0;
+    internal int pausetime {
+        get {
+            return _pausetime;
+        }
+        set {
+            _pausetime = value;
+            pausetime_node->set_content (value.to_string ());
+        }
+    }
+
+    // Number of breaks taken during the day
+    private int _breaks = 0;
+    internal int breaks {
+        get {
+            return _breaks;
+        }
+        set {
+            _breaks = value;
+            breaks_node->set_content (value.to_string ());
+        }
+    }
+
+    // A note written about the day. The element is only saved once there is a note
+    private string _note = "";
+    public string note {
+        get {
+            return _note;
+        }
+        set {
+            _note = value;
+            if (note_node == null) {
+                note_node = node->new_child (null, "note");
+            }
+            note_node->set_content (Markup.escape_text (value));
+        }
+    }
+
+    // When the first session of the day started and the last one ended, unknown for older days
+    private DateTime? _first_activity = null;
+    public DateTime? first_activity {
+        get {
+            return _first_activity;
+        }
+        set {
+            _first_activity = value;
+            if (value != null) {
+                node->set_prop ("first", value.format_iso8601 ());
+            }
+        }
+    }
+
+    private DateTime? _last_activity = null;
+    public DateTime? last_activity {
+        get {
+            return _last_activity;
+        }
+        set {
+            _last_activity = value;
+            if (value != null) {
+                node->set_prop ("last", value.format_iso8601 ());
+            }
+        }
+    }
+
+    public string worktime_display {
+        owned get {
+            return TimeUtils.format_time (counted_worktime ());
+        }
+    }
+
+    public string breaktime_display {
+        owned get {
+            return TimeUtils.format_time (breaktime);
+        }
+    }
+
     public string day_of_week {
         owned get {
             return date.format ("%A");
@@ -50,32 +189,228 @@ public class Flowtime.Models.Day : Object, InformationHolder {
     }
 
     public Day () {
+        create_nodes (new DateTime.now_local ());
+    }
+
+    public Day.for_date (DateTime date) {
+        create_nodes (date);
+    }
+
+    // An empty day that is never saved, such as the dates filled in for charts
+    public Day.unsaved (DateTime date) {
+        create_nodes (date);
+        owns_node = true;
+    }
+
+    ~Day () {
+        if (owns_node) {
+            delete node;
+        }
+    }
+
+    private void create_nodes (DateTime date) {
         node = new Xml.Node (null, "day");
-        date = new DateTime.now_local ();
+        this.date = date;
 
-        worktime_node = node->new_text_child (null, "worktime", "");
-        breaktime_node = node->new_text_child (null, "breaktime", "");
+        worktime_node = node->new_text_child (null, "worktime", "0");
+        breaktime_node = node->new_text_child (null, "breaktime", "0");
+        pausetime_node = node->new_text_child (null, "pausetime", "0");
+        breaks_node = node->new_text_child (null, "breaks", "0");
     }
 
     public Day.from_xml (Xml.Node* n) {
         node = n;
-        date = new DateTime.from_iso8601 (n->get_prop ("date"), null);
+        _date = TimeUtils.parse_date (n->get_prop ("date"));
+        if (_date == null) {
+            // The saved date is kept, the day is left out of the statistics by whoever reads it
+            warning ("A day has an invalid date: %s", n->get_prop ("date") ?? "none");
+            valid_date = false;
+            _date = new DateTime.now_local ();
+        }
+        _rest_day = n->get_prop ("rest") == "true";
+        _frozen = n->get_prop ("frozen") == "true";
+
+        string? energy_text = n->get_prop ("energy");
+        if (energy_text != null) {
+            uint64 rating;
+            if (uint64.try_parse (energy_text, out rating) && rating >= 1 && rating <= 5) {
+                _energy = (uint) rating;
+            } else {
+                warning ("A day has an invalid energy rating, it is read as not rated");
+            }
+        }
+
+        _first_activity = TimeUtils.parse_date (n->get_prop ("first"));
+        _last_activity = TimeUtils.parse_date (n->get_prop ("last"));
 
         for (Xml.Node* i = node->children; i != null; i = i->next) {
             if (i->type == ELEMENT_NODE) {
                 switch (i->name) {
                     case "worktime":
                         worktime_node = XmlUtils.get_content_node (i, "worktime");
-                        _worktime = int.parse(worktime_node->get_content ());
+                        _worktime = XmlUtils.parse_int_content (worktime_node);
                         break;
 
                     case "breaktime":
                         breaktime_node = XmlUtils.get_content_node (i, "breaktime");
-                        _breaktime = int.parse (breaktime_node->get_content ());
+                        _breaktime = XmlUtils.parse_int_content (breaktime_node);
+                        break;
+
+                    case "pausetime":
+                        pausetime_node = XmlUtils.get_content_node (i, "pausetime");
+                        _pausetime = XmlUtils.parse_int_content (pausetime_node);
+                        break;
+
+                    case "breaks":
+                        breaks_node = XmlUtils.get_content_node (i, "breaks");
+                        _breaks = XmlUtils.parse_int_content (breaks_node);
+                        break;
+
+                    case "note":
+                        note_node = i;
+                        _note = i->get_content ();
+                        break;
+
+                    case "session":
+                        sessions.append (new Session.from_xml (i));
                         break;
                 }
             }
         }
+
+        // Files written before pausetime existed do not have the element
+        if (pausetime_node == null) {
+            pausetime_node = node->new_text_child (null, "pausetime", "0");
+        }
+
+        if (breaks_node == null) {
+            breaks_node = node->new_text_child (null, "breaks", "0");
+        }
+    }
+
+    /*
+     * Every work session is stored on its own, so the daily worktime can be derived
+     * from the sum of the durations of its sessions. The session ends now, unless an end is given.
+     */
+    public void add_session (int duration, DateTime? end = null) {
+        var start = (end ?? new DateTime.now_local ()).add_seconds (-duration);
+        var session = new Session (start, duration);
+        node->add_child (session.node);
+        sessions.append (session);
+        track_activity (session);
+    }
+
+    public void extend_last_session (int duration) {
+        unowned List<Session>? last = sessions.last ();
+        if (last == null) {
+            add_session (duration);
+            return;
+        }
+        last.data.duration += duration;
+        track_activity (last.data);
+    }
+
+    // Adds a session done on another record of the same date
+    public void copy_session (Session other) {
+        var session = new Session (other.start, other.duration);
+        session.break_taken = other.break_taken;
+        node->add_child (session.node);
+        sessions.append (session);
+        track_activity (session);
+    }
+
+    private void track_activity (Session session) {
+        if (session.start == null) {
+            return;
+        }
+
+        if (first_activity == null || session.start.compare (first_activity) < 0) {
+            first_activity = session.start;
+        }
+
+        DateTime end = session.start.add_seconds (session.duration);
+        if (last_activity == null || end.compare (last_activity) > 0) {
+            last_activity = end;
+        }
+    }
+
+    // The hours the day was worked, such as 09:12–17:45, or empty if they are unknown
+    public string work_span_display () {
+        if (first_activity == null || last_activity == null) {
+            return "";
+        }
+        return "%s–%s".printf (first_activity.to_local ().format ("%R"),
+                               last_activity.to_local ().format ("%R"));
+    }
+
+    // Breaks are counted towards the session that was done before them
+    public void add_session_break (int duration) {
+        unowned List<Session>? last = sessions.last ();
+        if (last != null) {
+            last.data.break_taken += duration;
+        }
+    }
+
+    public int sessions_worktime () {
+        int sum = 0;
+        foreach (unowned Session session in sessions) {
+            sum += session.duration;
+        }
+        return sum;
+    }
+
+    /*
+     * The worktime counted towards the totals. When only deep work is counted, the sessions
+     * shorter than the deep work length are left out. Worktime saved before sessions were
+     * recorded is always counted. It is never more than the longest worktime of a day.
+     */
+    public int counted_worktime () {
+        var settings = new Services.Settings ();
+        int max_worktime = settings.max_day_worktime * 3600;
+        if (!settings.deep_work_only) {
+            return worktime.clamp (0, max_worktime);
+        }
+
+        int counted = int.max (worktime - sessions_worktime (), 0);
+        foreach (unowned Session session in sessions) {
+            if (session.duration >= settings.deep_work_minutes * 60) {
+                counted += session.duration;
+            }
+        }
+        return counted.clamp (0, max_worktime);
+    }
+
+    /*
+     * The break time earned during the day under the active break policy. Each session earns its
+     * own break, days saved before sessions were recorded use the total worktime instead.
+     */
+    public int earned_break () {
+        if (sessions.is_empty ()) {
+            return Services.Timer.suggested_break (worktime);
+        }
+
+        int earned = 0;
+        foreach (unowned Session session in sessions) {
+            earned += Services.Timer.suggested_break (session.duration);
+        }
+        return earned;
+    }
+
+    // Positive when more break was taken than earned, negative when breaks were cut short
+    public int break_balance () {
+        return breaktime - earned_break ();
+    }
+
+    /*
+     * The fraction of the earned break that was taken, up to 1. A day without earned break owes
+     * nothing, so it is fully compliant.
+     */
+    public double break_compliance () {
+        int earned = earned_break ();
+        if (earned == 0) {
+            return 1;
+        }
+        return double.min ((double) breaktime / earned, 1);
     }
 
     public void unlink () {
@@ -0,0 +1,17 @@
+/* FeedItem.vala
+ *
+ * Copyright 2023 Diego Iván <diegoivan.mae@gmail.com>
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+// Something worth telling about the history, such as a day the goal was reached
+public class Flowtime.Models.FeedItem : Object {
+    public DateTime timestamp { get; construct; }
+    public string icon_name { get; construct; }
+    public string message { get; construct; }
+
+    public FeedItem (DateTime timestamp, string icon_name, string message) {
+        Object (timestamp: timestamp, icon_name: icon_name, message: message);
+    }
+}
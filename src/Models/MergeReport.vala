@@ -0,0 +1,18 @@
+/* MergeReport.vala
+ *
+ * Copyright 2023 Diego Iván <diegoivan.mae@gmail.com>
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+public class Flowtime.Models.MergeReport : Object {
+    // Days that were not saved before
+    public uint added { get; set; default = 0; }
+    // Days whose time was added to a day already saved
+    public uint merged { get; set; default = 0; }
+    // Days identical to a day already saved
+    public uint skipped { get; set; default = 0; }
+
+    // The problems found while merging, one message for each
+    public string[] errors = {};
+}
@@ -0,0 +1,12 @@
+/* SeriesPoint.vala
+ *
+ * Copyright 2023 Diego Iván <diegoivan.mae@gmail.com>
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+// A value of a chart, such as the worktime of a day or a week, at the date it starts
+public struct Flowtime.Models.SeriesPoint {
+    public DateTime date;
+    public double value;
+}
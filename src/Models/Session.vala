@@ -0,0 +1,69 @@
+/* Session.vala
+ *
+ * Copyright 2023 Diego Iván <diegoivan.mae@gmail.com>
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+public class Flowtime.Models.Session : Object {
+    public Xml.Node* node { get; private set; }
+
+    private int _duration = 0;
+    public int duration {
+        get {
+            return _duration;
+        }
+        set {
+            _duration = value;
+            node->set_prop ("dur", value.to_string ());
+        }
+    }
+
+    // Sessions saved before the start was recorded do not have one
+    private DateTime? _start = null;
+    public DateTime? start {
+        get {
+            return _start;
+        }
+        set {
+            _start = value;
+            if (value != null) {
+                node->set_prop ("start", value.format_iso8601 ());
+            }
+        }
+    }
+
+    // The break taken once the session was over
+    private int _break_taken = 0;
+    public int break_taken {
+        get {
+            return _break_taken;
+        }
+        set {
+            _break_taken = value;
+            node->set_prop ("break", value.to_string ());
+        }
+    }
+
+    public Session (DateTime? start, int duration) {
+        node = new Xml.Node (null, "session");
+        this.start = start;
+        this.duration = duration;
+    }
+
+    public Session.from_xml (Xml.Node* n) {
+        node = n;
+
+        string? dur = n->get_prop ("dur");
+        if (dur != null) {
+            _duration = int.parse (dur);
+        }
+
+        _start = TimeUtils.parse_date (n->get_prop ("start"));
+
+        string? break_prop = n->get_prop ("break");
+        if (break_prop != null) {
+            _break_taken = int.parse (break_prop);
+        }
+    }
+}
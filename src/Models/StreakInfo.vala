@@ -0,0 +1,20 @@
+/* StreakInfo.vala
+ *
+ * Copyright 2023 Diego Iván <diegoivan.mae@gmail.com>
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+// Everything shown about the streak, so it can be read at once
+public struct Flowtime.Models.StreakInfo {
+    public uint current;
+    public uint longest;
+    // Set while the streak would be broken if today ended without work
+    public bool at_risk;
+    public int freezes_available;
+
+    public bool equal (StreakInfo other) {
+        return current == other.current && longest == other.longest
+            && at_risk == other.at_risk && freezes_available == other.freezes_available;
+    }
+}
@@ -0,0 +1,37 @@
+/* WeekComparison.vala
+ *
+ * Copyright 2023 Diego Iván <diegoivan.mae@gmail.com>
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+public class Flowtime.Models.WeekComparison : Object {
+    public int week_a_total { get; construct; }
+    public int week_b_total { get; construct; }
+
+    // How much more worktime week B had than week A
+    public int delta {
+        get {
+            return week_b_total - week_a_total;
+        }
+    }
+
+    // The difference of each day of the week, ordered from the first day of the week
+    public int[] weekday_deltas = new int[7];
+
+    public WeekComparison (int[] week_a, int[] week_b)
+    requires (week_a.length == 7 && week_b.length == 7) {
+        int total_a = 0;
+        int total_b = 0;
+        for (int i = 0; i < 7; i++) {
+            total_a += week_a[i];
+            total_b += week_b[i];
+        }
+
+        Object (week_a_total: total_a, week_b_total: total_b);
+
+        for (int i = 0; i < 7; i++) {
+            weekday_deltas[i] = week_b[i] - week_a[i];
+        }
+    }
+}
@@ -17,21 +17,232 @@ public class Flowtime.Services.Alarm {
         set {
             _timer = value;
             timer.done.connect (on_timer_done);
+            timer.notify["mode"].connect (on_timer_mode_changed);
+            timer.soft_cap_reached.connect (on_soft_cap_reached);
+            timer.break_reminder.connect (on_break_reminder);
         }
     }
 
     public Alarm (Timer timer) {
         this.timer = timer;
+
+        var settings = new Settings ();
+        settings.notify["daily-summary"].connect (schedule_summary);
+        settings.notify["summary-time"].connect (schedule_summary);
+        schedule_summary ();
+
+        var statistics = new Statistics ();
+        statistics.milestone_reached.connect (on_milestone_reached);
+        statistics.weekly_goal_reached.connect (on_weekly_goal_reached);
     }
 
+    private uint nudge_id = 0;
+    private uint summary_id = 0;
+
     private void on_timer_done () {
-        var notification = new GLib.Notification (_("Break is over!"));
-        notification.set_body (_("Let's get back to work"));
+        var settings = new Settings ();
+
+        if (settings.send_notifications) {
+            var notification = new GLib.Notification (_("Break is over!"));
+            notification.set_body (_("Let's get back to work"));
+            notification.set_priority (NORMAL);
+
+            application.send_notification ("Flowtime-Break-Done", notification);
+            schedule_nudge (settings.nudge_delay);
+        }
+
+        play_cue ();
+    }
+
+    private void on_timer_mode_changed () {
+        if (timer.mode == BREAK) {
+            play_cue ();
+        }
+    }
+
+    private void on_soft_cap_reached () {
+        var settings = new Settings ();
+        if (!settings.send_notifications || !settings.soft_cap_notification) {
+            return;
+        }
+
+        var notification = new GLib.Notification (_("Consider Taking a Break"));
+        notification.set_body (_("You have been working for a long time"));
         notification.set_priority (NORMAL);
 
-        application.send_notification ("Flowtime-Break-Done", notification);
+        application.send_notification ("Flowtime-Soft-Cap", notification);
+    }
+
+    /*
+     * Firm reminders are sent with a high priority, but never urgent, so do not disturb still
+     * hides them.
+     */
+    private void on_break_reminder (ReminderLevel level) {
+        var settings = new Settings ();
+        if (!settings.send_notifications || !settings.soft_cap_notification) {
+            return;
+        }
+
+        GLib.Notification notification;
+        if (level == FIRM) {
+            notification = new GLib.Notification (_("Time for a Break"));
+            notification.set_body (_("You have kept working well past your earned break"));
+            notification.set_priority (HIGH);
+        } else {
+            notification = new GLib.Notification (_("Consider Taking a Break"));
+            notification.set_body (_("You have been working for a long time"));
+            notification.set_priority (NORMAL);
+        }
+
+        application.send_notification ("Flowtime-Soft-Cap", notification);
+    }
+
+    // Playing is asynchronous, so the cue never blocks the timer
+    private void play_cue () {
+        if (!should_play_cue (Gtk.Settings.get_default ().gtk_enable_event_sounds)) {
+            return;
+        }
 
         var settings = new Settings ();
         player.play_tone (settings.tone);
     }
+
+    // The cues are only played if the system plays event sounds as well
+    internal static bool should_play_cue (bool event_sounds) {
+        var settings = new Settings ();
+        return settings.play_sounds && event_sounds;
+    }
+
+    private void schedule_nudge (int delay_minutes) {
+        if (nudge_id != 0) {
+            Source.remove (nudge_id);
+            nudge_id = 0;
+        }
+
+        if (delay_minutes == 0) {
+            return;
+        }
+
+        nudge_id = Timeout.add_seconds (delay_minutes * 60, () => {
+            nudge_id = 0;
+            if (should_nudge (timer)) {
+                send_nudge ();
+            }
+            return Source.REMOVE;
+        });
+    }
+
+    // The reminder is only useful while the break is over and work has not been resumed yet
+    internal static bool should_nudge (Timer timer) {
+        var settings = new Settings ();
+        return settings.send_notifications && timer.mode == BREAK && !timer.running;
+    }
+
+    private void send_nudge () {
+        var notification = new GLib.Notification (_("Ready to get back to it?"));
+        notification.set_body (_("Your break finished a while ago"));
+        notification.set_priority (LOW);
+
+        application.send_notification ("Flowtime-Break-Nudge", notification);
+    }
+
+    private void on_milestone_reached (uint hours) {
+        var settings = new Settings ();
+        if (!settings.send_notifications) {
+            return;
+        }
+
+        var notification = new GLib.Notification (_("Milestone Reached!"));
+        notification.set_body (ngettext (
+            "You have worked for %u hour with Flowtime",
+            "You have worked for %u hours with Flowtime",
+            hours
+        ).printf (hours));
+        notification.set_priority (NORMAL);
+
+        application.send_notification ("Flowtime-Milestone", notification);
+    }
+
+    private void on_weekly_goal_reached () {
+        var settings = new Settings ();
+        if (!settings.send_notifications) {
+            return;
+        }
+
+        var notification = new GLib.Notification (_("Weekly Goal Reached!"));
+        notification.set_body (_("You reached the worktime you planned for this week"));
+        notification.set_priority (NORMAL);
+
+        application.send_notification ("Flowtime-Weekly-Goal", notification);
+    }
+
+    /*
+     * The summary is sent every day at the configured minute after midnight. The time is read on
+     * the clock, so daylight saving time does not move it.
+     */
+    private void schedule_summary () {
+        if (summary_id != 0) {
+            Source.remove (summary_id);
+            summary_id = 0;
+        }
+
+        var settings = new Settings ();
+        if (!settings.daily_summary) {
+            return;
+        }
+
+        var now = new DateTime.now_local ();
+        DateTime next = next_summary (now, settings.summary_time);
+
+        uint delay = (uint) (next.difference (now) / TimeSpan.SECOND) + 1;
+        summary_id = Timeout.add_seconds (delay, () => {
+            summary_id = 0;
+            send_summary ();
+            schedule_summary ();
+            return Source.REMOVE;
+        });
+    }
+
+    // The first time after now at which the clock shows summary_time minutes after midnight
+    internal static DateTime next_summary (DateTime now, int summary_time) {
+        DateTime date = now.to_local ();
+        DateTime next = summary_on (date, summary_time);
+        if (next.compare (now) <= 0) {
+            // Moving 36 hours and normalizing again lands on the next date even when DST changes
+            date = TimeUtils.normalize_to_local_midnight (date).add_hours (36);
+            next = summary_on (date, summary_time);
+        }
+        return next;
+    }
+
+    private static DateTime summary_on (DateTime date, int summary_time) {
+        return new DateTime.local (date.get_year (), date.get_month (), date.get_day_of_month (),
+                                   summary_time / 60, summary_time % 60, 0);
+    }
+
+    // Days without any time are only summarized if the user asked for it
+    internal static bool should_send_summary (Models.Day? today) {
+        var settings = new Settings ();
+        if (!settings.send_notifications || today == null) {
+            return false;
+        }
+
+        bool idle = today.worktime == 0 && today.breaktime == 0;
+        return !idle || settings.summary_on_idle_days;
+    }
+
+    // Do not disturb is respected by the desktop, which hides the notification banners
+    private void send_summary () {
+        var statistics = new Statistics ();
+        Models.Day? today = statistics.today;
+        if (!should_send_summary (today)) {
+            return;
+        }
+
+        var notification = new GLib.Notification (_("Your Day in Flowtime"));
+        notification.set_body (statistics.summary_text (today));
+        notification.set_priority (LOW);
+
+        application.send_notification ("Flowtime-Daily-Summary", notification);
+    }
 }
@@ -27,17 +27,19 @@ public class Flowtime.Services.BackgroundStatusReporter {
     }
 
     private async void on_timer_updated () {
+        Terminology terminology = new Settings ().terminology;
         string status = "";
         switch (timer.mode) {
             case BREAK:
                 if (timer.seconds == 0) {
                     status = _("Break is over!");
                 } else {
-                    status = _("Break Stage: %s".printf (timer.formatted_time));
+                    // translators: The stage and the time of the timer, e.g. Break Stage: 04:30
+                    status = _("%s: %s").printf (terminology.break_stage_label (), timer.formatted_time);
                 }
                 break;
             case WORK:
-                status = _("Work Stage: %s".printf (timer.formatted_time));
+                status = _("%s: %s").printf (terminology.work_stage_label (), timer.formatted_time);
                 break;
         }
 
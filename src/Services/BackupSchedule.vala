@@ -0,0 +1,25 @@
+/* BackupSchedule.vala
+ *
+ * Copyright 2023 Diego Iván <diegoivan.mae@gmail.com>
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+// How often a backup of the statistics is written to the backup folder
+public enum Flowtime.Services.BackupSchedule {
+    NEVER,
+    DAILY,
+    WEEKLY;
+
+    // The days a backup is kept as the latest before another one is due
+    public int interval_days () {
+        switch (this) {
+            case DAILY:
+                return 1;
+            case WEEKLY:
+                return 7;
+            default:
+                return 0;
+        }
+    }
+}
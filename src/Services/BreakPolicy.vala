@@ -0,0 +1,114 @@
+/* BreakPolicy.vala
+ *
+ * Copyright 2023 Diego Iván <diegoivan.mae@gmail.com>
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+public enum Flowtime.Services.BreakPolicy {
+    PERCENTAGE,
+    FLOWTIME;
+
+    private const int MINUTE = 60;
+
+    public string to_string () {
+        switch (this) {
+            case PERCENTAGE:
+                return _("Percentage of Work Time");
+            case FLOWTIME:
+                return _("Flowtime Table");
+            default:
+                assert_not_reached ();
+        }
+    }
+
+    /*
+     * The break time, in seconds, that a work session of worktime seconds earns.
+     *
+     * The Flowtime table follows the original technique: up to 25 minutes of work earn a 5 minute
+     * break, up to 50 minutes earn 8 minutes, up to 90 minutes earn 10 and anything longer earns 15.
+     */
+    public int break_for (int worktime) {
+        if (worktime <= 0) {
+            return 0;
+        }
+
+        switch (this) {
+            case PERCENTAGE:
+                var settings = new Settings ();
+                return (int) (worktime * settings.break_percentage / 100);
+
+            case FLOWTIME:
+                if (worktime <= 25 * MINUTE) {
+                    return 5 * MINUTE;
+                }
+                if (worktime <= 50 * MINUTE) {
+                    return 8 * MINUTE;
+                }
+                if (worktime <= 90 * MINUTE) {
+                    return 10 * MINUTE;
+                }
+                return 15 * MINUTE;
+
+            default:
+                assert_not_reached ();
+        }
+    }
+
+    /*
+     * The worktime, in seconds, after which a break is considered earned. For the percentage
+     * policy it is the moment the break reaches a full minute, while the Flowtime table considers
+     * its first tier of 25 minutes a complete session.
+     */
+    public int earned_after () {
+        switch (this) {
+            case PERCENTAGE:
+                var settings = new Settings ();
+                return (int) Math.ceil (MINUTE * 100 / settings.break_percentage);
+
+            case FLOWTIME:
+                return 25 * MINUTE;
+
+            default:
+                assert_not_reached ();
+        }
+    }
+}
+
+public enum Flowtime.Services.BreakRounding {
+    NONE,
+    MINUTE,
+    FIVE_MINUTES;
+
+    public string to_string () {
+        switch (this) {
+            case NONE:
+                return _("Exact");
+            case MINUTE:
+                return _("Next Minute");
+            case FIVE_MINUTES:
+                return _("Next 5 Minutes");
+            default:
+                assert_not_reached ();
+        }
+    }
+
+    // Rounds the break up, so a suggestion is never made shorter than what was earned
+    public int round (int break_seconds) {
+        int step;
+        switch (this) {
+            case NONE:
+                return break_seconds;
+            case MINUTE:
+                step = 60;
+                break;
+            case FIVE_MINUTES:
+                step = 300;
+                break;
+            default:
+                assert_not_reached ();
+        }
+
+        return ((break_seconds + step - 1) / step) * step;
+    }
+}
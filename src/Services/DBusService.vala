@@ -0,0 +1,27 @@
+/* DBusService.vala
+ *
+ * Copyright 2023 Diego Iván <diegoivan.mae@gmail.com>
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+/*
+ * Read-only access to the statistics for extensions and companion apps, exported on
+ * the application's object path.
+ */
+[DBus (name = "io.github.diegoivanme.flowtime.Statistics")]
+public class Flowtime.Services.DBusService : Object {
+    private Statistics statistics = new Statistics ();
+
+    // See Statistics.snapshot_variant () for the meaning of each member
+    [DBus (signature = "(iiu)")]
+    public Variant snapshot {
+        owned get {
+            return statistics.snapshot_variant ();
+        }
+    }
+
+    construct {
+        statistics.updated.connect (() => notify_property ("snapshot"));
+    }
+}
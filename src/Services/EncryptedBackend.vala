@@ -0,0 +1,117 @@
+/* EncryptedBackend.vala
+ *
+ * Copyright 2023 Diego Iván <diegoivan.mae@gmail.com>
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+/*
+ * Saves the statistics as XML encrypted with a key derived from a passphrase. The file starts with
+ * a marker, followed by the salt of the key, the nonce and the encrypted document. Without the
+ * passphrase the statistics cannot be recovered.
+ */
+public class Flowtime.Services.EncryptedBackend : Object, StorageBackend {
+    public const string FILENAME = "statistics.enc";
+    private const string MARKER = "FLOWTIME-ENCRYPTED-1\n";
+
+    private string passphrase;
+    private uint8[] salt = {};
+    private uint8[] key = {};
+
+    public string filename {
+        get {
+            return FILENAME;
+        }
+    }
+
+    public EncryptedBackend (string passphrase) {
+        this.passphrase = passphrase;
+    }
+
+    public Xml.Doc* load (string path) throws StatisticsError {
+        uint8[] contents;
+        try {
+            FileUtils.get_data (path, out contents);
+        } catch (FileError e) {
+            throw new StatisticsError.IO ("%s cannot be read: %s".printf (path, e.message));
+        }
+
+        size_t salt_end = MARKER.length + Sodium.PasswordHash.SALT_BYTES;
+        size_t header_length = salt_end + Sodium.SecretBox.NONCE_BYTES;
+        if (contents.length < header_length + Sodium.SecretBox.MAC_BYTES
+            || Memory.cmp (contents, MARKER.data, MARKER.length) != 0) {
+            throw new StatisticsError.PARSE ("%s is not an encrypted statistics file".printf (path));
+        }
+
+        uint8[] file_salt = contents[MARKER.length:salt_end];
+        uint8[] nonce = contents[salt_end:header_length];
+        uint8[] ciphertext = contents[header_length:contents.length];
+
+        derive_key (file_salt);
+
+        // The message gets one more zeroed byte, so it ends like a string
+        int message_length = ciphertext.length - (int) Sodium.SecretBox.MAC_BYTES;
+        var message = new uint8[message_length + 1];
+        if (Sodium.SecretBox.decrypt (message, ciphertext, ciphertext.length, nonce, key) != 0) {
+            throw new StatisticsError.PARSE ("The passphrase is wrong or %s is damaged".printf (path));
+        }
+
+        Xml.Doc* doc = Xml.Parser.parse_memory ((string) message, message_length);
+        if (doc == null) {
+            throw new StatisticsError.PARSE ("The decrypted statistics cannot be parsed");
+        }
+        return doc;
+    }
+
+    public void save (Xml.Doc* doc, string path) throws StatisticsError {
+        if (salt.length == 0) {
+            var new_salt = new uint8[Sodium.PasswordHash.SALT_BYTES];
+            Sodium.random_bytes (new_salt, new_salt.length);
+            derive_key (new_salt);
+        }
+
+        uint8[] message = new XmlBackend ().serialize (doc).data;
+        var nonce = new uint8[Sodium.SecretBox.NONCE_BYTES];
+        Sodium.random_bytes (nonce, nonce.length);
+
+        var ciphertext = new uint8[message.length + Sodium.SecretBox.MAC_BYTES];
+        Sodium.SecretBox.encrypt (ciphertext, message, message.length, nonce, key);
+
+        var contents = new ByteArray ();
+        contents.append (MARKER.data);
+        contents.append (salt);
+        contents.append (nonce);
+        contents.append (ciphertext);
+
+        try {
+            FileUtils.set_data (path, contents.data);
+        } catch (FileError e) {
+            throw new StatisticsError.IO ("%s could not be written: %s".printf (path, e.message));
+        }
+    }
+
+    // Deriving the key is slow on purpose, so it is only done again if the salt changes
+    private void derive_key (uint8[] new_salt) throws StatisticsError {
+        if (key.length > 0 && Memory.cmp (salt, new_salt, new_salt.length) == 0) {
+            return;
+        }
+
+        if (Sodium.init () < 0) {
+            throw new StatisticsError.IO ("The encryption library cannot be initialized");
+        }
+
+        var new_key = new uint8[Sodium.SecretBox.KEY_BYTES];
+        uint8[] password = passphrase.data;
+        int result = Sodium.PasswordHash.derive_key (
+            new_key, new_key.length, password, password.length, new_salt,
+            Sodium.PasswordHash.OPS_LIMIT_INTERACTIVE, Sodium.PasswordHash.MEM_LIMIT_INTERACTIVE,
+            Sodium.PasswordHash.ALG_DEFAULT
+        );
+        if (result != 0) {
+            throw new StatisticsError.IO ("The encryption key cannot be derived");
+        }
+
+        salt = new_salt;
+        key = new_key;
+    }
+}
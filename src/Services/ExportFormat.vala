@@ -0,0 +1,13 @@
+/* ExportFormat.vala
+ *
+ * Copyright 2023 Diego Iván <diegoivan.mae@gmail.com>
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+// The formats the statistics can be exported in, to be read by other applications
+public enum Flowtime.Services.ExportFormat {
+    JSON,
+    CSV,
+    ICS
+}
@@ -0,0 +1,152 @@
+/* JsonBackend.vala
+ *
+ * Copyright 2023 Diego Iván <diegoivan.mae@gmail.com>
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+/*
+ * Writes the statistics document as JSON. Every element becomes an object in which attributes
+ * are members prefixed with "@", elements holding a single value become plain members and any
+ * other element is added to an array named after it. For example:
+ *
+ * { "statistics": { "@start": "...", "day": [ { "@date": "...", "worktime": 3600, ... } ] } }
+ */
+public class Flowtime.Services.JsonBackend : Object, StorageBackend {
+    private const string ROOT_NAME = "statistics";
+    // Only these elements are written as numbers, any other value is kept as the text it is
+    private const string[] NUMERIC_ELEMENTS = { "worktime", "breaktime", "pausetime", "breaks" };
+
+    public string filename {
+        get {
+            return "statistics.json";
+        }
+    }
+
+    public Xml.Doc* load (string path) throws StatisticsError {
+        var parser = new Json.Parser ();
+        try {
+            parser.load_from_file (path);
+        } catch (Error e) {
+            throw new StatisticsError.PARSE ("%s cannot be parsed: %s".printf (path, e.message));
+        }
+
+        unowned Json.Node? root = parser.get_root ();
+        if (root == null || root.get_node_type () != OBJECT) {
+            throw new StatisticsError.PARSE ("%s does not contain an object".printf (path));
+        }
+
+        unowned Json.Object root_object = root.get_object ();
+        if (!root_object.has_member (ROOT_NAME)) {
+            throw new StatisticsError.PARSE ("%s does not contain statistics".printf (path));
+        }
+
+        Xml.Doc* doc = new Xml.Doc ("1.0");
+        doc->set_root_element (object_to_element (root_object.get_object_member (ROOT_NAME), ROOT_NAME));
+        return doc;
+    }
+
+    public void save (Xml.Doc* doc, string path) throws StatisticsError {
+        var root_object = new Json.Object ();
+        root_object.set_object_member (ROOT_NAME, element_to_object (doc->get_root_element ()));
+
+        var root = new Json.Node (OBJECT);
+        root.set_object (root_object);
+
+        var generator = new Json.Generator () {
+            pretty = true,
+            root = root
+        };
+
+        try {
+            generator.to_file (path);
+        } catch (Error e) {
+            throw new StatisticsError.IO ("%s could not be written: %s".printf (path, e.message));
+        }
+    }
+
+    private Json.Object element_to_object (Xml.Node* element) {
+        var object = new Json.Object ();
+
+        for (Xml.Attr* attr = element->properties; attr != null; attr = attr->next) {
+            object.set_string_member ("@" + attr->name, element->get_prop (attr->name));
+        }
+
+        for (Xml.Node* child = element->children; child != null; child = child->next) {
+            if (child->type != ELEMENT_NODE) {
+                continue;
+            }
+
+            if (holds_value (child)) {
+                string content = child->get_content ();
+                int64 number;
+                if (child->name in NUMERIC_ELEMENTS && int64.try_parse (content.strip (), out number)) {
+                    object.set_int_member (child->name, number);
+                } else {
+                    object.set_string_member (child->name, content);
+                }
+                continue;
+            }
+
+            if (!object.has_member (child->name)) {
+                object.set_array_member (child->name, new Json.Array ());
+            }
+            object.get_array_member (child->name).add_object_element (element_to_object (child));
+        }
+
+        return object;
+    }
+
+    private Xml.Node* object_to_element (Json.Object object, string name) {
+        Xml.Node* element = new Xml.Node (null, name);
+
+        foreach (unowned string member in object.get_members ()) {
+            unowned Json.Node node = object.get_member (member);
+
+            if (member.has_prefix ("@")) {
+                element->new_prop (member.substring (1), value_to_string (node));
+                continue;
+            }
+
+            switch (node.get_node_type ()) {
+                case ARRAY:
+                    foreach (unowned Json.Node item in node.get_array ().get_elements ()) {
+                        if (item.get_node_type () == OBJECT) {
+                            element->add_child (object_to_element (item.get_object (), member));
+                        }
+                    }
+                    break;
+
+                case VALUE:
+                    element->new_text_child (null, member, value_to_string (node));
+                    break;
+
+                default:
+                    break;
+            }
+        }
+
+        return element;
+    }
+
+    // An element holds a single value if it has neither attributes nor elements inside
+    private bool holds_value (Xml.Node* element) {
+        if (element->properties != null) {
+            return false;
+        }
+
+        for (Xml.Node* child = element->children; child != null; child = child->next) {
+            if (child->type == ELEMENT_NODE) {
+                return false;
+            }
+        }
+        return true;
+    }
+
+    private string value_to_string (Json.Node node) {
+        if (node.get_value_type () == typeof (string)) {
+            return node.get_string ();
+        }
+        return Json.to_string (node, false);
+    }
+}
@@ -0,0 +1,64 @@
+/* PomodoroImporter.vala
+ *
+ * Copyright 2023 Diego Iván <diegoivan.mae@gmail.com>
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+/*
+ * Reads the statistics of GNOME Pomodoro, saved in the entries table of its SQLite database.
+ * The mapping is lossy: every pomodoro becomes a work session with the time that was actually
+ * spent in it, short and long breaks are both counted as breaks of the session before them, and
+ * the planned durations and pauses are not kept.
+ */
+public class Flowtime.Services.PomodoroImporter : Object {
+    private const string QUERY = """
+        SELECT datetime_string, state, elapsed FROM entries ORDER BY datetime_string;
+    """;
+
+    // The days found in the database, sorted from the oldest
+    public List<Models.Day> read_days (string path) throws StatisticsError {
+        Sqlite.Database db;
+        if (Sqlite.Database.open_v2 (path, out db, Sqlite.OPEN_READONLY) != Sqlite.OK) {
+            throw new StatisticsError.IO ("%s cannot be opened: %s".printf (path, db.errmsg ()));
+        }
+
+        Sqlite.Statement statement;
+        if (db.prepare_v2 (QUERY, -1, out statement) != Sqlite.OK) {
+            throw new StatisticsError.PARSE ("%s does not contain GNOME Pomodoro statistics: %s".printf (
+                path, db.errmsg ()
+            ));
+        }
+
+        var days = new List<Models.Day> ();
+        var days_table = new HashTable<string, Models.Day> (string.hash, str_equal);
+
+        while (statement.step () == Sqlite.ROW) {
+            DateTime? start = TimeUtils.parse_date (statement.column_text (0));
+            string state = statement.column_text (1);
+            int elapsed = (int) statement.column_int64 (2);
+            if (start == null || elapsed <= 0) {
+                continue;
+            }
+
+            string key = start.to_local ().format ("%F");
+            Models.Day? day = days_table[key];
+            if (day == null) {
+                day = new Models.Day.for_date (TimeUtils.normalize_to_local_midnight (start));
+                days_table[key] = day;
+                days.append (day);
+            }
+
+            if (state == "pomodoro") {
+                day.copy_session (new Models.Session (start, elapsed));
+                day.worktime += elapsed;
+            } else if (state == "short-break" || state == "long-break") {
+                day.add_session_break (elapsed);
+                day.breaktime += elapsed;
+                day.breaks++;
+            }
+        }
+
+        return days;
+    }
+}
@@ -0,0 +1,60 @@
+/* SampleData.vala
+ *
+ * Copyright 2023 Diego Iván <diegoivan.mae@gmail.com>
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+/*
+ * A made up history of a few months, to try Flowtime or take screenshots without real data. The
+ * same seed is always used, so the history is the same every time.
+ */
+public class Flowtime.Services.SampleData : Object {
+    private const uint32 SEED = 20230501;
+    private const int DAYS = 90;
+
+    public Xml.Doc* create_document () {
+        var rand = new Rand.with_seed (SEED);
+        var today = TimeUtils.normalize_to_local_midnight (new DateTime.now_local ());
+
+        Xml.Doc* doc = new Xml.Doc ("1.0");
+        Xml.Node* root = new Xml.Node (null, "statistics");
+        doc->set_root_element (root);
+
+        for (int i = DAYS - 1; i >= 0; i--) {
+            DateTime date = TimeUtils.normalize_to_local_midnight (today.add_days (-i));
+            bool weekend = date.get_day_of_week () > 5;
+
+            // Most weekends and a few weekdays are left without work
+            if (weekend ? rand.next_double () < 0.8 : rand.next_double () < 0.1) {
+                continue;
+            }
+
+            var day = new Models.Day.for_date (date);
+            DateTime start = date.add_minutes (rand.int_range (8 * 60, 10 * 60 + 30));
+
+            int sessions = rand.int_range (2, 6);
+            for (int j = 0; j < sessions; j++) {
+                int duration = rand.int_range (20, 91) * 60;
+                day.copy_session (new Models.Session (start, duration));
+                day.worktime += duration;
+
+                int break_seconds = Timer.suggested_break (duration);
+                if (rand.next_double () < 0.85) {
+                    day.add_session_break (break_seconds);
+                    day.breaktime += break_seconds;
+                    day.breaks++;
+                }
+
+                start = start.add_seconds (duration + break_seconds + rand.int_range (0, 30) * 60);
+            }
+
+            root->add_child (day.node);
+        }
+
+        root->set_prop ("start", today.add_days (1 - DAYS).format_iso8601 ());
+        root->set_prop ("version", "1");
+        root->set_prop ("milestone", "0");
+        return doc;
+    }
+}
@@ -91,7 +91,8 @@ public class Flowtime.Services.Screensaver : Object {
 
     private void timer_mode_changed () {
         var settings = new Settings ();
-        bool screensaver = settings.activate_screensaver && settings.autostart;
+        bool autostart = settings.autostart || settings.auto_start_break;
+        bool screensaver = settings.activate_screensaver && autostart;
 
         if (timer.mode == BREAK && screensaver && supported) {
             activate_screensaver.begin ();
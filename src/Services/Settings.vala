@@ -8,6 +8,7 @@
 [SingleInstance]
 public class Flowtime.Services.Settings : Object {
     private GLib.Settings settings = new GLib.Settings (Config.APP_ID);
+    private const int SETTINGS_VERSION = 1;
 
     private int _months_saved;
     public int months_saved {
@@ -37,10 +38,122 @@ public class Flowtime.Services.Settings : Object {
         }
     }
 
+    private int _daily_goal;
+    public int daily_goal {
+        get {
+            return _daily_goal;
+        }
+        set {
+            if (value < 1 || value > 1440) {
+                critical ("Daily goal is out of bounds");
+                return;
+            }
+            _daily_goal = value;
+        }
+    }
+
+    private int _weekly_goal;
+    public int weekly_goal {
+        get {
+            return _weekly_goal;
+        }
+        set {
+            if (value < 1 || value > 10080) {
+                critical ("Weekly goal is out of bounds");
+                return;
+            }
+            _weekly_goal = value;
+        }
+    }
+
+    private int _nudge_delay;
+    public int nudge_delay {
+        get {
+            return _nudge_delay;
+        }
+        set {
+            if (value < 0 || value > 60) {
+                critical ("Nudge delay is out of bounds");
+                return;
+            }
+            _nudge_delay = value;
+        }
+    }
+
+    public int min_break_seconds { get; set; }
+    public int max_break_seconds { get; set; }
+    public int active_threshold { get; set; }
+    public int focus_gap { get; set; }
+    public int work_hours_start { get; set; }
+    public int work_hours_end { get; set; }
+    public int afternoon_start { get; set; }
+    public int evening_start { get; set; }
+    public bool deep_work_only { get; set; }
+    public int deep_work_minutes { get; set; }
+    public int update_interval { get; set; }
+    public int tick_interval { get; set; }
+    public int session_grace { get; set; }
+    public int soft_cap { get; set; }
+    public bool soft_cap_notification { get; set; }
+    public int reminder_interval { get; set; }
+    public int firm_reminder_interval { get; set; }
+    public int reminder_escalation { get; set; }
+    public int max_day_worktime { get; set; }
+    public bool skip_out_of_range_days { get; set; }
+    public int non_work_days { get; set; }
+
+    private int _week_start;
+    public int week_start {
+        get {
+            return _week_start;
+        }
+        set {
+            if (value < 1 || value > 7) {
+                critical ("Week start is not a day of the week");
+                return;
+            }
+            _week_start = value;
+        }
+    }
+
+    public BreakPolicy break_policy { get; set; }
+    public BreakRounding break_rounding { get; set; }
+    public StorageFormat storage_format { get; set; }
+    public ProductiveDayMetric productive_day_metric { get; set; }
+    public Terminology terminology { get; set; }
+    public TimerDisplay timer_display { get; set; }
+    public StartupPhase startup_phase { get; set; }
+    public TimerMode last_mode { get; set; }
     public string tone { get; set; }
+    public string[] summary_metrics { get; set; }
+    public bool play_sounds { get; set; }
+    public double sound_volume { get; set; }
     public bool autostart { get; set; }
+    public bool auto_start_break { get; set; }
+    public bool confirm_break_start { get; set; }
+    public bool autostart_on_launch { get; set; }
     public bool distraction_free { get; set; }
     public bool activate_screensaver { get; set; }
+    public bool round_to_minutes { get; set; }
+    public bool bank_breaks { get; set; }
+    public bool split_at_midnight { get; set; }
+    public bool keep_empty_days { get; set; }
+    public bool streak_skip_weekends { get; set; }
+    public bool archive_old_data { get; set; }
+    public bool include_archive { get; set; }
+    public bool encrypt_statistics { get; set; }
+    public bool auto_export { get; set; }
+    public string auto_export_path { get; set; }
+    public ExportFormat auto_export_format { get; set; }
+    public string auto_export_error { get; set; }
+    public BackupSchedule backup_schedule { get; set; }
+    public string backup_path { get; set; }
+    public int backup_count { get; set; }
+    public string last_backup { get; set; }
+    public bool send_notifications { get; set; }
+    public bool daily_summary { get; set; }
+    public int summary_time { get; set; }
+    public bool summary_on_idle_days { get; set; }
 
     private Settings? instance = null;
     public Settings () {
@@ -50,14 +163,73 @@ public class Flowtime.Services.Settings : Object {
     }
 
     construct {
-        port_divisor ();
+        migrate_settings ();
 
         settings.bind ("tone", this, "tone", DEFAULT);
+        settings.bind ("summary-metrics", this, "summary-metrics", DEFAULT);
+        settings.bind ("play-sounds", this, "play-sounds", DEFAULT);
+        settings.bind ("sound-volume", this, "sound-volume", DEFAULT);
         settings.bind ("autostart", this, "autostart", DEFAULT);
+        settings.bind ("auto-start-break", this, "auto-start-break", DEFAULT);
+        settings.bind ("confirm-break-start", this, "confirm-break-start", DEFAULT);
+        settings.bind ("autostart-on-launch", this, "autostart-on-launch", DEFAULT);
+        settings.bind ("startup-phase", this, "startup-phase", DEFAULT);
+        settings.bind ("last-mode", this, "last-mode", DEFAULT);
         settings.bind ("distraction-free", this, "distraction-free", DEFAULT);
         settings.bind ("months-saved", this, "months-saved", DEFAULT);
+        settings.bind ("storage-format", this, "storage-format", DEFAULT);
+        settings.bind ("auto-export", this, "auto-export", DEFAULT);
+        settings.bind ("auto-export-path", this, "auto-export-path", DEFAULT);
+        settings.bind ("auto-export-format", this, "auto-export-format", DEFAULT);
+        settings.bind ("auto-export-error", this, "auto-export-error", DEFAULT);
+        settings.bind ("backup-schedule", this, "backup-schedule", DEFAULT);
+        settings.bind ("backup-path", this, "backup-path", DEFAULT);
+        settings.bind ("backup-count", this, "backup-count", DEFAULT);
+        settings.bind ("last-backup", this, "last-backup", DEFAULT);
+        settings.bind ("productive-day-metric", this, "productive-day-metric", DEFAULT);
+        settings.bind ("terminology", this, "terminology", DEFAULT);
+        settings.bind ("timer-display", this, "timer-display", DEFAULT);
         settings.bind ("break-percentage", this, "break-percentage", DEFAULT);
+        settings.bind ("break-policy", this, "break-policy", DEFAULT);
+        settings.bind ("break-rounding", this, "break-rounding", DEFAULT);
+        settings.bind ("min-break-seconds", this, "min-break-seconds", DEFAULT);
+        settings.bind ("max-break-seconds", this, "max-break-seconds", DEFAULT);
         settings.bind ("activate-screensaver", this, "activate-screensaver", DEFAULT);
+        settings.bind ("round-to-minutes", this, "round-to-minutes", DEFAULT);
+        settings.bind ("daily-goal", this, "daily-goal", DEFAULT);
+        settings.bind ("weekly-goal", this, "weekly-goal", DEFAULT);
+        settings.bind ("active-threshold", this, "active-threshold", DEFAULT);
+        settings.bind ("focus-gap", this, "focus-gap", DEFAULT);
+        settings.bind ("work-hours-start", this, "work-hours-start", DEFAULT);
+        settings.bind ("work-hours-end", this, "work-hours-end", DEFAULT);
+        settings.bind ("afternoon-start", this, "afternoon-start", DEFAULT);
+        settings.bind ("evening-start", this, "evening-start", DEFAULT);
+        settings.bind ("deep-work-only", this, "deep-work-only", DEFAULT);
+        settings.bind ("deep-work-minutes", this, "deep-work-minutes", DEFAULT);
+        settings.bind ("update-interval", this, "update-interval", DEFAULT);
+        settings.bind ("tick-interval", this, "tick-interval", DEFAULT);
+        settings.bind ("session-grace", this, "session-grace", DEFAULT);
+        settings.bind ("max-day-worktime", this, "max-day-worktime", DEFAULT);
+        settings.bind ("skip-out-of-range-days", this, "skip-out-of-range-days", DEFAULT);
+        settings.bind ("week-start", this, "week-start", DEFAULT);
+        settings.bind ("bank-breaks", this, "bank-breaks", DEFAULT);
+        settings.bind ("split-at-midnight", this, "split-at-midnight", DEFAULT);
+        settings.bind ("keep-empty-days", this, "keep-empty-days", DEFAULT);
+        settings.bind ("streak-skip-weekends", this, "streak-skip-weekends", DEFAULT);
+        settings.bind ("non-work-days", this, "non-work-days", DEFAULT);
+        settings.bind ("archive-old-data", this, "archive-old-data", DEFAULT);
+        settings.bind ("include-archive", this, "include-archive", DEFAULT);
+        settings.bind ("encrypt-statistics", this, "encrypt-statistics", DEFAULT);
+        settings.bind ("send-notifications", this, "send-notifications", DEFAULT);
+        settings.bind ("nudge-delay", this, "nudge-delay", DEFAULT);
+        settings.bind ("soft-cap", this, "soft-cap", DEFAULT);
+        settings.bind ("soft-cap-notification", this, "soft-cap-notification", DEFAULT);
+        settings.bind ("reminder-interval", this, "reminder-interval", DEFAULT);
+        settings.bind ("firm-reminder-interval", this, "firm-reminder-interval", DEFAULT);
+        settings.bind ("reminder-escalation", this, "reminder-escalation", DEFAULT);
+        settings.bind ("daily-summary", this, "daily-summary", DEFAULT);
+        settings.bind ("summary-time", this, "summary-time", DEFAULT);
+        settings.bind ("summary-on-idle-days", this, "summary-on-idle-days", DEFAULT);
 
         settings.delay ();
     }
@@ -66,6 +238,41 @@ public class Flowtime.Services.Settings : Object {
         settings.apply ();
     }
 
+    /*
+     * Stores whether the statistics are encrypted at once, instead of at shutdown, as the old file
+     * of the statistics is removed right after.
+     */
+    public void store_encrypt_statistics (bool encrypt) {
+        encrypt_statistics = encrypt;
+        settings.apply ();
+        GLib.Settings.sync ();
+    }
+
+    // Whether the date is one of the non-work days, if they are skipped
+    public bool is_non_work_day (DateTime date) {
+        if (!streak_skip_weekends) {
+            return false;
+        }
+        return (non_work_days & (1 << (date.get_day_of_week () - 1))) != 0;
+    }
+
+    /*
+     * Moves the values of keys from older versions to the keys that replaced them, so users do not
+     * lose their preferences after an upgrade. Each step runs only once.
+     */
+    private void migrate_settings () {
+        int version = settings.get_int ("settings-version");
+        if (version >= SETTINGS_VERSION) {
+            return;
+        }
+
+        if (version < 1) {
+            port_divisor ();
+        }
+
+        settings.set_int ("settings-version", SETTINGS_VERSION);
+    }
+
     private void port_divisor () {
         int divisor = settings.get_int ("break-divisor");
         if (divisor < 0) {
@@ -0,0 +1,64 @@
+/* SleepMonitor.vala
+ *
+ * Copyright 2023 Diego Iván <diegoivan.mae@gmail.com>
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+/*
+ * Pauses the timer while the system is suspended, so the time the computer was sleeping
+ * is not counted as work or break.
+ */
+public class Flowtime.Services.SleepMonitor : Object {
+    private const string BUS_NAME = "org.freedesktop.login1";
+    private const string OBJECT_PATH = "/org/freedesktop/login1";
+    private const string INTERFACE_NAME = "org.freedesktop.login1.Manager";
+    private const string SIGNAL_NAME = "PrepareForSleep";
+
+    private DBusConnection? connection = null;
+    private uint subscription_id = 0;
+    private bool resume_on_wake = false;
+
+    public Timer timer { get; construct; }
+
+    public async SleepMonitor (Timer timer) throws IOError {
+        Object (timer: timer);
+
+        connection = yield Bus.get (SYSTEM, null);
+        subscription_id = connection.signal_subscribe (BUS_NAME, INTERFACE_NAME, SIGNAL_NAME,
+                                                       OBJECT_PATH, null, NONE, on_prepare_for_sleep);
+    }
+
+    ~SleepMonitor () {
+        if (subscription_id != 0) {
+            connection.signal_unsubscribe (subscription_id);
+        }
+    }
+
+    private void on_prepare_for_sleep (DBusConnection connection, string? sender_name,
+                                       string object_path, string interface_name,
+                                       string signal_name, Variant parameters) {
+        bool sleeping;
+        parameters.get ("(b)", out sleeping);
+
+        if (sleeping) {
+            on_sleep ();
+        } else {
+            on_wake ();
+        }
+    }
+
+    public void on_sleep () {
+        resume_on_wake = timer.running;
+        if (resume_on_wake) {
+            timer.suspend ();
+        }
+    }
+
+    public void on_wake () {
+        if (resume_on_wake) {
+            timer.start ();
+        }
+        resume_on_wake = false;
+    }
+}
@@ -0,0 +1,185 @@
+/* SqliteBackend.vala
+ *
+ * Copyright 2023 Diego Iván <diegoivan.mae@gmail.com>
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+/*
+ * Keeps every element of the statistics in its own row. Days are indexed by the unix time of
+ * their date, so ranges of days can be queried without reading the whole history.
+ */
+public class Flowtime.Services.SqliteBackend : Object, StorageBackend {
+    private const string ROOT_NAME = "statistics";
+
+    private const string SCHEMA = """
+        CREATE TABLE IF NOT EXISTS properties (name TEXT PRIMARY KEY, value TEXT NOT NULL);
+        CREATE TABLE IF NOT EXISTS elements (
+            day INTEGER,
+            worktime INTEGER NOT NULL DEFAULT 0,
+            content TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS elements_day ON elements (day);
+    """;
+
+    public string filename {
+        get {
+            return "statistics.db";
+        }
+    }
+
+    public Xml.Doc* load (string path) throws StatisticsError {
+        Sqlite.Database db = open_database (path);
+        Sqlite.Statement statement = prepare (db, "SELECT content FROM elements ORDER BY day;");
+        return read_document (db, statement, path);
+    }
+
+    /*
+     * Reads the days dated on or after start and before end, or with no end if it is null, using
+     * the index of the days. The elements that are not dated days are always read, so they are not
+     * lost once the document is saved again.
+     */
+    public Xml.Doc* load_days_between (string path, DateTime start, DateTime? end = null)
+                                       throws StatisticsError {
+        Sqlite.Database db = open_database (path);
+        Sqlite.Statement statement = prepare (db,
+            "SELECT content FROM elements WHERE day IS NULL OR (day >= ? AND day < ?) ORDER BY day;"
+        );
+        statement.bind_int64 (1, start.to_unix ());
+        statement.bind_int64 (2, end != null ? end.to_unix () : int64.MAX);
+        return read_document (db, statement, path);
+    }
+
+    public void save (Xml.Doc* doc, string path) throws StatisticsError {
+        Sqlite.Database db = open_database (path);
+        execute (db, "BEGIN TRANSACTION;");
+
+        // Nothing is written unless every row is, the database keeps the statistics it had before
+        try {
+            write_document (db, doc);
+            execute (db, "COMMIT;");
+        } catch (StatisticsError e) {
+            db.exec ("ROLLBACK;");
+            throw e;
+        }
+    }
+
+    /*
+     * Imports the statistics saved in the XML file at xml_path into a new database at path. It is
+     * only done once, an existing database is never written over.
+     */
+    public void import_xml (string xml_path, string path) throws StatisticsError {
+        if (FileUtils.test (path, EXISTS)) {
+            throw new StatisticsError.NOT_EMPTY ("%s already exists, nothing is imported".printf (path));
+        }
+
+        Xml.Doc* doc = new XmlBackend ().load (xml_path);
+        try {
+            save (doc, path);
+        } catch (StatisticsError e) {
+            // An empty database would be read instead of the XML file from now on
+            FileUtils.remove (path);
+            throw e;
+        } finally {
+            delete doc;
+        }
+    }
+
+    private Xml.Doc* read_document (Sqlite.Database db, Sqlite.Statement statement, string path)
+                                    throws StatisticsError {
+        var builder = new StringBuilder ("<%s>".printf (ROOT_NAME));
+        while (statement.step () == Sqlite.ROW) {
+            builder.append (statement.column_text (0));
+        }
+        builder.append ("</%s>".printf (ROOT_NAME));
+
+        Xml.Doc* doc = Xml.Parser.parse_memory (builder.str, (int) builder.len);
+        if (doc == null) {
+            throw new StatisticsError.PARSE ("%s contains elements that cannot be parsed".printf (path));
+        }
+
+        Xml.Node* root = doc->get_root_element ();
+        Sqlite.Statement properties = prepare (db, "SELECT name, value FROM properties;");
+        while (properties.step () == Sqlite.ROW) {
+            root->set_prop (properties.column_text (0), properties.column_text (1));
+        }
+
+        return doc;
+    }
+
+    private void write_document (Sqlite.Database db, Xml.Doc* doc) throws StatisticsError {
+        execute (db, "DELETE FROM properties; DELETE FROM elements;");
+
+        Xml.Node* root = doc->get_root_element ();
+
+        Sqlite.Statement statement = prepare (db, "INSERT INTO properties VALUES (?, ?);");
+        for (Xml.Attr* attr = root->properties; attr != null; attr = attr->next) {
+            statement.bind_text (1, attr->name);
+            statement.bind_text (2, root->get_prop (attr->name));
+            step (db, statement);
+        }
+
+        statement = prepare (db, "INSERT INTO elements VALUES (?, ?, ?);");
+        for (Xml.Node* child = root->children; child != null; child = child->next) {
+            if (child->type != ELEMENT_NODE) {
+                continue;
+            }
+
+            // A day with an invalid date is kept without an index, so it is not lost
+            DateTime? day = TimeUtils.parse_date (child->get_prop ("date"));
+            if (child->name == "day" && day != null) {
+                statement.bind_int64 (1, day.to_unix ());
+                statement.bind_int (2, child_int (child, "worktime"));
+            } else {
+                statement.bind_null (1);
+                statement.bind_int (2, 0);
+            }
+
+            var buffer = new Xml.Buffer ();
+            buffer.node_dump (doc, child, 0, 0);
+            statement.bind_text (3, buffer.content ());
+            step (db, statement);
+        }
+    }
+
+    private Sqlite.Database open_database (string path) throws StatisticsError {
+        Sqlite.Database db;
+        if (Sqlite.Database.open_v2 (path, out db) != Sqlite.OK) {
+            throw new StatisticsError.IO ("%s cannot be opened: %s".printf (path, db.errmsg ()));
+        }
+
+        execute (db, SCHEMA);
+        return db;
+    }
+
+    private void execute (Sqlite.Database db, string sql) throws StatisticsError {
+        string error_message;
+        if (db.exec (sql, null, out error_message) != Sqlite.OK) {
+            throw new StatisticsError.IO ("SQLite error: %s".printf (error_message));
+        }
+    }
+
+    private Sqlite.Statement prepare (Sqlite.Database db, string sql) throws StatisticsError {
+        Sqlite.Statement statement;
+        if (db.prepare_v2 (sql, -1, out statement) != Sqlite.OK) {
+            throw new StatisticsError.IO ("SQLite error: %s".printf (db.errmsg ()));
+        }
+        return statement;
+    }
+
+    private void step (Sqlite.Database db, Sqlite.Statement statement) throws StatisticsError {
+        if (statement.step () != Sqlite.DONE) {
+            throw new StatisticsError.IO ("SQLite error: %s".printf (db.errmsg ()));
+        }
+        statement.reset ();
+    }
+
+    private int child_int (Xml.Node* element, string name) {
+        for (Xml.Node* child = element->children; child != null; child = child->next) {
+            if (child->type == ELEMENT_NODE && child->name == name) {
+                return XmlUtils.parse_int_content (child);
+            }
+        }
+        return 0;
+    }
+}
@@ -7,30 +7,139 @@
 
 using Flowtime.Models;
 
+public errordomain Flowtime.Services.StatisticsError {
+    PARSE,
+    IO,
+    NEWER_VERSION,
+    READ_ONLY,
+    NOT_EMPTY,
+    MERGE_CONFLICT,
+    NOT_FOUND,
+    INVALID_ARGUMENT
+}
+
 [SingleInstance]
 public class Flowtime.Services.Statistics : GLib.Object {
-    private Xml.Doc* doc = new Xml.Doc ("1.0");
+    private Xml.Doc* doc = null;
     private Xml.Node* root_element;
+    // The statistics put aside while the sample history is shown
+    private Xml.Doc* real_doc = null;
+    private bool real_read_only = false;
+    private StorageBackend backend;
+
+    // Version of the format of the statistics, written to the version attribute of the document
+    private const int FILE_VERSION = 1;
+
+    private const int MONTHLY_FREEZES = 2;
+    private const int MIN_START_DAYS = 3;
+    private const int DEFAULT_AFTERNOON_START = 12;
+    private const int DEFAULT_EVENING_START = 18;
+
+    private const int WEEK_DAYS = 7;
+    private const int MONTH_DAYS = 30;
+
+    private const string BACKUP_PREFIX = "flowtime-backup-";
+    private const uint BACKUP_CHECK_SECONDS = 24 * 60 * 60;
+
+    private bool holds_lock = false;
+    private bool lock_failed = false;
+    private uint? updated_timeout_id = null;
+    private int64 last_updated_time = 0;
+
+    private Xml.Doc* archive_doc = null;
+    private Xml.Node* archive_root;
+    // Set when the archive file cannot be parsed, so it is never written over
+    private bool archive_unreadable = false;
 
     private string path {
         owned get {
-            return Path.build_filename (Environment.get_user_data_dir (), "statistics.xml");
+            return path_for_backend (backend);
+        }
+    }
+
+    private string lock_path {
+        owned get {
+            return Path.build_filename (Environment.get_user_data_dir (), "statistics.lock");
+        }
+    }
+
+    private string archive_path {
+        owned get {
+            return Path.build_filename (Environment.get_user_data_dir (), "statistics-archive.xml");
         }
     }
 
     public Day? today { get; private set; default = null; }
 
+    public int today_worktime {
+        get {
+            return today != null ? today.counted_worktime () : 0;
+        }
+    }
+
+    public int today_breaktime {
+        get {
+            return today != null ? today.breaktime : 0;
+        }
+    }
+
+    public string today_worktime_display {
+        owned get {
+            return TimeUtils.format_time (today_worktime);
+        }
+    }
+
     public List<Day> all_days = new List<Day> ();
+    public List<Day> archived_days = new List<Day> ();
+    private ListStore days_store = new ListStore (typeof (Day));
     public string productive_day { get; private set; }
 
     public State total = new State ();
     public State month = new State ();
     public State week = new State ();
+    // The counted time of today, which may be less than the time saved in the day
+    public State today_period = new State ();
+
+    // The reason the statistics file could not be read, if it failed
+    public string? load_error { get; private set; default = null; }
+    /*
+     * Set when the statistics were written by a newer version of Flowtime, or another instance is
+     * saving them. They are shown, but never saved, so the data on disk is not overwritten.
+     */
+    public bool read_only { get; private set; default = false; }
+    // Set while the statistics are encrypted and the passphrase has not been given yet
+    public bool locked { get; private set; default = false; }
+    // Set while the sample history is shown instead of the statistics, which are never saved then
+    public bool sample_mode { get; private set; default = false; }
 
     public signal void updated ();
+    public signal void day_changed ();
+    public signal void data_error (string message);
+    // Emitted with the time the statistics were saved, once they have been written
+    public signal void saved (DateTime time);
+    // Emitted once when the total worktime reaches one of the milestones, in hours
+    public signal void milestone_reached (uint hours);
+    public signal void streak_changed (StreakInfo info);
+    // Emitted once a week, when the worktime of the week reaches the weekly goal
+    public signal void weekly_goal_reached ();
+
+    private const uint[] MILESTONE_HOURS = { 10, 50, 100, 250, 500, 1000, 2500, 5000, 10000 };
+    private const uint[] STREAK_MILESTONES = { 7, 14, 30, 60, 100, 365 };
+    private uint[] session_milestones = {};
+    private StreakInfo? last_streak_info = null;
 
     ~Statistics () {
+        if (holds_lock) {
+            FileUtils.remove (lock_path);
+        }
+
         delete doc;
+        if (real_doc != null) {
+            delete real_doc;
+        }
+        if (archive_doc != null) {
+            delete archive_doc;
+        }
     }
 
     private static Statistics? instance = null;
@@ -41,40 +150,370 @@ public class Flowtime.Services.Statistics : GLib.Object {
     }
 
     construct {
+        var settings = new Settings ();
+        backend = settings.storage_format.create_backend ();
+        settings.notify["storage-format"].connect (on_storage_format_changed);
+        settings.notify["include-archive"].connect (on_include_archive_changed);
+        settings.notify["productive-day-metric"].connect (() => {
+            get_most_productive_day ();
+            updated ();
+        });
+        settings.notify["deep-work-only"].connect (recompute);
+        settings.notify["deep-work-minutes"].connect (recompute);
+        settings.notify["max-day-worktime"].connect (recompute);
+
+        create_document ();
+        notify["today"].connect (notify_today);
+        updated.connect (check_streak);
+        day_changed.connect (() => {
+            refill_freezes ();
+            check_streak ();
+        });
+
+        if (!acquire_lock ()) {
+            warning ("The statistics are being used by another instance of Flowtime. They will not be saved");
+            lock_failed = true;
+            read_only = true;
+            load_error = _("The statistics are being used by another instance of Flowtime");
+        }
+
+        // Flowtime may be left open for days, so the backups are checked every day too
+        Timeout.add_seconds (BACKUP_CHECK_SECONDS, () => {
+            check_backup ();
+            return Source.CONTINUE;
+        });
+
+        // Encrypted statistics are only read once the passphrase is given to unlock ()
+        if (settings.encrypt_statistics || find_stored_backend () is EncryptedBackend) {
+            if (!settings.encrypt_statistics) {
+                warning ("Only encrypted statistics were found, they are opened as such");
+                settings.store_encrypt_statistics (true);
+            }
+            locked = true;
+            read_only = true;
+            setup_new_statistics_file ();
+            append_day (today);
+            return;
+        }
+
+        retrieve_statistics.begin ();
+        check_backup ();
+    }
+
+    /*
+     * Only one instance of Flowtime may save the statistics. The lock file holds the host and the
+     * process of the instance that saves them, and a lock left behind by a process that is no
+     * longer running is taken over.
+     */
+    private bool acquire_lock () {
+        string host = Environment.get_host_name ();
+        int pid;
+        try {
+            pid = new Credentials ().get_unix_pid ();
+        } catch (Error e) {
+            warning ("The process cannot be identified: %s", e.message);
+            return true;
+        }
+
+        try {
+            string contents;
+            FileUtils.get_contents (lock_path, out contents);
+
+            string[] owner = contents.strip ().split (":");
+            bool owner_running = owner.length == 2
+                                 && owner[0] == host
+                                 && int.parse (owner[1]) != pid
+                                 && FileUtils.test ("/proc/%s".printf (owner[1]), EXISTS);
+            if (owner_running) {
+                return false;
+            }
+        } catch (FileError e) {
+            // There is no lock yet
+        }
+
+        try {
+            DirUtils.create_with_parents (Environment.get_user_data_dir (), 0755);
+            FileUtils.set_contents (lock_path, "%s:%d".printf (host, pid));
+        } catch (FileError e) {
+            warning ("The statistics cannot be locked: %s", e.message);
+            return true;
+        }
+
+        holds_lock = true;
+        return true;
+    }
+
+    private void check_writable () throws StatisticsError {
+        if (read_only) {
+            throw new StatisticsError.READ_ONLY ("The statistics are read-only");
+        }
+    }
+
+    private void create_document () {
+        if (doc != null) {
+            delete doc;
+        }
+
+        doc = new Xml.Doc ("1.0");
         root_element = new Xml.Node (null, "statistics");
         doc->set_root_element (root_element);
+
         Xml.Node* comment = new Xml.Node.comment ("GENERATED BY FLOWTIME : DO NOT MODIFY");
         root_element->add_child (comment);
+    }
 
-        retrieve_statistics.begin ();
+    private string path_for_backend (StorageBackend storage) {
+        return Path.build_filename (Environment.get_user_data_dir (), storage.filename);
     }
 
     private async void retrieve_statistics () {
-        var file = File.new_for_path (path);
+        StorageBackend? source = find_stored_backend ();
 
-        if (!file.query_exists ()) {
+        // An empty file is treated like a missing one, there is nothing in it to be lost
+        if (source == null || is_blank_file (path_for_backend (source))) {
             setup_new_statistics_file ();
+            append_day (today);
             save ();
             return;
         }
 
-        delete doc;
+        // Statistics saved as XML are imported into a new database once, only the database is read after
+        if (source is XmlBackend && backend is SqliteBackend && !read_only) {
+            try {
+                ((SqliteBackend) backend).import_xml (path_for_backend (source), path);
+                FileUtils.remove (path_for_backend (source));
+                source = backend;
+            } catch (StatisticsError e) {
+                warning ("%s. The statistics are not imported into the database yet", e.message);
+            }
+        }
 
-        doc = Xml.Parser.parse_file (path);
-        if (doc == null) {
-            warning ("Statistics file was found, but cannot be parsed. Creating a new one");
-            setup_new_statistics_file ();
+        string source_path = path_for_backend (source);
+        Xml.Doc* conflicting = null;
+
+        try {
+            Xml.Doc* loaded;
+            try {
+                loaded = load_from (source, source_path);
+            } catch (StatisticsError.MERGE_CONFLICT e) {
+                warning ("%s. Merging both versions", e.message);
+                loaded = load_conflict (source_path, out conflicting);
+            }
+            delete doc;
+            doc = loaded;
+        } catch (StatisticsError e) {
+            warning ("%s. Creating a new statistics file", e.message);
+            load_error = e.message;
+            data_error (e.message);
 
-            doc = new Xml.Doc ();
-            doc->set_root_element (root_element);
+            create_document ();
+            setup_new_statistics_file ();
+            source = backend;
         }
 
         root_element = doc->get_root_element ();
+
+        try {
+            check_version ();
+        } catch (StatisticsError e) {
+            warning ("%s. The statistics will not be saved", e.message);
+            read_only = true;
+            load_error = e.message;
+            data_error (e.message);
+        }
+
         retrieve_days ();
+
+        // A read-only file is left with the conflict, to be merged by an instance that can save it
+        if (conflicting != null && read_only) {
+            warning ("The statistics have merge conflicts, only our version is shown");
+            delete conflicting;
+        } else if (conflicting != null) {
+            merge_conflict (conflicting, source_path);
+        }
+
+        // The file was saved in a different format, the statistics are moved to the selected one
+        if (source != backend) {
+            StorageBackend selected = backend;
+            backend = source;
+            save_migrated (selected, source_path);
+        }
+    }
+
+    /*
+     * The days older than the months saved are dropped once the days are retrieved, so they are
+     * not read from a database unless they are archived. The index of the days is used instead of
+     * reading the whole history.
+     */
+    private Xml.Doc* load_from (StorageBackend source, string source_path) throws StatisticsError {
+        var settings = new Settings ();
+        var sqlite = source as SqliteBackend;
+        if (sqlite == null || settings.archive_old_data) {
+            return source.load (source_path);
+        }
+
+        int kept_days = MONTH_DAYS * settings.months_saved - 1;
+        var oldest = TimeUtils.normalize_to_local_midnight (new DateTime.now_local ().add_days (-kept_days));
+        return sqlite.load_days_between (source_path, oldest);
+    }
+
+    // A file with only white space, or a byte order mark, has no statistics
+    private bool is_blank_file (string file_path) {
+        string contents;
+        try {
+            FileUtils.get_contents (file_path, out contents);
+        } catch (FileError e) {
+            return false;
+        }
+
+        if (contents.has_prefix ("\xEF\xBB\xBF")) {
+            contents = contents.substring (3);
+        }
+        return contents.strip () == "";
+    }
+
+    // Reads our version of a file with merge conflicts, and their version into theirs
+    private Xml.Doc* load_conflict (string file_path, out Xml.Doc* theirs) throws StatisticsError {
+        theirs = null;
+
+        string contents;
+        try {
+            FileUtils.get_contents (file_path, out contents);
+        } catch (FileError e) {
+            throw new StatisticsError.IO ("%s cannot be read: %s".printf (file_path, e.message));
+        }
+
+        var xml_backend = new XmlBackend ();
+        string ours_contents, theirs_contents;
+        xml_backend.split_conflict (contents, out ours_contents, out theirs_contents);
+
+        Xml.Doc* ours = xml_backend.parse (ours_contents, file_path);
+        try {
+            theirs = xml_backend.parse (theirs_contents, file_path);
+        } catch (StatisticsError e) {
+            delete ours;
+            throw e;
+        }
+        return ours;
+    }
+
+    /*
+     * Merges their version into the statistics. The original file is kept next to it only once the
+     * merged statistics were saved, so there is always a statistics file to be read.
+     */
+    private void merge_conflict (Xml.Doc* theirs, string file_path) {
+        string original;
+        try {
+            FileUtils.get_contents (file_path, out original);
+        } catch (FileError e) {
+            warning ("The statistics with conflicts cannot be read again: %s", e.message);
+            delete theirs;
+            return;
+        }
+
+        var report = new MergeReport ();
+        try {
+            merge_document (theirs, report);
+        } catch (StatisticsError e) {
+            warning ("The conflicting statistics cannot be merged: %s", e.message);
+        }
+        delete theirs;
+
+        recompute ();
+        try {
+            write_document (backend, path);
+            FileUtils.set_contents (file_path + ".conflict", original);
+        } catch (Error e) {
+            critical ("The merged statistics could not be saved: %s", e.message);
+            return;
+        }
+
+        data_error (_("Your statistics had conflicting changes, both versions were merged. The original file was kept as %s").printf (
+            file_path + ".conflict"
+        ));
+    }
+
+    private void check_version () throws StatisticsError {
+        string? version_prop = root_element->get_prop ("version");
+        // Files written before the version was recorded are the first version
+        int version = version_prop != null ? int.parse (version_prop) : 1;
+
+        if (version > FILE_VERSION) {
+            throw new StatisticsError.NEWER_VERSION (
+                "The statistics file has version %d, but only up to version %d is supported".printf (
+                    version, FILE_VERSION
+                )
+            );
+        }
+
+        root_element->set_prop ("version", FILE_VERSION.to_string ());
+    }
+
+    /*
+     * The backend whose file holds the statistics, preferring the selected format. It is null in
+     * case no statistics have been saved yet.
+     */
+    private StorageBackend? find_stored_backend () {
+        if (FileUtils.test (path, EXISTS)) {
+            return backend;
+        }
+
+        StorageFormat[] formats = { XML, JSON, SQLITE };
+        foreach (StorageFormat format in formats) {
+            StorageBackend storage = format.create_backend ();
+            if (FileUtils.test (path_for_backend (storage), EXISTS)) {
+                return storage;
+            }
+        }
+
+        // Without the passphrase the backend can only tell that there are encrypted statistics
+        var encrypted = new EncryptedBackend ("");
+        if (FileUtils.test (path_for_backend (encrypted), EXISTS)) {
+            return encrypted;
+        }
+
+        return null;
+    }
+
+    private void on_storage_format_changed () {
+        var settings = new Settings ();
+        // Encrypted statistics are always saved as XML inside the encrypted file
+        if (settings.encrypt_statistics) {
+            return;
+        }
+
+        StorageBackend selected = settings.storage_format.create_backend ();
+        if (path_for_backend (selected) == path) {
+            backend = selected;
+            return;
+        }
+        save_migrated (selected, path);
+    }
+
+    /*
+     * Saves the statistics with the new backend and removes the file at old_path. The backend is
+     * only switched once the statistics are saved, so they keep being read from a file that exists.
+     */
+    private void save_migrated (StorageBackend new_backend, string old_path) {
+        if (read_only) {
+            return;
+        }
+
+        try {
+            write_document (new_backend, path_for_backend (new_backend));
+        } catch (StatisticsError e) {
+            critical ("Statistics could not be migrated: %s", e.message);
+            return;
+        }
+
+        backend = new_backend;
+        FileUtils.remove (old_path);
     }
 
     private void setup_new_statistics_file () {
         root_element->new_prop ("start", new DateTime.now_utc ().format_iso8601 ());
+        root_element->new_prop ("version", FILE_VERSION.to_string ());
+        root_element->new_prop ("milestone", "0");
         today = new Day ();
         root_element->add_child (today.node);
     }
@@ -86,38 +525,44 @@ public class Flowtime.Services.Statistics : GLib.Object {
 
         int months_saved = settings.months_saved;
         Day[] overpassed_days = {};
-
-        const TimeSpan WEEK = TimeSpan.DAY * 7;
-        const TimeSpan MONTH = TimeSpan.DAY * 30;
+        Day[] out_of_range_days = {};
+        Day[] empty_days = {};
+        int invalid_days = 0;
 
         for (Xml.Node* i = root_element->children; i != null; i = i->next) {
             if (i->type == ELEMENT_NODE) {
                 var d = new Day.from_xml (XmlUtils.get_content_node (i, "day"));
-                TimeSpan ts = current_date.difference (d.date);
-
-                if (ts > MONTH * months_saved) {
-                    overpassed_days += d;
+                // Days with an invalid date stay saved as they are, until they are repaired
+                if (!d.valid_date) {
+                    invalid_days++;
                     continue;
                 }
 
-                total.worktime += d.worktime;
-                total.breaktime += d.breaktime;
-                all_days.append (d);
-
-                if (ts > MONTH) {
+                if (!settings.keep_empty_days && is_empty_day (d)
+                    && !TimeUtils.same_day (d.date, current_date)) {
+                    empty_days += d;
                     continue;
                 }
 
-                month.worktime += d.worktime;
-                month.breaktime += d.breaktime;
-                if (ts > WEEK) {
+                // The saved worktime is left as it is, only the counted worktime is clamped
+                if (!worktime_in_range (d)) {
+                    out_of_range_days += d;
+                    if (settings.skip_out_of_range_days) {
+                        continue;
+                    }
+                }
+
+                int days = TimeUtils.days_between (d.date, current_date);
+
+                if (days >= MONTH_DAYS * months_saved) {
+                    overpassed_days += d;
                     continue;
                 }
 
-                week.worktime += d.worktime;
-                week.breaktime += d.breaktime;
+                add_to_periods (d, days);
+                append_day (d);
 
-                if (d.date.get_day_of_year () == current_date.get_day_of_year ()) {
+                if (TimeUtils.same_day (d.date, current_date)) {
                     today = d;
                 }
             }
@@ -126,123 +571,2495 @@ public class Flowtime.Services.Statistics : GLib.Object {
         if (today == null) {
             today = new Day ();
             root_element->add_child (today.node);
-            all_days.append(today);
+            append_day (today);
         }
 
-        foreach (var day in overpassed_days) {
+        if (settings.archive_old_data) {
+            load_archive ();
+        }
+
+        foreach (var day in empty_days) {
             day.unlink ();
         }
 
+        foreach (var day in out_of_range_days) {
+            warning ("The worktime of %s is out of range", day.date.format ("%F"));
+        }
+        if (invalid_days > 0) {
+            data_error (ngettext (
+                "%d day has an invalid date and was left out",
+                "%d days have an invalid date and were left out",
+                invalid_days
+            ).printf (invalid_days));
+        }
+
+        if (out_of_range_days.length > 0) {
+            data_error (ngettext (
+                "%d day had a worktime out of range",
+                "%d days had a worktime out of range",
+                out_of_range_days.length
+            ).printf (out_of_range_days.length));
+        }
+
+        // The old days stay in the statistics file while the archive cannot be written
+        foreach (var day in overpassed_days) {
+            if (!settings.archive_old_data) {
+                day.unlink ();
+            } else if (!archive_unreadable) {
+                archive_day (day);
+            }
+        }
+
+        if (settings.archive_old_data && overpassed_days.length > 0) {
+            save_archive ();
+        }
+
+        if (settings.include_archive) {
+            add_archive_to_total ();
+        }
         get_most_productive_day ();
+
+        // Files saved before milestones existed do not celebrate the ones reached long ago
+        if (root_element->get_prop ("milestone") == null) {
+            root_element->set_prop ("milestone", highest_milestone (total.worktime).to_string ());
+        }
     }
 
-    public void add_time_to_mode (TimerMode mode, int time_seconds) {
-        switch (mode) {
-            case WORK:
-                today.worktime += time_seconds;
-                week.worktime += time_seconds;
-                month.worktime += time_seconds;
-                total.worktime += time_seconds;
-                break;
+    /*
+     * A corrupt file could have a worktime that would make every average meaningless, so it is
+     * checked against the longest worktime a day can have.
+     */
+    private bool worktime_in_range (Day day) {
+        var settings = new Settings ();
+        return day.worktime >= 0 && day.worktime <= settings.max_day_worktime * 3600;
+    }
 
-            case BREAK:
-                today.breaktime += time_seconds;
-                week.breaktime += time_seconds;
-                month.breaktime += time_seconds;
-                total.breaktime += time_seconds;
-                break;
+    // Adds the time of a day to the periods it belongs to, given how many days ago it was
+    private void add_to_periods (Day day, int days_ago) {
+        int worktime = day.counted_worktime ();
+        total.worktime += worktime;
+        total.breaktime += day.breaktime;
 
-            default:
-                assert_not_reached ();
+        if (days_ago >= MONTH_DAYS) {
+            return;
         }
 
-        save ();
-        updated ();
-    }
+        month.worktime += worktime;
+        month.breaktime += day.breaktime;
+        if (days_ago >= WEEK_DAYS) {
+            return;
+        }
 
-    public int get_time_from_mode_and_period (TimerMode mode, TimePeriod period) {
-        if (mode == WORK) {
-            return get_worktime_from_period (period);
+        week.worktime += worktime;
+        week.breaktime += day.breaktime;
+        if (days_ago > 0) {
+            return;
         }
-        return get_breaktime_from_period (period);
+
+        today_period.worktime += worktime;
+        today_period.breaktime += day.breaktime;
     }
 
-    private int get_worktime_from_period (TimePeriod period) {
-        switch (period) {
-            case TODAY:
-                return today.worktime;
-            case WEEK:
-                return week.worktime;
-            case MONTH:
-                return month.worktime;
-            case ALL:
-                return total.worktime;
-            default:
-                assert_not_reached ();
+    /*
+     * Calculates the periods and the most productive day again from the saved days. Callers that
+     * change several days at once can call it once they are done, instead of after every change.
+     */
+    public void recompute () {
+        var current_date = new DateTime.now_local ();
+        var settings = new Settings ();
+
+        total.worktime = total.breaktime = 0;
+        month.worktime = month.breaktime = 0;
+        week.worktime = week.breaktime = 0;
+        today_period.worktime = today_period.breaktime = 0;
+
+        foreach (unowned Day day in all_days) {
+            add_to_periods (day, TimeUtils.days_between (day.date, current_date));
         }
-    }
 
-    private int get_breaktime_from_period (TimePeriod period) {
-        switch (period) {
-            case TODAY:
-                return today.breaktime;
-            case WEEK:
-                return week.breaktime;
-            case MONTH:
-                return month.breaktime;
-            case ALL:
-                return total.breaktime;
-            default:
-                assert_not_reached ();
+        if (settings.include_archive) {
+            foreach (unowned Day day in archived_days) {
+                total.worktime += day.counted_worktime ();
+                total.breaktime += day.breaktime;
+            }
         }
+
+        get_most_productive_day ();
+        notify_today ();
+        updated ();
     }
 
-    private void get_most_productive_day () {
-        var days_table = new HashTable<string, int?> (string.hash, str_equal);
+    private void load_archive () {
+        if (archive_doc != null) {
+            return;
+        }
 
-        foreach (Day day in all_days) {
-            if (!(day.day_of_week in days_table)) {
-                days_table[day.day_of_week] = day.worktime;
-                continue;
+        if (FileUtils.test (archive_path, EXISTS)) {
+            archive_doc = Xml.Parser.parse_file (archive_path);
+            if (archive_doc == null) {
+                warning ("%s cannot be parsed. The archive will not be saved", archive_path);
+                archive_unreadable = true;
+                data_error (_("The archive could not be read, no days will be archived until it is fixed"));
             }
-            int time = days_table[day.day_of_week] + day.worktime;
-            days_table[day.day_of_week] = time;
         }
 
-        int highest = -1;
-        foreach (string day_string in days_table.get_keys ()) {
-            int count = days_table[day_string];
-            if (count >= highest) {
-                productive_day = day_string;
-                highest = count;
+        if (archive_doc == null) {
+            archive_doc = new Xml.Doc ("1.0");
+            archive_root = new Xml.Node (null, "statistics");
+            archive_doc->set_root_element (archive_root);
+            return;
+        }
+
+        archive_root = archive_doc->get_root_element ();
+        for (Xml.Node* i = archive_root->children; i != null; i = i->next) {
+            if (i->type == ELEMENT_NODE) {
+                var day = new Day.from_xml (XmlUtils.get_content_node (i, "day"));
+                if (day.valid_date) {
+                    archived_days.append (day);
+                }
             }
         }
     }
 
-    public void save () {
-        message ("Saving document...");
-        doc->save_file (path);
+    private void archive_day (Day day) {
+        day.unlink ();
+        archive_root->add_child (day.node);
+        archived_days.append (day);
     }
-}
-
-public enum Flowtime.Services.TimePeriod {
-    TODAY,
-    WEEK,
-    MONTH,
-    ALL;
 
-    public string to_string () {
-        switch (this) {
-            case TODAY:
-                return _("Today");
-            case WEEK:
-                return _("This Week");
-            case MONTH:
-                return _("This Month");
-            case ALL:
-                return _("All Time");
-            default:
-                assert_not_reached ();
+    private void save_archive () {
+        if (read_only || archive_unreadable) {
+            return;
+        }
+
+        message ("Saving archive...");
+        archive_doc->save_file (archive_path);
+    }
+
+    /*
+     * Moves the days older than the given months to the archive file, so the statistics file
+     * stays small while keeping the history.
+     */
+    public void archive_older_than (int months) throws StatisticsError {
+        check_writable ();
+
+        var settings = new Settings ();
+        var current_date = new DateTime.now_local ();
+        load_archive ();
+        if (archive_unreadable) {
+            throw new StatisticsError.PARSE ("%s cannot be parsed, it is left as it is".printf (archive_path));
+        }
+
+        Day[] old_days = {};
+        foreach (Day day in all_days) {
+            if (day != today && TimeUtils.days_between (day.date, current_date) > months * 30) {
+                old_days += day;
+            }
+        }
+
+        if (old_days.length == 0) {
+            return;
+        }
+
+        foreach (Day day in old_days) {
+            remove_day (day);
+
+            archive_day (day);
+            if (!settings.include_archive) {
+                total.worktime -= day.counted_worktime ();
+                total.breaktime -= day.breaktime;
+            }
+        }
+
+        save_archive ();
+        save ();
+        updated ();
+    }
+
+    private void add_archive_to_total () {
+        load_archive ();
+        foreach (unowned Day day in archived_days) {
+            total.worktime += day.counted_worktime ();
+            total.breaktime += day.breaktime;
+        }
+    }
+
+    private void on_include_archive_changed () {
+        var settings = new Settings ();
+        if (settings.include_archive) {
+            add_archive_to_total ();
+        } else {
+            foreach (unowned Day day in archived_days) {
+                total.worktime -= day.counted_worktime ();
+                total.breaktime -= day.breaktime;
+            }
+        }
+        updated ();
+    }
+
+    /*
+     * Creates the day for today once the date changes. The day that ended is dropped if it had
+     * no activity and empty days are not kept, the streaks count the missing date as inactive.
+     */
+    public void ensure_today () {
+        if (today != null && TimeUtils.same_day (today.date, new DateTime.now_local ())) {
+            return;
+        }
+
+        var settings = new Settings ();
+        if (today != null && !settings.keep_empty_days && is_empty_day (today)) {
+            remove_day (today);
+            today.unlink ();
+        }
+
+        today = new Day ();
+        root_element->add_child (today.node);
+        append_day (today);
+        today_period.worktime = today_period.breaktime = 0;
+
+        save ();
+        day_changed ();
+    }
+
+    private void append_day (Day day) {
+        all_days.append (day);
+        days_store.append (day);
+    }
+
+    private void remove_day (Day day) {
+        all_days.remove (day);
+
+        for (uint i = 0; i < days_store.get_n_items (); i++) {
+            if (days_store.get_item (i) == day) {
+                days_store.remove (i);
+                return;
+            }
+        }
+    }
+
+    /*
+     * Work time is saved as a new session, unless new_session is false, in which case it is added
+     * to the last session of today instead. A new session ends now, or at end if it is given.
+     */
+    public void add_time_to_mode (TimerMode mode, int time_seconds, bool new_session = true,
+                                  DateTime? end = null) {
+        switch (mode) {
+            case WORK:
+                // A session may only be counted once it is long enough to be deep work
+                int counted_before = today.counted_worktime ();
+                if (time_seconds > 0 && new_session) {
+                    today.add_session (time_seconds, end);
+                } else if (time_seconds > 0) {
+                    today.extend_last_session (time_seconds);
+                }
+                today.worktime += time_seconds;
+
+                int counted_seconds = today.counted_worktime () - counted_before;
+                today_period.worktime += counted_seconds;
+                week.worktime += counted_seconds;
+                month.worktime += counted_seconds;
+                total.worktime += counted_seconds;
+                check_milestones ();
+                check_weekly_goal ();
+                break;
+
+            case BREAK:
+                if (time_seconds > 0) {
+                    today.breaks++;
+                }
+                today.add_session_break (time_seconds);
+                today.breaktime += time_seconds;
+                today_period.breaktime += time_seconds;
+                week.breaktime += time_seconds;
+                month.breaktime += time_seconds;
+                total.breaktime += time_seconds;
+                break;
+
+            default:
+                assert_not_reached ();
+        }
+
+        save ();
+        notify_today ();
+        queue_updated ();
+    }
+
+    /*
+     * The time of today is notified right away, but the updated signal makes the listeners compute
+     * their aggregates again, so it is emitted at most once every update interval. A change made
+     * within the interval is emitted once it is over.
+     */
+    private void queue_updated () {
+        if (updated_timeout_id != null) {
+            return;
+        }
+
+        var settings = new Settings ();
+        int64 interval = settings.update_interval * TimeSpan.SECOND;
+        int64 elapsed = get_monotonic_time () - last_updated_time;
+
+        if (elapsed >= interval) {
+            last_updated_time = get_monotonic_time ();
+            updated ();
+            return;
+        }
+
+        uint remaining_ms = (uint) ((interval - elapsed) / 1000);
+        updated_timeout_id = Timeout.add (remaining_ms, () => {
+            updated_timeout_id = null;
+            last_updated_time = get_monotonic_time ();
+            updated ();
+            return Source.REMOVE;
+        });
+    }
+
+    private void notify_today () {
+        notify_property ("today-worktime");
+        notify_property ("today-breaktime");
+        notify_property ("today-worktime-display");
+    }
+
+    public void add_pausetime (int time_seconds) {
+        today.pausetime += time_seconds;
+
+        save ();
+        queue_updated ();
+    }
+
+    /*
+     * Packs the current statistics with the signature (iiu):
+     *  - Today's worktime in seconds
+     *  - Today's breaktime in seconds
+     *  - The amount of days saved in the history
+     *
+     * The signature is part of the DBus interface, new members must be added at the end.
+     */
+    public Variant snapshot_variant () {
+        int worktime = today != null ? today.counted_worktime () : 0;
+        int breaktime = today != null ? today.breaktime : 0;
+
+        return new Variant ("(iiu)", worktime, breaktime, all_days.length ());
+    }
+
+    public int get_time_from_mode_and_period (TimerMode mode, TimePeriod period) {
+        if (mode == WORK) {
+            return get_worktime_from_period (period);
+        }
+        return get_breaktime_from_period (period);
+    }
+
+    private int get_worktime_from_period (TimePeriod period) {
+        switch (period) {
+            case TODAY:
+                return today.counted_worktime ();
+            case WEEK:
+                return week.worktime;
+            case MONTH:
+                return month.worktime;
+            case ALL:
+                return total.worktime;
+            default:
+                assert_not_reached ();
+        }
+    }
+
+    private int get_breaktime_from_period (TimePeriod period) {
+        switch (period) {
+            case TODAY:
+                return today.breaktime;
+            case WEEK:
+                return week.breaktime;
+            case MONTH:
+                return month.breaktime;
+            case ALL:
+                return total.breaktime;
+            default:
+                assert_not_reached ();
+        }
+    }
+
+    public Day? find_day (DateTime date) {
+        foreach (Day day in all_days) {
+            if (TimeUtils.same_day (day.date, date)) {
+                return day;
+            }
+        }
+        return null;
+    }
+
+    /*
+     * Average length in seconds of the breaks taken in the saved days. Days saved before breaks were
+     * counted are left out, as their breaktime cannot be divided.
+     */
+    public double average_break_length () {
+        int breaktime = 0;
+        int breaks = 0;
+        foreach (unowned Day day in all_days) {
+            if (day.breaks == 0) {
+                continue;
+            }
+            breaktime += day.breaktime;
+            breaks += day.breaks;
+        }
+
+        if (breaks == 0) {
+            return 0;
+        }
+        return (double) breaktime / breaks;
+    }
+
+    /*
+     * The fraction of the work sessions that were not followed by a break. The last session of
+     * today is left out, as its break may still be taken. It is zero without sessions.
+     */
+    public double break_skip_rate () {
+        uint sessions = 0;
+        uint skipped = 0;
+        foreach (unowned Day day in all_days) {
+            foreach (unowned Session session in day.sessions) {
+                if (day == today && session == day.sessions.last ().data) {
+                    continue;
+                }
+
+                sessions++;
+                if (session.break_taken == 0) {
+                    skipped++;
+                }
+            }
+        }
+
+        if (sessions == 0) {
+            return 0;
+        }
+        return (double) skipped / sessions;
+    }
+
+    /*
+     * The fraction of the days with work whose break compliance is at least threshold, from 0 to
+     * 1. It is zero without work.
+     */
+    public double compliant_day_rate (double threshold) {
+        uint days = 0;
+        uint compliant = 0;
+        foreach (unowned Day day in all_days) {
+            if (day.counted_worktime () <= 0) {
+                continue;
+            }
+
+            days++;
+            if (day.break_compliance () >= threshold) {
+                compliant++;
+            }
+        }
+
+        if (days == 0) {
+            return 0;
+        }
+        return (double) compliant / days;
+    }
+
+    // The breaktime taken for each second of work over the history. It is zero without work
+    public double realized_break_ratio () {
+        int64 worktime = 0;
+        int64 breaktime = 0;
+        foreach (unowned Day day in history_days ()) {
+            worktime += day.counted_worktime ();
+            breaktime += day.breaktime;
+        }
+
+        if (worktime <= 0) {
+            return 0;
+        }
+        return (double) breaktime / worktime;
+    }
+
+    // The breaktime earned for each second of work over the history, under the break policy
+    public double planned_break_ratio () {
+        int64 worktime = 0;
+        int64 earned = 0;
+        foreach (unowned Day day in history_days ()) {
+            worktime += day.counted_worktime ();
+            earned += day.earned_break ();
+        }
+
+        if (worktime <= 0) {
+            return 0;
+        }
+        return (double) earned / worktime;
+    }
+
+    // The saved days, and the archived ones too if the archive is included in the statistics
+    private List<unowned Day> history_days () {
+        var settings = new Settings ();
+        List<unowned Day> days = all_days.copy ();
+        if (settings.include_archive) {
+            foreach (unowned Day day in archived_days) {
+                days.append (day);
+            }
+        }
+        return days;
+    }
+
+    /*
+     * Compares the breaks taken with the ones earned. A tenth of difference either way is still
+     * as planned. Null is returned while there is no work to compare.
+     */
+    public string? break_ratio_insight () {
+        double planned = planned_break_ratio ();
+        if (planned <= 0) {
+            return null;
+        }
+
+        double realized = realized_break_ratio ();
+        if (realized < planned * 0.9) {
+            return _("You are resting less than planned");
+        }
+        if (realized > planned * 1.1) {
+            return _("You are resting more than planned");
+        }
+        return _("You are resting as planned");
+    }
+
+    /*
+     * The work sessions done on a date, in the order they were done. The model is empty for days
+     * saved before sessions were recorded.
+     */
+    public ListModel sessions_model (DateTime date) {
+        var model = new ListStore (typeof (Session));
+
+        Day? day = find_day (date);
+        if (day != null) {
+            foreach (Session session in day.sessions) {
+                model.append (session);
+            }
+        }
+
+        return model;
+    }
+
+    /*
+     * The fractions of work and break of the time logged on a date, which add up to 1. Both are
+     * zero for a day without time, and false is returned if the date has no record.
+     */
+    public bool day_split (DateTime date, out double work_fraction, out double break_fraction) {
+        work_fraction = break_fraction = 0;
+
+        Day? day = find_day (date);
+        if (day == null) {
+            return false;
+        }
+
+        int total_time = day.worktime + day.breaktime;
+        if (total_time > 0) {
+            work_fraction = (double) day.worktime / total_time;
+            break_fraction = 1 - work_fraction;
+        }
+
+        return true;
+    }
+
+    public bool is_active (Day day) {
+        var settings = new Settings ();
+        int worktime = day.counted_worktime ();
+        return worktime > 0 && worktime >= settings.active_threshold;
+    }
+
+    // The dates of the active days, normalized to midnight and sorted from the oldest
+    public List<DateTime> active_dates () {
+        var dates = new List<DateTime> ();
+
+        foreach (Day day in all_days) {
+            if (is_active (day)) {
+                dates.append (TimeUtils.normalize_to_local_midnight (day.date));
+            }
+        }
+        dates.sort ((a, b) => a.compare (b));
+
+        return dates;
+    }
+
+    /*
+     * Returns one day for each calendar date between start and end, both included. Dates without
+     * a record are filled with empty days that are not added to the history.
+     */
+    public List<Day> dense_days (DateTime start, DateTime end) {
+        var days_table = new HashTable<string, Day> (string.hash, str_equal);
+        foreach (Day day in all_days) {
+            days_table[day.date.to_local ().format ("%F")] = day;
+        }
+
+        var dense = new List<Day> ();
+        DateTime last = TimeUtils.normalize_to_local_midnight (end);
+
+        // Moving 36 hours and normalizing again lands on the next date even when DST changes
+        for (DateTime date = TimeUtils.normalize_to_local_midnight (start);
+             date.compare (last) <= 0;
+             date = TimeUtils.normalize_to_local_midnight (date.add_hours (36))) {
+            Day? day = days_table[date.format ("%F")];
+            dense.append (day ?? new Day.unsaved (date));
+        }
+
+        return dense;
+    }
+
+    /*
+     * The dates between start and end, both included, without an active day. Rest days, frozen
+     * days and non-work days were not missed, so they are not gaps.
+     */
+    public List<DateTime> gap_days (DateTime start, DateTime end) {
+        var gaps = new List<DateTime> ();
+        foreach (unowned Day day in dense_days (start, end)) {
+            if (!is_active (day) && !is_neutral_day (day)) {
+                gaps.append (TimeUtils.normalize_to_local_midnight (day.date));
+            }
+        }
+        return gaps;
+    }
+
+    /*
+     * A model of the days whose worktime is between min and max seconds, both included. It is
+     * filtered again every time the statistics are updated, until the model is no longer used.
+     */
+    public ListModel days_model_in_worktime_range (uint min, uint max)
+    requires (min <= max) {
+        var filter = new Gtk.CustomFilter ((item) => {
+            var day = (Day) item;
+            int worktime = day.counted_worktime ();
+            return worktime >= min && worktime <= max;
+        });
+        var model = new Gtk.FilterListModel (days_store, filter);
+
+        // The handler must not keep the filter alive, or the model would never be finalized
+        var filter_ref = WeakRef (filter);
+        ulong handler_id = 0;
+        handler_id = updated.connect (() => {
+            var weak_filter = (Gtk.Filter?) filter_ref.get ();
+            if (weak_filter == null) {
+                disconnect (handler_id);
+                return;
+            }
+            weak_filter.changed (DIFFERENT);
+        });
+
+        return model;
+    }
+
+    /*
+     * The worktime of each day of the week that contains anchor, ordered from the configured
+     * first day of the week.
+     */
+    public int[] week_totals (DateTime anchor) {
+        var settings = new Settings ();
+        DateTime start = TimeUtils.week_start (anchor, settings.week_start);
+        int[] totals = new int[7];
+
+        foreach (unowned Day day in all_days) {
+            int offset = TimeUtils.days_between (start, day.date);
+            if (offset >= 0 && offset < 7) {
+                totals[offset] += day.counted_worktime ();
+            }
+        }
+
+        return totals;
+    }
+
+    // The minutes of work to reach every week
+    public uint weekly_goal () {
+        var settings = new Settings ();
+        return settings.weekly_goal;
+    }
+
+    // The fraction of the weekly goal reached in the current week. It goes over 1 once exceeded
+    public double week_progress () {
+        int worktime = 0;
+        foreach (int day_worktime in week_totals (new DateTime.now_local ())) {
+            worktime += day_worktime;
+        }
+        return (double) worktime / (weekly_goal () * 60);
+    }
+
+    // The week the goal was reached is saved, so it is only celebrated once in the week
+    private void check_weekly_goal () {
+        if (week_progress () < 1) {
+            return;
+        }
+
+        var settings = new Settings ();
+        string week = TimeUtils.week_start (new DateTime.now_local (), settings.week_start).format ("%F");
+        if (root_element->get_prop ("weekly-goal") == week) {
+            return;
+        }
+
+        root_element->set_prop ("weekly-goal", week);
+        weekly_goal_reached ();
+    }
+
+    /*
+     * The worktime of each of the last weeks, including the current one, paired with the date the
+     * week starts. Weeks are ordered from the oldest and the weeks without time are zero.
+     */
+    public SeriesPoint[] weekly_series (uint weeks) {
+        var settings = new Settings ();
+        var series = new SeriesPoint[weeks];
+        if (weeks == 0) {
+            return series;
+        }
+
+        DateTime current_week = TimeUtils.week_start (new DateTime.now_local (), settings.week_start);
+        DateTime oldest_week = TimeUtils.week_start (
+            current_week.add_days (-7 * ((int) weeks - 1)).add_hours (12), settings.week_start
+        );
+
+        for (int i = 0; i < weeks; i++) {
+            series[i].date = TimeUtils.week_start (oldest_week.add_days (7 * i).add_hours (12),
+                                                   settings.week_start);
+            series[i].value = 0;
+        }
+
+        foreach (unowned Day day in all_days) {
+            int offset = TimeUtils.days_between (oldest_week, day.date);
+            if (offset >= 0 && offset < 7 * weeks) {
+                series[offset / 7].value += day.counted_worktime ();
+            }
+        }
+
+        return series;
+    }
+
+    /*
+     * The worktime of the sessions done in the morning, the afternoon and the evening, split at
+     * the configured hours. A session that crosses one of them is split too. Sessions saved before
+     * their start was recorded cannot be placed in the day, so they are left out.
+     */
+    public void daypart_split (out uint64 morning, out uint64 afternoon, out uint64 evening) {
+        var settings = new Settings ();
+        int afternoon_hour = settings.afternoon_start;
+        int evening_hour = settings.evening_start;
+        if (afternoon_hour >= evening_hour) {
+            warning ("The afternoon starts after the evening, using the defaults");
+            afternoon_hour = DEFAULT_AFTERNOON_START;
+            evening_hour = DEFAULT_EVENING_START;
+        }
+
+        uint64[] parts = { 0, 0, 0 };
+        foreach (unowned Day day in all_days) {
+            foreach (unowned Session session in day.sessions) {
+                if (session.start == null) {
+                    continue;
+                }
+
+                DateTime time = session.start.to_local ();
+                DateTime end = time.add_seconds (session.duration);
+                while (time.compare (end) < 0) {
+                    DateTime afternoon_start = at_minute (time, afternoon_hour * 60);
+                    DateTime evening_start = at_minute (time, evening_hour * 60);
+
+                    int part = 0;
+                    DateTime part_end = afternoon_start;
+                    if (time.compare (evening_start) >= 0) {
+                        part = 2;
+                        // The evening lasts until the next midnight, however long the day is
+                        part_end = TimeUtils.normalize_to_local_midnight (
+                            TimeUtils.normalize_to_local_midnight (time).add_hours (36)
+                        );
+                    } else if (time.compare (afternoon_start) >= 0) {
+                        part = 1;
+                        part_end = evening_start;
+                    }
+
+                    if (part_end.compare (end) > 0) {
+                        part_end = end;
+                    }
+                    parts[part] += part_end.difference (time) / TimeSpan.SECOND;
+                    time = part_end;
+                }
+            }
+        }
+
+        morning = parts[0];
+        afternoon = parts[1];
+        evening = parts[2];
+    }
+
+    /*
+     * The worktime of the sessions started at each hour of each day of the week, from Monday in
+     * the first row. Every session is counted in the hour it started. Sessions saved before their
+     * start was recorded cannot be placed, so they are left out.
+     */
+    public uint[,] punchcard () {
+        var cells = new uint[WEEK_DAYS, 24];
+        foreach (unowned Day day in all_days) {
+            foreach (unowned Session session in day.sessions) {
+                if (session.start == null) {
+                    continue;
+                }
+
+                DateTime start = session.start.to_local ();
+                cells[start.get_day_of_week () - 1, start.get_hour ()] += session.duration;
+            }
+        }
+        return cells;
+    }
+
+    /*
+     * The local time at which work usually starts, as the median of the start of the first session
+     * of the active days, so a few unusual days do not move it. False is returned if less than
+     * three active days have a session with a start.
+     */
+    public bool typical_start_time (out int hour, out int minute) {
+        hour = minute = 0;
+
+        var starts = new List<int> ();
+        foreach (unowned Day day in all_days) {
+            if (!is_active (day)) {
+                continue;
+            }
+
+            DateTime? first = null;
+            foreach (unowned Session session in day.sessions) {
+                if (session.start != null && (first == null || session.start.compare (first) < 0)) {
+                    first = session.start;
+                }
+            }
+
+            if (first != null) {
+                DateTime local = first.to_local ();
+                starts.insert_sorted (local.get_hour () * 60 + local.get_minute (), (a, b) => a - b);
+            }
+        }
+
+        uint length = starts.length ();
+        if (length < MIN_START_DAYS) {
+            return false;
+        }
+
+        uint middle = length / 2;
+        int median = starts.nth_data (middle);
+        if (length % 2 == 0) {
+            median = (starts.nth_data (middle - 1) + median) / 2;
+        }
+
+        hour = median / 60;
+        minute = median % 60;
+        return true;
+    }
+
+    /*
+     * The worktime of the sessions done outside the work hours. Work hours that end before they
+     * start go past midnight. Sessions saved before their start was recorded cannot be placed in
+     * the day, so they are left out.
+     */
+    public uint64 offhours_worktime () {
+        var settings = new Settings ();
+        int start_minute = settings.work_hours_start;
+        int end_minute = settings.work_hours_end;
+
+        uint64 offhours = 0;
+        foreach (unowned Day day in all_days) {
+            foreach (unowned Session session in day.sessions) {
+                if (session.start == null) {
+                    continue;
+                }
+
+                DateTime start = session.start.to_local ();
+                DateTime end = start.add_seconds (session.duration);
+                if (start_minute <= end_minute) {
+                    offhours += session.duration - seconds_in_window (start, end, start_minute, end_minute);
+                } else {
+                    offhours += seconds_in_window (start, end, end_minute, start_minute);
+                }
+            }
+        }
+        return offhours;
+    }
+
+    // The work and break time of the whole history, in hours
+    public double total_tracked_hours () {
+        return ((double) total.worktime + total.breaktime) / 3600;
+    }
+
+    // The fraction of the worktime of the sessions with a start that was done off-hours
+    public string? offhours_text () {
+        int64 worktime = 0;
+        foreach (unowned Day day in all_days) {
+            foreach (unowned Session session in day.sessions) {
+                if (session.start != null) {
+                    worktime += session.duration;
+                }
+            }
+        }
+
+        if (worktime <= 0) {
+            return null;
+        }
+
+        int percent = (int) Math.round (100.0 * offhours_worktime () / worktime);
+        // translators: For example: 15% of your work is off-hours
+        return _("%d%% of your work is off-hours").printf (percent);
+    }
+
+    /*
+     * The seconds between start and end that are within the same minutes of every day, from
+     * start_minute until end_minute after midnight.
+     */
+    private int seconds_in_window (DateTime start, DateTime end, int start_minute, int end_minute) {
+        int seconds = 0;
+        for (DateTime date = TimeUtils.normalize_to_local_midnight (start);
+             date.compare (end) < 0;
+             date = TimeUtils.normalize_to_local_midnight (date.add_hours (36))) {
+            DateTime window_start = at_minute (date, start_minute);
+            DateTime window_end = at_minute (date, end_minute);
+
+            DateTime overlap_start = start.compare (window_start) > 0 ? start : window_start;
+            DateTime overlap_end = end.compare (window_end) < 0 ? end : window_end;
+            if (overlap_end.compare (overlap_start) > 0) {
+                seconds += (int) (overlap_end.difference (overlap_start) / TimeSpan.SECOND);
+            }
+        }
+        return seconds;
+    }
+
+    // The time a number of minutes after midnight of the same date
+    private DateTime at_minute (DateTime date, int minute) {
+        return new DateTime.local (date.get_year (), date.get_month (), date.get_day_of_month (),
+                                   minute / 60, minute % 60, 0);
+    }
+
+    /*
+     * The break compliance of each of the last days, from the oldest and ending today. A day
+     * without work owes no break, so it is fully compliant.
+     */
+    public SeriesPoint[] break_adherence_series (uint days) {
+        SeriesPoint[] series = {};
+        if (days == 0) {
+            return series;
+        }
+
+        var now = new DateTime.now_local ();
+        DateTime start = TimeUtils.normalize_to_local_midnight (now.add_days (1 - (int) days));
+        foreach (unowned Day day in dense_days (start, now)) {
+            series += SeriesPoint () {
+                date = TimeUtils.normalize_to_local_midnight (day.date),
+                value = day.break_compliance ()
+            };
+        }
+
+        return series;
+    }
+
+    /*
+     * The total worktime saved until each date between start and end, both included, with the
+     * days before start counted too. Dates without a record keep the total of the date before.
+     */
+    public SeriesPoint[] cumulative_series (DateTime start, DateTime end) {
+        DateTime first = TimeUtils.normalize_to_local_midnight (start);
+        uint64 running = 0;
+        foreach (unowned Day day in all_days) {
+            if (day.date.compare (first) < 0) {
+                running += day.counted_worktime ();
+            }
+        }
+
+        SeriesPoint[] series = {};
+        foreach (unowned Day day in dense_days (start, end)) {
+            running += day.counted_worktime ();
+            series += SeriesPoint () {
+                date = TimeUtils.normalize_to_local_midnight (day.date),
+                value = running
+            };
+        }
+
+        return series;
+    }
+
+    /*
+     * Compares the average worktime of each day of the week in the older and the recent half of
+     * the history. The day of the week that improved the most, from 1 for Monday to 7 for Sunday,
+     * is returned with its improvement as a fraction of the older average. False is returned if
+     * there are less than two weeks of history or no day of the week improved.
+     */
+    public bool most_improved_weekday (out int weekday, out double delta) {
+        weekday = 0;
+        delta = 0;
+
+        List<Day> days = days_until_today ();
+        uint length = days.length ();
+        if (length < 14) {
+            return false;
+        }
+
+        var older_totals = new int[7];
+        var older_counts = new int[7];
+        var recent_totals = new int[7];
+        var recent_counts = new int[7];
+
+        uint index = 0;
+        foreach (unowned Day day in days) {
+            int weekday_index = day.date.to_local ().get_day_of_week () - 1;
+            if (index < length / 2) {
+                older_totals[weekday_index] += day.counted_worktime ();
+                older_counts[weekday_index]++;
+            } else {
+                recent_totals[weekday_index] += day.counted_worktime ();
+                recent_counts[weekday_index]++;
+            }
+            index++;
+        }
+
+        for (int i = 0; i < 7; i++) {
+            if (older_counts[i] == 0 || recent_counts[i] == 0 || older_totals[i] == 0) {
+                continue;
+            }
+
+            double older_average = (double) older_totals[i] / older_counts[i];
+            double recent_average = (double) recent_totals[i] / recent_counts[i];
+            double improvement = (recent_average - older_average) / older_average;
+
+            if (improvement > delta) {
+                weekday = i + 1;
+                delta = improvement;
+            }
+        }
+
+        return weekday != 0;
+    }
+
+    // Compares the weeks that contain each anchor date
+    public WeekComparison compare_weeks (DateTime week_a_anchor, DateTime week_b_anchor) {
+        return new WeekComparison (week_totals (week_a_anchor), week_totals (week_b_anchor));
+    }
+
+    /*
+     * The worktime this week will end with if the daily average is kept. On the first day of the
+     * week the average of the previous week is used, as a single unfinished day says little.
+     */
+    public uint week_forecast () {
+        var settings = new Settings ();
+        var now = new DateTime.now_local ();
+        DateTime start = TimeUtils.week_start (now, settings.week_start);
+        int elapsed_days = TimeUtils.days_between (start, now) + 1;
+
+        int logged = 0;
+        foreach (int time in week_totals (now)) {
+            logged += time;
+        }
+
+        double average;
+        if (elapsed_days == 1) {
+            int previous = 0;
+            foreach (int time in week_totals (now.add_days (-7))) {
+                previous += time;
+            }
+            average = previous / 7.0;
+        } else {
+            average = (double) logged / elapsed_days;
+        }
+
+        return (uint) (logged + average * (7 - elapsed_days));
+    }
+
+    /*
+     * The average worktime of the last 30 days, in which every day weighs 10% less than the day
+     * after it, so the average follows the recent habits.
+     */
+    public double decayed_daily_average () {
+        const int DAYS = 30;
+        const double DECAY = 0.9;
+
+        var now = new DateTime.now_local ();
+        double weighted_sum = 0;
+        double weights = 0;
+        double weight = 1;
+
+        List<Day> days = dense_days (now.add_days (1 - DAYS), now);
+        for (unowned List<Day>? i = days.last (); i != null; i = i.prev) {
+            weighted_sum += i.data.counted_worktime () * weight;
+            weights += weight;
+            weight *= DECAY;
+        }
+
+        return weighted_sum / weights;
+    }
+
+    /*
+     * The days left to reach a total worktime of target_hours at the recent daily average. It is
+     * zero for a milestone that was already reached, and null if there is no recent work.
+     */
+    public int64? time_to_milestone (uint target_hours) {
+        int64 remaining = (int64) target_hours * 3600 - total.worktime;
+        if (remaining <= 0) {
+            return 0;
+        }
+
+        double average = decayed_daily_average ();
+        if (average <= 0) {
+            return null;
+        }
+
+        return (int64) Math.ceil (remaining / average);
+    }
+
+    // The milestones reached since Flowtime was started, in hours
+    public uint[] milestones_reached_this_session () {
+        return session_milestones;
+    }
+
+    // The highest milestone, in hours, that a total worktime reaches. It is zero if none is reached
+    private uint highest_milestone (int worktime) {
+        uint highest = 0;
+        foreach (uint hours in MILESTONE_HOURS) {
+            if (worktime >= (int) hours * 3600) {
+                highest = hours;
+            }
+        }
+        return highest;
+    }
+
+    /*
+     * The highest milestone celebrated is saved in the statistics, so a milestone is never
+     * celebrated again, even if the total worktime goes below it.
+     */
+    private void check_milestones () {
+        uint celebrated = (uint) uint64.parse (root_element->get_prop ("milestone") ?? "0");
+        uint reached = highest_milestone (total.worktime);
+        if (reached <= celebrated) {
+            return;
+        }
+
+        root_element->set_prop ("milestone", reached.to_string ());
+        foreach (uint hours in MILESTONE_HOURS) {
+            if (hours > celebrated && hours <= reached) {
+                session_milestones += hours;
+                milestone_reached (hours);
+            }
+        }
+    }
+
+    /*
+     * The n days with the most worktime, from the highest. Days with the same worktime are ordered
+     * from the earliest. Only the best days found so far are kept sorted while the days are read.
+     */
+    public List<Day> top_days (uint n) {
+        var top = new List<Day> ();
+        uint length = 0;
+        if (n == 0) {
+            return top;
+        }
+
+        foreach (Day day in all_days) {
+            if (length == n && compare_days_by_worktime (day, top.last ().data) >= 0) {
+                continue;
+            }
+
+            top.insert_sorted (day, compare_days_by_worktime);
+            if (length == n) {
+                top.remove (top.last ().data);
+            } else {
+                length++;
+            }
+        }
+
+        return top;
+    }
+
+    private static int compare_days_by_worktime (Day a, Day b) {
+        int a_worktime = a.counted_worktime ();
+        int b_worktime = b.counted_worktime ();
+        if (a_worktime != b_worktime) {
+            return a_worktime > b_worktime ? -1 : 1;
+        }
+        return a.date.compare (b.date);
+    }
+
+    // The duration of the longest work session ever recorded, or null if there are no sessions
+    public int? longest_session () {
+        int? longest = null;
+
+        foreach (unowned Day day in all_days) {
+            foreach (unowned Session session in day.sessions) {
+                if (longest == null || session.duration > longest) {
+                    longest = session.duration;
+                }
+            }
+        }
+
+        return longest;
+    }
+
+    /*
+     * The longest worktime done in a row on a day, counting as one run the sessions separated by
+     * pauses shorter than the focus gap. Sessions saved without their start are runs of their own.
+     */
+    public int longest_focus_run (Day day) {
+        var settings = new Settings ();
+        TimeSpan max_gap = settings.focus_gap * TimeSpan.MINUTE;
+
+        int longest = 0;
+        int run = 0;
+        DateTime? run_end = null;
+
+        foreach (unowned Session session in day.sessions) {
+            if (session.start == null) {
+                run = 0;
+                run_end = null;
+            } else if (run_end == null || session.start.difference (run_end) > max_gap) {
+                run = 0;
+            }
+
+            run += session.duration;
+            longest = int.max (longest, run);
+
+            if (session.start != null) {
+                run_end = session.start.add_seconds (session.duration);
+            }
+        }
+
+        return longest;
+    }
+
+    // A short recap of a day, with its worktime, the breaks taken and whether the goal was met
+    public string summary_text (Day day) {
+        var settings = new Settings ();
+        string goal;
+        if (day.counted_worktime () >= settings.daily_goal * 60) {
+            goal = _("Daily goal met.");
+        } else {
+            goal = _("Daily goal not met.");
+        }
+
+        // translators: For example: You worked 02:30 hours and took 80% of your earned breaks.
+        return _("You worked %s and took %d%% of your earned breaks.").printf (
+            day.worktime_display, (int) Math.round (day.break_compliance () * 100)
+        ) + " " + goal;
+    }
+
+    // The mean worktime of the active days
+    public double worktime_mean () {
+        int sum = 0;
+        uint count = 0;
+        foreach (unowned Day day in all_days) {
+            if (is_active (day)) {
+                sum += day.counted_worktime ();
+                count++;
+            }
+        }
+
+        if (count == 0) {
+            return 0;
+        }
+        return (double) sum / count;
+    }
+
+    /*
+     * The population standard deviation of the worktime of the active days. The lower it is, the
+     * more consistent the days are. It is zero with less than two active days.
+     */
+    public double worktime_stddev () {
+        double mean = worktime_mean ();
+        double squares = 0;
+        uint count = 0;
+
+        foreach (unowned Day day in all_days) {
+            if (is_active (day)) {
+                squares += Math.pow (day.counted_worktime () - mean, 2);
+                count++;
+            }
+        }
+
+        if (count < 2) {
+            return 0;
+        }
+        return Math.sqrt (squares / count);
+    }
+
+    // The standard deviation relative to the mean, to compare the consistency of different habits
+    public double worktime_variation () {
+        double mean = worktime_mean ();
+        if (mean == 0) {
+            return 0;
+        }
+        return worktime_stddev () / mean;
+    }
+
+    public uint goal_hit_count () {
+        var settings = new Settings ();
+        int goal_seconds = settings.daily_goal * 60;
+        uint count = 0;
+
+        foreach (unowned Day day in all_days) {
+            if (day.counted_worktime () >= goal_seconds) {
+                count++;
+            }
+        }
+
+        return count;
+    }
+
+    public double goal_hit_rate () {
+        var settings = new Settings ();
+        int goal_seconds = settings.daily_goal * 60;
+        uint active_days = 0;
+        uint hits = 0;
+
+        foreach (unowned Day day in all_days) {
+            // Days without any time are just placeholders, they do not count against the goal
+            if (day.worktime == 0 && day.breaktime == 0) {
+                continue;
+            }
+            if (is_neutral_day (day)) {
+                continue;
+            }
+
+            active_days++;
+            if (day.counted_worktime () >= goal_seconds) {
+                hits++;
+            }
+        }
+
+        if (active_days == 0) {
+            return 0;
+        }
+
+        return (double) hits / active_days;
+    }
+
+    // Neutral days neither break nor extend a streak
+    private bool is_neutral_day (Day day) {
+        if (day.rest_day || day.frozen) {
+            return true;
+        }
+
+        var settings = new Settings ();
+        return settings.is_non_work_day (day.date.to_local ());
+    }
+
+    // The days from the oldest saved day until today, including the dates without a record
+    private List<Day> days_until_today () {
+        var now = new DateTime.now_local ();
+        DateTime oldest = now;
+        foreach (unowned Day day in all_days) {
+            if (day.date.compare (oldest) < 0) {
+                oldest = day.date;
+            }
+        }
+        return dense_days (oldest, now);
+    }
+
+    // Active days in a row until today. Today only counts once it is active
+    public uint current_streak () {
+        List<Day> days = days_until_today ();
+        var now = new DateTime.now_local ();
+        uint streak = 0;
+
+        for (unowned List<Day>? i = days.last (); i != null; i = i.prev) {
+            Day day = i.data;
+            if (is_neutral_day (day)) {
+                continue;
+            }
+
+            if (is_active (day)) {
+                streak++;
+            } else if (!TimeUtils.same_day (day.date, now)) {
+                break;
+            }
+        }
+
+        return streak;
+    }
+
+    public uint longest_streak () {
+        uint longest = 0;
+        uint streak = 0;
+
+        foreach (unowned Day day in days_until_today ()) {
+            if (is_neutral_day (day)) {
+                continue;
+            }
+
+            streak = is_active (day) ? streak + 1 : 0;
+            longest = uint.max (longest, streak);
+        }
+
+        return longest;
+    }
+
+    /*
+     * The latest notable events of the history, from the newest: the days the goal was reached,
+     * the days with more worktime than any day before them, the streaks that reached a milestone
+     * and the milestones of the total worktime. At most limit events are returned.
+     */
+    public ListModel activity_feed (uint limit) {
+        var settings = new Settings ();
+        int goal_seconds = settings.daily_goal * 60;
+
+        FeedItem[] events = {};
+        int64 running = 0;
+        int best = 0;
+        uint streak = 0;
+        var now = new DateTime.now_local ();
+
+        foreach (unowned Day day in days_until_today ()) {
+            DateTime date = TimeUtils.normalize_to_local_midnight (day.date);
+
+            int64 running_before = running;
+            running += day.counted_worktime ();
+            foreach (uint hours in MILESTONE_HOURS) {
+                int64 milestone = (int64) hours * 3600;
+                if (running_before < milestone && running >= milestone) {
+                    events += new FeedItem (date, "timer-sand-symbolic", ngettext (
+                        "Worked for %u hour in total", "Worked for %u hours in total", hours
+                    ).printf (hours));
+                }
+            }
+
+            if (day.counted_worktime () > 0 && day.counted_worktime () >= goal_seconds) {
+                events += new FeedItem (date, "profit-symbolic", _("Reached the daily goal"));
+            }
+
+            if (best > 0 && day.counted_worktime () > best) {
+                events += new FeedItem (date, "starred-symbolic", _("New best day with %s").printf (
+                    day.worktime_display
+                ));
+            }
+            best = int.max (best, day.counted_worktime ());
+
+            if (is_neutral_day (day)) {
+                continue;
+            }
+            if (is_active (day)) {
+                streak++;
+                if (streak in STREAK_MILESTONES) {
+                    events += new FeedItem (date, "month-symbolic", _("%u days in a row").printf (streak));
+                }
+            } else if (!TimeUtils.same_day (day.date, now)) {
+                streak = 0;
+            }
+        }
+
+        var feed = new ListStore (typeof (FeedItem));
+        for (int i = events.length - 1; i >= 0 && feed.get_n_items () < limit; i--) {
+            feed.append (events[i]);
+        }
+        return feed;
+    }
+
+    /*
+     * The value of a metric of the summary bar, from its identifier. Times are in seconds and
+     * progress is a fraction of the goal. Null is returned for an unknown identifier.
+     */
+    public double? metric_by_id (string id) {
+        var settings = new Settings ();
+        switch (id) {
+            case "today-worktime":
+                return today_worktime;
+            case "today-breaktime":
+                return today_breaktime;
+            case "streak":
+                return current_streak ();
+            case "longest-streak":
+                return longest_streak ();
+            case "week-worktime":
+                return week.worktime;
+            case "weekly-average":
+                return (double) week.worktime / WEEK_DAYS;
+            case "month-worktime":
+                return month.worktime;
+            case "total-worktime":
+                return total.worktime;
+            case "goal-progress":
+                return (double) today_worktime / (settings.daily_goal * 60);
+            case "week-progress":
+                return week_progress ();
+            default:
+                debug ("Unknown metric %s", id);
+                return null;
+        }
+    }
+
+    public DashboardSnapshot dashboard_snapshot () {
+        var settings = new Settings ();
+        var now = new DateTime.now_local ();
+
+        var snapshot = DashboardSnapshot () {
+            today_worktime = today_worktime,
+            today_breaktime = today_breaktime,
+            current_streak = current_streak (),
+            longest_streak = longest_streak (),
+            week_worktime = week.worktime,
+            week_trend = compare_weeks (now.add_days (-WEEK_DAYS), now).delta,
+            productive_day = productive_day,
+            goal_progress = (double) today_worktime / (settings.daily_goal * 60),
+            best_day = null,
+            best_day_worktime = 0
+        };
+
+        List<Day> best_days = top_days (1);
+        if (!best_days.is_empty ()) {
+            snapshot.best_day = best_days.data.date;
+            snapshot.best_day_worktime = best_days.data.counted_worktime ();
+        }
+
+        return snapshot;
+    }
+
+    public StreakInfo streak_info () {
+        uint current = current_streak ();
+        return StreakInfo () {
+            current = current,
+            longest = longest_streak (),
+            at_risk = current > 0 && today != null && !is_neutral_day (today) && !is_active (today),
+            freezes_available = freezes_left ()
+        };
+    }
+
+    private void check_streak () {
+        StreakInfo info = streak_info ();
+        if (last_streak_info != null && info.equal (last_streak_info)) {
+            return;
+        }
+
+        last_streak_info = info;
+        streak_changed (info);
+    }
+
+    private void get_most_productive_day () {
+        var settings = new Settings ();
+        var days_table = new HashTable<string, double?> (string.hash, str_equal);
+        var counts_table = new HashTable<string, int?> (string.hash, str_equal);
+
+        foreach (Day day in all_days) {
+            double value = day.counted_worktime ();
+            if (settings.productive_day_metric == FREQUENCY) {
+                value = is_active (day) ? 1 : 0;
+            }
+
+            if (!(day.day_of_week in days_table)) {
+                days_table[day.day_of_week] = value;
+                counts_table[day.day_of_week] = 1;
+                continue;
+            }
+
+            counts_table[day.day_of_week] = counts_table[day.day_of_week] + 1;
+            if (settings.productive_day_metric == PEAK_DAY) {
+                days_table[day.day_of_week] = double.max (days_table[day.day_of_week], value);
+            } else {
+                days_table[day.day_of_week] = days_table[day.day_of_week] + value;
+            }
+        }
+
+        double highest = -1;
+        foreach (string day_string in days_table.get_keys ()) {
+            double count = days_table[day_string];
+            if (settings.productive_day_metric == AVERAGE_WORKTIME) {
+                count /= counts_table[day_string];
+            }
+
+            if (count >= highest) {
+                productive_day = day_string;
+                highest = count;
+            }
+        }
+    }
+
+    /*
+     * Looks for entries that are likely to be mistakes, such as those made when the file is edited
+     * by hand. Every problem found is returned as a message.
+     */
+    public string[] verify () {
+        string[] warnings = {};
+        var seen_dates = new GenericSet<string> (string.hash, str_equal);
+        var now = new DateTime.now_local ();
+
+        foreach (unowned Day day in all_days) {
+            string date = day.date.to_local ().format ("%F");
+
+            if (date in seen_dates) {
+                warnings += _("%s is saved more than once").printf (date);
+            }
+            seen_dates.add (date);
+
+            if (day.worktime < 0 || day.breaktime < 0) {
+                warnings += _("%s has a negative time").printf (date);
+            }
+
+            if (day.worktime + day.breaktime > 86400) {
+                warnings += _("%s has more than 24 hours of time").printf (date);
+            }
+
+            if (day.date.compare (now) > 0) {
+                warnings += _("%s is in the future").printf (date);
+            }
+        }
+
+        return warnings;
+    }
+
+    public DataHealth data_health () {
+        var health = new DataHealth () {
+            day_count = all_days.length (),
+            warning_count = verify ().length
+        };
+
+        foreach (unowned Day day in all_days) {
+            if (health.earliest_date == null || day.date.compare (health.earliest_date) < 0) {
+                health.earliest_date = day.date;
+            }
+            if (health.latest_date == null || day.date.compare (health.latest_date) > 0) {
+                health.latest_date = day.date;
+            }
+        }
+
+        try {
+            var file = File.new_for_path (path);
+            FileInfo info = file.query_info (FileAttribute.STANDARD_SIZE, NONE);
+            health.file_size = info.get_size ();
+        } catch (Error e) {
+            warning ("Could not query the size of the statistics file: %s", e.message);
+        }
+
+        return health;
+    }
+
+    // When the statistics file was last written, in local time, or null if there is no file yet
+    public DateTime? data_last_modified () {
+        var file = File.new_for_path (path);
+        try {
+            FileInfo info = file.query_info (FileAttribute.TIME_MODIFIED, NONE);
+            return info.get_modification_date_time ().to_local ();
+        } catch (IOError.NOT_FOUND e) {
+            return null;
+        } catch (Error e) {
+            warning ("Could not query when the statistics file was modified: %s", e.message);
+            return null;
+        }
+    }
+
+    public void set_note (DateTime date, string note) throws StatisticsError {
+        check_writable ();
+        find_or_add_day (date).note = note;
+        save ();
+        updated ();
+    }
+
+    public void set_rest_day (DateTime date, bool rest_day) throws StatisticsError {
+        check_writable ();
+        find_or_add_day (date).rest_day = rest_day;
+        save ();
+        updated ();
+    }
+
+    // Rates how the user felt on a date from 1 to 5, or removes the rating with 0
+    public void set_energy (DateTime date, uint rating) throws StatisticsError
+    requires (rating <= 5) {
+        check_writable ();
+        find_or_add_day (date).energy = rating;
+        save ();
+        updated ();
+    }
+
+    /*
+     * The worktime of the days rated with each energy level, the first element being the days
+     * rated 1. Days that were not rated are left out.
+     */
+    public uint64[] worktime_by_energy () {
+        var worktime = new uint64[5];
+        foreach (unowned Day day in all_days) {
+            if (day.energy > 0) {
+                worktime[day.energy - 1] += day.counted_worktime ();
+            }
+        }
+        return worktime;
+    }
+
+    /*
+     * Spends one of the streak freezes on a date, so that it does not break the streak. False is
+     * returned if there are no freezes left or the date is already frozen.
+     */
+    public bool use_freeze (DateTime date) throws StatisticsError {
+        check_writable ();
+
+        int freezes = freezes_left ();
+        if (freezes <= 0) {
+            return false;
+        }
+
+        Day day = find_or_add_day (date);
+        if (day.frozen) {
+            return false;
+        }
+
+        day.frozen = true;
+        root_element->set_prop ("freeze-month", current_freeze_month ());
+        root_element->set_prop ("freezes", (freezes - 1).to_string ());
+        save ();
+        updated ();
+        return true;
+    }
+
+    /*
+     * The freezes are given back at the start of every month, once the day changes. They are kept
+     * in the statistics, so a spent freeze is saved together with the day it froze.
+     */
+    private void refill_freezes () {
+        string month = current_freeze_month ();
+
+        if (root_element->get_prop ("freeze-month") != month) {
+            root_element->set_prop ("freeze-month", month);
+            root_element->set_prop ("freezes", MONTHLY_FREEZES.to_string ());
+        }
+    }
+
+    private string current_freeze_month () {
+        return new DateTime.now_local ().format ("%Y-%m");
+    }
+
+    // The freezes spent in an earlier month are already back, even before they are refilled
+    private int freezes_left () {
+        string? freezes = root_element->get_prop ("freezes");
+        if (freezes == null || root_element->get_prop ("freeze-month") != current_freeze_month ()) {
+            return MONTHLY_FREEZES;
+        }
+        return int.parse (freezes).clamp (0, MONTHLY_FREEZES);
+    }
+
+    private Day find_or_add_day (DateTime date) {
+        Day? day = find_day (date);
+        if (day == null) {
+            day = new Day.for_date (date);
+            root_element->add_child (day.node);
+            append_day (day);
+        }
+        return day;
+    }
+
+    /*
+     * Writes the days as a Markdown table with their notes, followed by the totals. Only the days
+     * between start and end are written if both are given.
+     */
+    public void export_markdown (string file_path, DateTime? start = null, DateTime? end = null)
+    throws StatisticsError {
+        var builder = new StringBuilder ();
+        builder.append ("| %s | %s | %s | %s |\n".printf (_("Date"), _("Worktime"), _("Breaktime"), _("Notes")));
+        builder.append ("| --- | --- | --- | --- |\n");
+
+        int worktime = 0;
+        int breaktime = 0;
+        foreach (unowned Day day in all_days) {
+            if (start != null && end != null) {
+                // The dates of the range are included whatever time they were given at
+                DateTime date = TimeUtils.normalize_to_local_midnight (day.date);
+                if (date.compare (TimeUtils.normalize_to_local_midnight (start)) < 0 ||
+                    date.compare (TimeUtils.normalize_to_local_midnight (end)) > 0) {
+                    continue;
+                }
+            }
+
+            builder.append ("| %s | %s | %s | %s |\n".printf (
+                day.date.to_local ().format ("%F"),
+                day.worktime_display,
+                day.breaktime_display,
+                escape_markdown_cell (day.note)
+            ));
+            worktime += day.worktime;
+            breaktime += day.breaktime;
+        }
+
+        builder.append ("\n**%s:** %s, **%s:** %s\n".printf (
+            _("Total Worktime"), TimeUtils.format_time (worktime),
+            _("Total Breaktime"), TimeUtils.format_time (breaktime)
+        ));
+
+        try {
+            FileUtils.set_contents (file_path, builder.str);
+        } catch (FileError e) {
+            throw new StatisticsError.IO ("%s could not be written: %s".printf (file_path, e.message));
+        }
+    }
+
+    // Pipes would end the cell and new lines the row, so they cannot be written as they are
+    private string escape_markdown_cell (string text) {
+        return text.replace ("|", "\\|").replace ("\n", " ");
+    }
+
+    /*
+     * Writes everything saved about a day as JSON, with its sessions and note, and the values
+     * derived from them, so the day can be shared.
+     */
+    public void export_day_json (DateTime date, string file_path) throws StatisticsError {
+        Day? day = find_day (date);
+        if (day == null) {
+            throw new StatisticsError.NOT_FOUND ("There are no statistics for %s".printf (date.format ("%F")));
+        }
+
+        var settings = new Settings ();
+        var builder = new Json.Builder ();
+        builder.begin_object ();
+        builder.set_member_name ("date").add_string_value (day.date.to_local ().format ("%F"));
+        builder.set_member_name ("worktime").add_int_value (day.worktime);
+        builder.set_member_name ("breaktime").add_int_value (day.breaktime);
+        builder.set_member_name ("pausetime").add_int_value (day.pausetime);
+        builder.set_member_name ("breaks").add_int_value (day.breaks);
+        builder.set_member_name ("note").add_string_value (day.note);
+        builder.set_member_name ("rest_day").add_boolean_value (day.rest_day);
+        if (day.energy > 0) {
+            builder.set_member_name ("energy").add_int_value (day.energy);
+        }
+
+        builder.set_member_name ("sessions").begin_array ();
+        foreach (unowned Session session in day.sessions) {
+            builder.begin_object ();
+            if (session.start != null) {
+                builder.set_member_name ("start").add_string_value (session.start.format_iso8601 ());
+            }
+            builder.set_member_name ("duration").add_int_value (session.duration);
+            builder.set_member_name ("break").add_int_value (session.break_taken);
+            builder.end_object ();
+        }
+        builder.end_array ();
+
+        builder.set_member_name ("earned_break").add_int_value (day.earned_break ());
+        builder.set_member_name ("break_compliance").add_double_value (day.break_compliance ());
+        builder.set_member_name ("longest_focus_run").add_int_value (longest_focus_run (day));
+        builder.set_member_name ("goal_met").add_boolean_value (day.counted_worktime () >= settings.daily_goal * 60);
+        builder.end_object ();
+
+        var generator = new Json.Generator () {
+            pretty = true,
+            root = builder.get_root ()
+        };
+
+        try {
+            generator.to_file (file_path);
+        } catch (Error e) {
+            throw new StatisticsError.IO ("%s could not be written: %s".printf (file_path, e.message));
+        }
+    }
+
+    public void export (string file_path, ExportFormat format) throws StatisticsError {
+        switch (format) {
+            case JSON:
+                new JsonBackend ().save (doc, file_path);
+                break;
+            case CSV:
+                export_csv (file_path);
+                break;
+            case ICS:
+                export_ics (file_path);
+                break;
+            default:
+                assert_not_reached ();
+        }
+    }
+
+    // The times of every day in seconds, to be read by spreadsheets
+    public void export_csv (string file_path) throws StatisticsError {
+        var builder = new StringBuilder ("date,worktime,breaktime,pausetime\n");
+        foreach (unowned Day day in all_days) {
+            builder.append ("%s,%d,%d,%d\n".printf (
+                day.date.to_local ().format ("%F"), day.worktime, day.breaktime, day.pausetime
+            ));
+        }
+
+        write_export (file_path, builder.str);
+    }
+
+    /*
+     * Every work session as an event of a calendar. Sessions saved before their start was
+     * recorded cannot be placed in the calendar, so they are left out.
+     */
+    public void export_ics (string file_path) throws StatisticsError {
+        var settings = new Settings ();
+        var builder = new StringBuilder ();
+        builder.append ("BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//Flowtime//Statistics//EN\r\n");
+
+        foreach (unowned Day day in all_days) {
+            foreach (unowned Session session in day.sessions) {
+                if (session.start == null) {
+                    continue;
+                }
+
+                DateTime start = session.start.to_utc ();
+                builder.append ("BEGIN:VEVENT\r\n");
+                builder.append ("UID:%s@flowtime\r\n".printf (start.to_unix ().to_string ()));
+                builder.append ("DTSTAMP:%s\r\n".printf (start.format ("%Y%m%dT%H%M%SZ")));
+                builder.append ("DTSTART:%s\r\n".printf (start.format ("%Y%m%dT%H%M%SZ")));
+                builder.append ("DTEND:%s\r\n".printf (
+                    start.add_seconds (session.duration).format ("%Y%m%dT%H%M%SZ")
+                ));
+                builder.append ("SUMMARY:%s\r\n".printf (settings.terminology.work_label ()));
+                builder.append ("END:VEVENT\r\n");
+            }
+        }
+
+        builder.append ("END:VCALENDAR\r\n");
+        write_export (file_path, builder.str);
+    }
+
+    private void write_export (string file_path, string contents) throws StatisticsError {
+        try {
+            FileUtils.set_contents (file_path, contents);
+        } catch (FileError e) {
+            throw new StatisticsError.IO ("%s could not be written: %s".printf (file_path, e.message));
+        }
+    }
+
+    /*
+     * Exports the statistics to the configured file when Flowtime is closed. A failure does not
+     * keep Flowtime from closing, it is saved to be shown on the next launch instead.
+     */
+    public void auto_export () {
+        var settings = new Settings ();
+        // Locked statistics are only a placeholder, which must not replace the last export
+        if (!settings.auto_export || settings.auto_export_path == "" || locked || sample_mode) {
+            return;
+        }
+
+        try {
+            export (settings.auto_export_path, settings.auto_export_format);
+            settings.auto_export_error = "";
+        } catch (StatisticsError e) {
+            warning ("Statistics could not be exported: %s", e.message);
+            settings.auto_export_error = e.message;
+        }
+    }
+
+    // Whether a backup should be written at now, going by the calendar days since the last one
+    public bool backup_due (DateTime now) {
+        var settings = new Settings ();
+        int interval = settings.backup_schedule.interval_days ();
+        if (interval == 0 || settings.backup_path == "") {
+            return false;
+        }
+
+        DateTime? last_backup = TimeUtils.parse_date (settings.last_backup);
+        if (last_backup == null) {
+            return true;
+        }
+        return TimeUtils.days_between (last_backup, now) >= interval;
+    }
+
+    /*
+     * Writes a backup of the statistics as JSON to the backup folder, named after the time it was
+     * written, and deletes the oldest backups past the ones kept.
+     */
+    public void backup () throws StatisticsError {
+        var settings = new Settings ();
+        var now = new DateTime.now_local ();
+
+        if (DirUtils.create_with_parents (settings.backup_path, 0755) != 0) {
+            throw new StatisticsError.IO ("%s cannot be created: %s".printf (
+                settings.backup_path, strerror (errno)
+            ));
+        }
+
+        string file_name = "%s%s.json".printf (BACKUP_PREFIX, now.format ("%Y%m%d-%H%M%S"));
+        export (Path.build_filename (settings.backup_path, file_name), JSON);
+        settings.last_backup = now.format_iso8601 ();
+
+        prune_backups (settings.backup_path, settings.backup_count);
+    }
+
+    /*
+     * Deletes the backups in directory but the newest keep ones. Backups are named after the time
+     * they were written, so sorting their names sorts them by age. Other files are left alone.
+     */
+    public void prune_backups (string directory, int keep) throws StatisticsError {
+        var backups = new List<string> ();
+        try {
+            Dir dir = Dir.open (directory);
+            string? name;
+            while ((name = dir.read_name ()) != null) {
+                if (name.has_prefix (BACKUP_PREFIX) && name.has_suffix (".json")) {
+                    backups.insert_sorted (name, strcmp);
+                }
+            }
+        } catch (FileError e) {
+            throw new StatisticsError.IO ("%s cannot be read: %s".printf (directory, e.message));
+        }
+
+        int excess = (int) backups.length () - keep;
+        foreach (unowned string name in backups) {
+            if (excess <= 0) {
+                break;
+            }
+
+            string backup_path = Path.build_filename (directory, name);
+            if (FileUtils.remove (backup_path) != 0) {
+                warning ("%s cannot be deleted: %s", backup_path, strerror (errno));
+            }
+            excess--;
+        }
+    }
+
+    private void check_backup () {
+        if (locked || sample_mode || !backup_due (new DateTime.now_local ())) {
+            return;
+        }
+
+        try {
+            backup ();
+        } catch (StatisticsError e) {
+            warning ("Statistics could not be backed up: %s", e.message);
+        }
+    }
+
+    /*
+     * Writes how many days had a worktime within each bucket of bucket_minutes, as a CSV of the
+     * start of the bucket in minutes and the count of days. Buckets without days are written too,
+     * up to the longest day, and days without work are left out.
+     */
+    public void export_histogram_csv (string file_path, uint bucket_minutes) throws StatisticsError {
+        if (bucket_minutes == 0) {
+            throw new StatisticsError.INVALID_ARGUMENT (
+                "The buckets of the histogram must be at least one minute long"
+            );
+        }
+
+        int bucket_seconds = (int) bucket_minutes * 60;
+        uint[] counts = {};
+        foreach (unowned Day day in all_days) {
+            int worktime = day.counted_worktime ();
+            if (worktime <= 0) {
+                continue;
+            }
+
+            int bucket = worktime / bucket_seconds;
+            while (counts.length <= bucket) {
+                counts += 0;
+            }
+            counts[bucket]++;
+        }
+
+        var builder = new StringBuilder ("minutes,days\n");
+        for (int i = 0; i < counts.length; i++) {
+            builder.append ("%u,%u\n".printf ((uint) i * bucket_minutes, counts[i]));
+        }
+
+        try {
+            FileUtils.set_contents (file_path, builder.str);
+        } catch (FileError e) {
+            throw new StatisticsError.IO ("%s could not be written: %s".printf (file_path, e.message));
+        }
+    }
+
+    /*
+     * Merges every statistics file in a directory, either XML or JSON. A file that cannot be read
+     * is added to the errors of the report, and the rest of the files are still merged.
+     */
+    public MergeReport import_directory (string dir_path) throws StatisticsError {
+        check_writable ();
+
+        Dir dir;
+        try {
+            dir = Dir.open (dir_path);
+        } catch (FileError e) {
+            throw new StatisticsError.IO ("%s cannot be opened: %s".printf (dir_path, e.message));
+        }
+
+        var report = new MergeReport ();
+        string? name = null;
+        while ((name = dir.read_name ()) != null) {
+            StorageBackend storage;
+            if (name.has_suffix (".xml")) {
+                storage = new XmlBackend ();
+            } else if (name.has_suffix (".json")) {
+                storage = new JsonBackend ();
+            } else {
+                continue;
+            }
+
+            Xml.Doc* imported = null;
+            try {
+                imported = storage.load (Path.build_filename (dir_path, name));
+                merge_document (imported, report);
+            } catch (StatisticsError e) {
+                report.errors += "%s: %s".printf (name, e.message);
+            } finally {
+                delete imported;
+            }
+        }
+
+        recompute ();
+        save ();
+        return report;
+    }
+
+    /*
+     * Merges the statistics of GNOME Pomodoro, read from its database, like the days of another
+     * Flowtime file. See PomodoroImporter for how they are mapped.
+     */
+    public MergeReport import_gnome_pomodoro (string db_path) throws StatisticsError {
+        check_writable ();
+
+        var report = new MergeReport ();
+        foreach (Day day in new PomodoroImporter ().read_days (db_path)) {
+            merge_day (day, report);
+        }
+
+        recompute ();
+        save ();
+        return report;
+    }
+
+    /*
+     * Adds the days of a document to the saved days. A day identical to the saved one is skipped,
+     * otherwise the times of both are added up. The document may hold a single day as its root.
+     */
+    private void merge_document (Xml.Doc* imported, MergeReport report) throws StatisticsError {
+        Xml.Node* imported_root = imported->get_root_element ();
+        if (imported_root == null) {
+            throw new StatisticsError.PARSE ("The document is empty");
+        }
+
+        if (imported_root->name == "day") {
+            merge_day (new Day.from_xml (imported_root), report);
+            return;
+        }
+
+        if (imported_root->name != "statistics") {
+            throw new StatisticsError.PARSE ("The document does not contain statistics");
+        }
+
+        for (Xml.Node* i = imported_root->children; i != null; i = i->next) {
+            if (i->type == ELEMENT_NODE && i->name == "day") {
+                merge_day (new Day.from_xml (i), report);
+            }
+        }
+    }
+
+    private void merge_day (Day imported, MergeReport report) {
+        if (!imported.valid_date) {
+            report.skipped++;
+            return;
+        }
+
+        Day? day = find_day (imported.date);
+
+        if (day == null) {
+            day = new Day.for_date (imported.date);
+            root_element->add_child (day.node);
+            append_day (day);
+            report.added++;
+        } else if (day.worktime == imported.worktime && day.breaktime == imported.breaktime) {
+            report.skipped++;
+            return;
+        } else {
+            report.merged++;
+        }
+
+        add_day_times (day, imported);
+    }
+
+    private void add_day_times (Day day, Day imported) {
+        day.worktime += imported.worktime;
+        day.breaktime += imported.breaktime;
+        day.pausetime += imported.pausetime;
+        day.breaks += imported.breaks;
+        foreach (unowned Session session in imported.sessions) {
+            day.copy_session (session);
+        }
+    }
+
+    /*
+     * Writes the statistics again sorted by date, with every date at local midnight. Days of the
+     * same date are merged like imported days, and days without anything saved are dropped.
+     * Normalizing a file that was already normalized leaves it as it was.
+     */
+    public void normalize_file () throws StatisticsError {
+        check_writable ();
+
+        Xml.Doc* tidy = new Xml.Doc ("1.0");
+        Xml.Node* tidy_root = new Xml.Node (null, "statistics");
+        for (Xml.Attr* attr = root_element->properties; attr != null; attr = attr->next) {
+            tidy_root->set_prop (attr->name, root_element->get_prop (attr->name));
+        }
+        tidy->set_root_element (tidy_root);
+
+        List<unowned Day> days = all_days.copy ();
+        days.sort ((a, b) => a.date.compare (b.date));
+
+        Day? previous = null;
+        foreach (unowned Day day in days) {
+            if (is_empty_day (day) && day != today) {
+                continue;
+            }
+
+            DateTime date = TimeUtils.normalize_to_local_midnight (day.date);
+            if (previous != null && TimeUtils.same_day (previous.date, date)) {
+                if (previous.worktime != day.worktime || previous.breaktime != day.breaktime) {
+                    add_day_times (previous, day);
+                }
+                continue;
+            }
+
+            var copy = new Day.from_xml (day.node->copy (1));
+            copy.date = date;
+            tidy_root->add_child (copy.node);
+            previous = copy;
+        }
+
+        replace_document (tidy);
+        save ();
+    }
+
+    /*
+     * Writes every date of the days and their sessions in the same variant of ISO 8601, the one
+     * Flowtime saves. Returns how many dates were written again.
+     */
+    public uint normalize_dates () {
+        uint repaired = 0;
+        string[] day_props = { "date", "first", "last" };
+
+        foreach (unowned Day day in all_days) {
+            foreach (unowned string prop in day_props) {
+                if (normalize_date_prop (day.node, prop)) {
+                    repaired++;
+                }
+            }
+
+            foreach (unowned Session session in day.sessions) {
+                if (normalize_date_prop (session.node, "start")) {
+                    repaired++;
+                }
+            }
+        }
+
+        if (repaired > 0) {
+            save ();
+        }
+        return repaired;
+    }
+
+    // True if the date in the attribute was not written in the saved variant of ISO 8601
+    private bool normalize_date_prop (Xml.Node* node, string prop) {
+        string? text = node->get_prop (prop);
+        DateTime? date = TimeUtils.parse_date (text);
+        if (date == null || text == date.format_iso8601 ()) {
+            return false;
+        }
+
+        node->set_prop (prop, date.format_iso8601 ());
+        return true;
+    }
+
+    private bool is_empty_day (Day day) {
+        return day.worktime == 0 && day.breaktime == 0 && day.pausetime == 0 && day.note == ""
+            && !day.rest_day && !day.frozen && day.energy == 0;
+    }
+
+    /*
+     * Replaces the statistics with the ones saved in a JSON file, which are then saved in the
+     * selected format. Statistics that already have time are only replaced if force is set.
+     */
+    public void restore_from_json (string json_path, bool force = false) throws StatisticsError {
+        check_writable ();
+
+        if (!force) {
+            foreach (unowned Day day in all_days) {
+                if (day.worktime > 0 || day.breaktime > 0) {
+                    throw new StatisticsError.NOT_EMPTY (
+                        "The statistics already have time saved, restoring them must be forced"
+                    );
+                }
+            }
+        }
+
+        replace_document (new JsonBackend ().load (json_path));
+        save ();
+    }
+
+    /*
+     * Shows a made up history instead of the statistics. The statistics are read-only from then
+     * on, so the file of the user is never touched, until unload_sample is called. sample_mode is
+     * set first, so the time of a running timer is still saved to the statistics.
+     */
+    public void load_sample () {
+        if (sample_mode) {
+            return;
+        }
+
+        sample_mode = true;
+        save ();
+        real_doc = doc->copy (1);
+        real_read_only = read_only;
+        read_only = true;
+        replace_document (new SampleData ().create_document ());
+    }
+
+    // Shows the statistics again in place of the sample history
+    public void unload_sample () {
+        if (!sample_mode) {
+            return;
+        }
+
+        Xml.Doc* restored = real_doc;
+        real_doc = null;
+        read_only = real_read_only;
+        sample_mode = false;
+        replace_document (restored);
+    }
+
+    // Drops the days in memory and reads the ones of another document instead
+    private void replace_document (Xml.Doc* replacement) {
+        delete doc;
+        doc = replacement;
+        root_element = doc->get_root_element ();
+
+        all_days = new List<Day> ();
+        days_store.remove_all ();
+        today = null;
+        total.worktime = total.breaktime = 0;
+        month.worktime = month.breaktime = 0;
+        week.worktime = week.breaktime = 0;
+        today_period.worktime = today_period.breaktime = 0;
+
+        try {
+            check_version ();
+        } catch (StatisticsError e) {
+            warning ("%s. The statistics will not be saved", e.message);
+            read_only = true;
+            load_error = e.message;
+            data_error (e.message);
+        }
+        retrieve_days ();
+
+        notify_today ();
+        updated ();
+    }
+
+    /*
+     * Reads the encrypted statistics with the passphrase, and keeps saving them encrypted with it.
+     * A new encrypted file is created if there is none yet.
+     */
+    public void unlock (string passphrase) throws StatisticsError {
+        unload_sample ();
+
+        var encrypted_backend = new EncryptedBackend (passphrase);
+        string encrypted_path = path_for_backend (encrypted_backend);
+
+        if (FileUtils.test (encrypted_path, EXISTS)) {
+            Xml.Doc* decrypted = encrypted_backend.load (encrypted_path);
+
+            // The time tracked while the statistics were locked is added to them once they are open
+            Day[] pending = {};
+            foreach (unowned Day day in all_days) {
+                if (!is_empty_day (day)) {
+                    var copy = new Day.for_date (day.date);
+                    add_day_times (copy, day);
+                    pending += copy;
+                }
+            }
+
+            backend = encrypted_backend;
+            read_only = lock_failed;
+            replace_document (decrypted);
+
+            foreach (Day day in pending) {
+                add_day_times (find_or_add_day (day.date), day);
+            }
+            if (pending.length > 0) {
+                recompute ();
+            }
+        } else {
+            backend = encrypted_backend;
+            read_only = lock_failed;
+        }
+
+        locked = false;
+        save ();
+    }
+
+    /*
+     * From now on the statistics are saved encrypted with the passphrase. The passphrase cannot be
+     * recovered, the statistics are lost if it is forgotten.
+     */
+    public void enable_encryption (string passphrase) throws StatisticsError {
+        check_writable ();
+        migrate_encryption (new EncryptedBackend (passphrase), true);
+    }
+
+    public void disable_encryption () throws StatisticsError {
+        check_writable ();
+        migrate_encryption (new Settings ().storage_format.create_backend (), false);
+    }
+
+    /*
+     * Saves the statistics with the backend, and stores the setting before the old file is removed.
+     * Otherwise a crash before the settings are saved would leave the statistics where they are not
+     * looked for.
+     */
+    private void migrate_encryption (StorageBackend new_backend, bool encrypt) throws StatisticsError {
+        string old_path = path;
+        StorageBackend old_backend = backend;
+
+        backend = new_backend;
+        try {
+            write_document (backend, path);
+        } catch (StatisticsError e) {
+            backend = old_backend;
+            throw e;
+        }
+
+        new Settings ().store_encrypt_statistics (encrypt);
+        FileUtils.remove (old_path);
+    }
+
+    /*
+     * The statistics as they would be written to an XML file, without writing them. The notes of
+     * the days are included.
+     */
+    public string dump_xml () throws StatisticsError {
+        return new XmlBackend ().serialize (doc);
+    }
+
+    public void save () {
+        if (read_only) {
+            return;
+        }
+
+        message ("Saving document...");
+        try {
+            write_document (backend, path);
+        } catch (StatisticsError e) {
+            critical (e.message);
+        }
+    }
+
+    // Every write of the statistics goes through here, so the saved signal is never missed
+    private void write_document (StorageBackend storage, string file_path) throws StatisticsError {
+        storage.save (doc, file_path);
+        saved (new DateTime.now_local ());
+    }
+}
+
+public enum Flowtime.Services.TimePeriod {
+    TODAY,
+    WEEK,
+    MONTH,
+    ALL;
+
+    public string to_string () {
+        switch (this) {
+            case TODAY:
+                return _("Today");
+            case WEEK:
+                return _("This Week");
+            case MONTH:
+                return _("This Month");
+            case ALL:
+                return _("All Time");
+            default:
+                assert_not_reached ();
         }
     }
 }
+
+// What makes a day of the week the most productive one
+public enum Flowtime.Services.ProductiveDayMetric {
+    // The highest average worktime
+    AVERAGE_WORKTIME,
+    // The most days with work
+    FREQUENCY,
+    // The single day with the most worktime
+    PEAK_DAY
+}
@@ -0,0 +1,49 @@
+/* StorageBackend.vala
+ *
+ * Copyright 2023 Diego Iván <diegoivan.mae@gmail.com>
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+/*
+ * The statistics are always kept in memory as an XML document. A backend reads that document
+ * from its own file format and writes it back.
+ */
+public interface Flowtime.Services.StorageBackend : Object {
+    public abstract string filename { get; }
+
+    public abstract Xml.Doc* load (string path) throws StatisticsError;
+    public abstract void save (Xml.Doc* doc, string path) throws StatisticsError;
+}
+
+public enum Flowtime.Services.StorageFormat {
+    XML,
+    JSON,
+    SQLITE;
+
+    public string to_string () {
+        switch (this) {
+            case XML:
+                return "XML";
+            case JSON:
+                return "JSON";
+            case SQLITE:
+                return "SQLite";
+            default:
+                assert_not_reached ();
+        }
+    }
+
+    public StorageBackend create_backend () {
+        switch (this) {
+            case XML:
+                return new XmlBackend ();
+            case JSON:
+                return new JsonBackend ();
+            case SQLITE:
+                return new SqliteBackend ();
+            default:
+                assert_not_reached ();
+        }
+    }
+}
@@ -0,0 +1,59 @@
+/* Terminology.vala
+ *
+ * Copyright 2023 Diego Iván <diegoivan.mae@gmail.com>
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+/*
+ * The words used for the stages in the interface. Only the labels change, the statistics keep
+ * their element names.
+ */
+public enum Flowtime.Services.Terminology {
+    WORK,
+    STUDY;
+
+    public string work_label () {
+        switch (this) {
+            case WORK:
+                return _("Work");
+            case STUDY:
+                return _("Study");
+            default:
+                assert_not_reached ();
+        }
+    }
+
+    public string break_label () {
+        switch (this) {
+            case WORK:
+                return _("Break");
+            case STUDY:
+                return _("Rest");
+            default:
+                assert_not_reached ();
+        }
+    }
+
+    public string work_stage_label () {
+        switch (this) {
+            case WORK:
+                return _("Work Stage");
+            case STUDY:
+                return _("Study Stage");
+            default:
+                assert_not_reached ();
+        }
+    }
+
+    public string break_stage_label () {
+        switch (this) {
+            case WORK:
+                return _("Break Stage");
+            case STUDY:
+                return _("Rest Stage");
+            default:
+                assert_not_reached ();
+        }
+    }
+}
@@ -27,16 +27,58 @@ public class Flowtime.Services.Timer : Object {
     }
 
     public TimerMode mode { get; private set; default = WORK; }
+
+    // What the timer is doing right now, so the interface can bind to a single property
+    public TimerPhase phase {
+        get {
+            if (!running) {
+                return IDLE;
+            }
+            return mode == WORK ? TimerPhase.WORK : TimerPhase.BREAK;
+        }
+    }
     public string formatted_time { get; private set; }
+    // Set while the work stage is longer than the long session warning, until a break is taken
+    public bool over_soft_cap { get; private set; default = false; }
+    // How insistent the reminders to take a break are, it goes back to none once a break is taken
+    public ReminderLevel reminder_level { get; private set; default = NONE; }
 
     public signal void updated ();
     public signal void done ();
 
-    private const int INTERVAL_MILLISECONDS = 1000;
+    // Emitted when the break stage begins, depending on whether it was started automatically
+    public signal void break_started ();
+    public signal void break_waiting ();
+    public signal void soft_cap_reached ();
+    public signal void break_reminder (ReminderLevel level);
+
+    private const int DEFAULT_MIN_BREAK = 60;
+    private const int DEFAULT_MAX_BREAK = 3600;
     private uint? timeout_id = null;
 
     private DateTime? last_datetime = null;
+    private DateTime? paused_datetime = null;
     private int initial_breaktime = 0;
+    // Work seconds of the current stage that were already saved when the session was split
+    private int saved_seconds = 0;
+
+    private int _banked_break = 0;
+    private DateTime? bank_date = null;
+
+    // The work stage that was just ended, so it can be continued within the session grace period
+    private DateTime? work_ended_datetime = null;
+    private int ended_work_seconds = 0;
+    private int ended_banked_break = 0;
+    // Set when the work of the stage is part of the session that was saved before
+    private bool continuing_session = false;
+
+    // Worktime counted since the start of the clock hour in hour_start
+    private int hour_worktime = 0;
+    private DateTime? hour_start = null;
+
+    // The reminders to take a break given in the work stage that were not followed by a break
+    private int ignored_reminders = 0;
+    private int last_reminder_seconds = 0;
 
     ~Timer () {
         stop ();
@@ -45,62 +87,404 @@ public class Flowtime.Services.Timer : Object {
 
     construct {
         seconds = 0;
+        notify["running"].connect (() => notify_property ("phase"));
+        notify["mode"].connect (() => notify_property ("phase"));
+
+        var settings = new Settings ();
+        settings.notify["timer-display"].connect (() => {
+            formatted_time = format_time ();
+        });
+        notify["mode"].connect (() => {
+            settings.last_mode = mode;
+        });
+
+        // Time tracked while the sample history is shown would be lost, so the timer is stopped
+        var statistics = new Statistics ();
+        statistics.notify["sample-mode"].connect (() => {
+            if (statistics.sample_mode && running) {
+                stop ();
+                save_to_statistics ();
+            }
+        });
+    }
+
+    /*
+     * Prepares the stage chosen to open Flowtime in. A break begun this way has the minimum break
+     * length, since no work was done to earn it.
+     */
+    public void restore_startup_phase () {
+        var settings = new Settings ();
+        TimerMode startup_mode = settings.startup_phase.mode (settings.last_mode);
+        if (startup_mode != BREAK || is_used) {
+            return;
+        }
+
+        initial_breaktime = settings.min_break_seconds;
+        seconds = initial_breaktime;
+        mode = BREAK;
+    }
+
+    /*
+     * Starts the work stage as Flowtime is launched, if it is set to. The timer is left as it is
+     * when it opens in the break stage or is already in use.
+     */
+    public void start_on_launch () {
+        var settings = new Settings ();
+        if (!settings.autostart_on_launch || settings.startup_phase == IDLE) {
+            return;
+        }
+
+        if (mode == WORK && !is_used) {
+            start ();
+        }
     }
 
     public void start () {
+        var statistics = new Statistics ();
+        if (statistics.sample_mode) {
+            return;
+        }
+        statistics.ensure_today ();
+
+        save_pausetime ();
         last_datetime = new DateTime.now_utc ();
         running = true;
-        timeout_id = Timeout.add (INTERVAL_MILLISECONDS, timeout);
+        var settings = new Settings ();
+        timeout_id = Timeout.add_seconds (settings.tick_interval, timeout);
     }
 
     public void stop () {
+        if (running && mode == WORK) {
+            paused_datetime = new DateTime.now_utc ();
+        }
+
         running = false;
         if (timeout_id == null) {
             return;
         }
         Source.remove (timeout_id);
+        timeout_id = null;
+    }
+
+    // Stops the timer without counting the time until it is started again as paused time
+    public void suspend () {
+        stop ();
+        paused_datetime = null;
     }
 
     public void next_mode () {
         stop ();
         last_datetime = null;
-        save_to_statistics ();
+        save_pausetime ();
 
         var settings = new Settings ();
 
+        if (mode == BREAK && resumes_within_grace ()) {
+            continue_session ();
+            if (settings.autostart) {
+                start ();
+            }
+            return;
+        }
+
+        save_to_statistics ();
+
         /*
          * This would mean that we want to change the mode to break. We will obtain the seconds for this
          * mode and change it accordingly
          */
         if (mode == WORK) {
-            initial_breaktime = (int) (seconds * settings.break_percentage / 100);
+            work_ended_datetime = new DateTime.now_utc ();
+            ended_work_seconds = seconds;
+            ended_banked_break = banked_break ();
+
+            initial_breaktime = suggested_break (seconds) + banked_break ();
+            _banked_break = 0;
             seconds = initial_breaktime;
+            over_soft_cap = false;
+            reminder_level = NONE;
+            ignored_reminders = 0;
             mode = BREAK;
         }
         else {
+            if (settings.bank_breaks && seconds > 0) {
+                bank_break (seconds);
+            }
+
             // Reset timer in case the next mode is work mode
             seconds = 0;
+            saved_seconds = 0;
             initial_breaktime = 0;
             mode = WORK;
         }
 
+        if (mode == BREAK) {
+            if (settings.autostart || settings.auto_start_break) {
+                start ();
+                break_started ();
+            } else {
+                break_waiting ();
+            }
+            return;
+        }
+
         if (settings.autostart) {
             start ();
         }
     }
 
+    /*
+     * Going back to work within the grace period after a work stage is over continues the same
+     * session, without a break being saved.
+     */
+    private bool resumes_within_grace () {
+        if (work_ended_datetime == null || seconds == 0) {
+            return false;
+        }
+
+        var settings = new Settings ();
+        TimeSpan elapsed = new DateTime.now_utc ().difference (work_ended_datetime);
+        return elapsed <= settings.session_grace * TimeSpan.SECOND;
+    }
+
+    private void continue_session () {
+        work_ended_datetime = null;
+        _banked_break = ended_banked_break;
+        initial_breaktime = 0;
+
+        // The work already counted was saved, only the time added from now on is saved again
+        seconds = ended_work_seconds;
+        saved_seconds = ended_work_seconds;
+        continuing_session = true;
+        mode = WORK;
+    }
+
+    /*
+     * The break earned by worktime seconds of work, within the minimum and maximum breaks. No break
+     * is earned before the work the policy asks for, so it agrees with time_until_break ().
+     */
+    public static int suggested_break (int worktime) {
+        var settings = new Settings ();
+        if (worktime < settings.break_policy.earned_after ()) {
+            return 0;
+        }
+
+        int break_seconds = settings.break_policy.break_for (worktime);
+        if (break_seconds == 0) {
+            return 0;
+        }
+        break_seconds = settings.break_rounding.round (break_seconds);
+
+        int min = settings.min_break_seconds;
+        int max = settings.max_break_seconds;
+        if (min > max) {
+            warning ("Minimum break is longer than the maximum break, using the defaults");
+            min = DEFAULT_MIN_BREAK;
+            max = DEFAULT_MAX_BREAK;
+        }
+
+        return break_seconds.clamp (min, max);
+    }
+
+    /*
+     * Seconds of work left until a break is earned under the active break policy, zero once it has
+     * been earned or while the break stage is running.
+     */
+    public int time_until_break () {
+        if (mode != WORK) {
+            return 0;
+        }
+
+        var settings = new Settings ();
+        return int.max (settings.break_policy.earned_after () - seconds, 0);
+    }
+
+    /*
+     * Break time skipped earlier today that will be added to the next break. The bank is emptied
+     * once the day is over.
+     */
+    public int banked_break () {
+        if (bank_date == null || !TimeUtils.same_day (bank_date, new DateTime.now_local ())) {
+            return 0;
+        }
+        return _banked_break;
+    }
+
+    private void bank_break (int break_seconds) {
+        _banked_break = banked_break () + break_seconds;
+        bank_date = new DateTime.now_local ();
+    }
+
+    /*
+     * Seconds left of the suggested break, counting the banked break that was added to it. It is
+     * zero once the break is over, or outside of the break stage. The time since the last tick is
+     * counted too, so it is exact however long the tick interval is.
+     */
+    public int remaining_break () {
+        if (mode != BREAK) {
+            return 0;
+        }
+
+        int remaining = seconds;
+        if (running && last_datetime != null) {
+            remaining -= (int) (new DateTime.now_utc ().difference (last_datetime) / TimeSpan.SECOND);
+        }
+        return int.max (remaining, 0);
+    }
+
+    // True while the break stage is waiting to be started for the first time
+    public bool break_pending () {
+        return mode == BREAK && !running && seconds == initial_breaktime;
+    }
+
+    // Whether starting the timer by hand now must be confirmed first
+    public bool start_needs_confirmation () {
+        var settings = new Settings ();
+        return settings.confirm_break_start && break_pending ();
+    }
+
+    // The break that would be taken if the work stage was stopped now
+    public int earned_break_preview () {
+        if (mode != WORK) {
+            return 0;
+        }
+        return suggested_break (seconds) + banked_break ();
+    }
+
     private string format_time () {
-        return "%02d:%02d".printf (seconds / 60, seconds % 60);
+        var settings = new Settings ();
+        return TimeUtils.format_elapsed (seconds, settings.timer_display);
     }
 
     public void save_to_statistics () {
         var statistics = new Statistics ();
         if (mode == WORK) {
-            statistics.add_time_to_mode (mode, seconds);
+            statistics.add_time_to_mode (mode, seconds - saved_seconds, !continuing_session);
+            saved_seconds = seconds;
+            continuing_session = false;
         }
         else {
             statistics.add_time_to_mode (mode, initial_breaktime - seconds);
         }
+
+        statistics.ensure_today ();
+    }
+
+    /*
+     * When splitting is enabled, the work done before midnight is saved to the day that is ending
+     * and the rest of the session is saved to the new day.
+     */
+    internal void split_at_midnight (DateTime current_time) {
+        var settings = new Settings ();
+        if (!settings.split_at_midnight) {
+            return;
+        }
+
+        DateTime midnight = TimeUtils.normalize_to_local_midnight (current_time);
+        int before_midnight = (int) (midnight.difference (last_datetime) / TimeSpan.SECOND);
+
+        var statistics = new Statistics ();
+        statistics.add_time_to_mode (WORK, seconds + before_midnight - saved_seconds,
+                                     !continuing_session, midnight);
+        saved_seconds = seconds + before_midnight;
+        continuing_session = false;
+        statistics.ensure_today ();
+    }
+
+    // The worktime counted during the current hour of the clock
+    public int worktime_this_hour () {
+        return worktime_in_hour (new DateTime.now_local ());
+    }
+
+    // The worktime counted during the hour of the clock that time is in, if it is the last one
+    internal int worktime_in_hour (DateTime time) {
+        if (hour_start == null || !hour_start.equal (current_hour_start (time.to_local ()))) {
+            return 0;
+        }
+        return hour_worktime;
+    }
+
+    internal void add_hour_worktime (DateTime current_time, int time_seconds) {
+        DateTime current_hour = current_hour_start (current_time.to_local ());
+
+        if (hour_start == null || !hour_start.equal (current_hour)) {
+            // Only the part of the tick after the top of the hour belongs to the new hour
+            int since_hour = (int) (current_time.difference (current_hour) / TimeSpan.SECOND);
+            hour_worktime = int.min (time_seconds, since_hour);
+            hour_start = current_hour;
+            return;
+        }
+
+        hour_worktime += time_seconds;
+    }
+
+    private DateTime current_hour_start (DateTime time) {
+        return new DateTime.local (time.get_year (), time.get_month (), time.get_day_of_month (),
+                                   time.get_hour (), 0, 0);
+    }
+
+    // The warning is only given once per work stage, the timer is never stopped by it
+    private void check_soft_cap () {
+        var settings = new Settings ();
+        if (over_soft_cap) {
+            check_reminder ();
+            return;
+        }
+
+        if (settings.soft_cap == 0 || seconds < settings.soft_cap * 60) {
+            return;
+        }
+
+        over_soft_cap = true;
+        reminder_level = GENTLE;
+        last_reminder_seconds = seconds;
+        soft_cap_reached ();
+    }
+
+    /*
+     * Once the warning was given, the break is reminded again every reminder interval. Every
+     * reminder that was not followed by a break counts as ignored, and after enough of them the
+     * reminders become firm and are given more often.
+     */
+    private void check_reminder () {
+        var settings = new Settings ();
+        if (settings.reminder_interval == 0) {
+            return;
+        }
+
+        int interval = settings.reminder_interval;
+        if (reminder_level == FIRM) {
+            interval = settings.firm_reminder_interval;
+        }
+        if (seconds - last_reminder_seconds < interval * 60) {
+            return;
+        }
+
+        ignored_reminders++;
+        if (ignored_reminders >= settings.reminder_escalation) {
+            reminder_level = FIRM;
+        }
+        last_reminder_seconds = seconds;
+        break_reminder (reminder_level);
+    }
+
+    /*
+     * Time spent paused during a work stage is not a break, so it is saved apart from the
+     * breaktime once the timer is resumed or the stage is changed.
+     */
+    private void save_pausetime () {
+        if (paused_datetime == null) {
+            return;
+        }
+
+        var current_time = new DateTime.now_utc ();
+        int pause_seconds = (int) (current_time.difference (paused_datetime) / TimeSpan.SECOND);
+        paused_datetime = null;
+
+        if (pause_seconds > 0) {
+            var statistics = new Statistics ();
+            statistics.add_pausetime (pause_seconds);
+        }
     }
 
     protected bool timeout () {
@@ -114,12 +498,22 @@ public class Flowtime.Services.Timer : Object {
             last_datetime = current_time;
         }
 
-        // Obtaining the difference between the last and current times, casting it to seconds
+        /*
+         * Obtaining the difference between the last and current times, casting it to seconds. The
+         * fraction of a second left over is counted on the next tick, so it is not lost however
+         * long the tick interval is.
+         */
         int time_seconds = (int) (current_time.difference (last_datetime) / TimeSpan.SECOND);
+        DateTime counted_time = last_datetime.add_seconds (time_seconds);
 
         switch (mode) {
             case WORK:
+                if (!TimeUtils.same_day (last_datetime, current_time)) {
+                    split_at_midnight (current_time);
+                }
+                add_hour_worktime (current_time, time_seconds);
                 seconds += time_seconds;
+                check_soft_cap ();
                 break;
 
             case BREAK:
@@ -137,7 +531,7 @@ public class Flowtime.Services.Timer : Object {
             default:
                 assert_not_reached ();
         }
-        last_datetime = current_time;
+        last_datetime = counted_time;
 
         return true;
     }
@@ -148,13 +542,51 @@ public enum Flowtime.Services.TimerMode {
     BREAK;
 
     public string to_string () {
+        var settings = new Settings ();
         switch (this) {
             case WORK:
-                return _("Work");
+                return settings.terminology.work_label ();
             case BREAK:
-                return _("Break");
+                return settings.terminology.break_label ();
             default:
                 assert_not_reached ();
         }
     }
 }
+
+public enum Flowtime.Services.TimerPhase {
+    IDLE,
+    WORK,
+    BREAK
+}
+
+public enum Flowtime.Services.TimerDisplay {
+    MINUTES_SECONDS,
+    HOURS_MINUTES,
+    HOURS_MINUTES_SECONDS
+}
+
+public enum Flowtime.Services.ReminderLevel {
+    NONE,
+    GENTLE,
+    FIRM
+}
+
+public enum Flowtime.Services.StartupPhase {
+    WORK,
+    BREAK,
+    LAST_USED,
+    IDLE;
+
+    // The stage the timer opens in, given the one it was in when Flowtime was last used
+    public TimerMode mode (TimerMode last_mode) {
+        switch (this) {
+            case BREAK:
+                return TimerMode.BREAK;
+            case LAST_USED:
+                return last_mode;
+            default:
+                return TimerMode.WORK;
+        }
+    }
+}
@@ -37,7 +37,9 @@ public class Flowtime.Services.TonePlayer : Object {
     }
 
     public void register_sound (string name, string sound_file) {
-        var file = File.new_for_uri (@"resource://$(app.resource_base_path)/$(sound_file)");
+        // The default application is used, so the tests can be built without main.vala
+        string base_path = GLib.Application.get_default ().resource_base_path;
+        var file = File.new_for_uri (@"resource://$(base_path)/$(sound_file)");
         var sound = Sound () {
             name = name,
             sound_file = file
@@ -52,6 +54,9 @@ public class Flowtime.Services.TonePlayer : Object {
             media_file.pause ();
         }
         swap_media_file (sound);
+
+        var settings = new Settings ();
+        media_file.volume = settings.sound_volume;
         media_file.play_now ();
     }
 
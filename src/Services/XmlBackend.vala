@@ -0,0 +1,107 @@
+/* XmlBackend.vala
+ *
+ * Copyright 2023 Diego Iván <diegoivan.mae@gmail.com>
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+public class Flowtime.Services.XmlBackend : Object, StorageBackend {
+    public string filename {
+        get {
+            return "statistics.xml";
+        }
+    }
+
+    public Xml.Doc* load (string path) throws StatisticsError {
+        string contents;
+        try {
+            FileUtils.get_contents (path, out contents);
+        } catch (FileError e) {
+            throw new StatisticsError.IO ("%s cannot be read: %s".printf (path, e.message));
+        }
+
+        if (has_conflict_markers (contents)) {
+            throw new StatisticsError.MERGE_CONFLICT ("%s has unresolved merge conflicts".printf (path));
+        }
+
+        return parse (contents, path);
+    }
+
+    public Xml.Doc* parse (string contents, string path) throws StatisticsError {
+        Xml.Doc* parsed = Xml.Parser.parse_memory (contents, contents.length);
+        if (parsed != null) {
+            return parsed;
+        }
+
+        Xml.Error* xml_error = Xml.get_last_error ();
+        if (xml_error == null) {
+            throw new StatisticsError.PARSE ("%s cannot be parsed".printf (path));
+        }
+
+        // The column of the error is kept in int2 by libxml
+        throw new StatisticsError.PARSE ("%s cannot be parsed at line %d, column %d: %s".printf (
+            path, xml_error->line, xml_error->int2, xml_error->message.strip ()
+        ));
+    }
+
+    // Files synchronized with git may be left with the markers of a merge conflict
+    private bool has_conflict_markers (string contents) {
+        return contents.has_prefix ("<<<<<<< ") || "\n<<<<<<< " in contents;
+    }
+
+    /*
+     * Splits a file with merge conflicts in both of its versions. The lines outside the conflicts
+     * belong to both versions.
+     */
+    public void split_conflict (string contents, out string ours, out string theirs) {
+        var ours_builder = new StringBuilder ();
+        var theirs_builder = new StringBuilder ();
+        bool in_ours = false;
+        bool in_theirs = false;
+
+        foreach (unowned string line in contents.split ("\n")) {
+            if (line.has_prefix ("<<<<<<<")) {
+                in_ours = true;
+                continue;
+            }
+            if (line.has_prefix ("=======") && in_ours) {
+                in_ours = false;
+                in_theirs = true;
+                continue;
+            }
+            if (line.has_prefix (">>>>>>>") && in_theirs) {
+                in_theirs = false;
+                continue;
+            }
+
+            if (!in_theirs) {
+                ours_builder.append (line).append_c ('\n');
+            }
+            if (!in_ours) {
+                theirs_builder.append (line).append_c ('\n');
+            }
+        }
+
+        ours = ours_builder.str;
+        theirs = theirs_builder.str;
+    }
+
+    public void save (Xml.Doc* doc, string path) throws StatisticsError {
+        try {
+            FileUtils.set_contents (path, serialize (doc));
+        } catch (FileError e) {
+            throw new StatisticsError.IO ("%s could not be written: %s".printf (path, e.message));
+        }
+    }
+
+    public string serialize (Xml.Doc* doc) throws StatisticsError {
+        string contents;
+        int length;
+        doc->dump_memory (out contents, out length);
+
+        if (contents == null) {
+            throw new StatisticsError.IO ("The statistics could not be written");
+        }
+        return contents;
+    }
+}
@@ -0,0 +1,160 @@
+/* TimeUtils.vala
+ *
+ * Copyright 2022-2023 Diego Iván <diegoivan.mae@gmail.com>
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+namespace Flowtime.TimeUtils {
+    public string format_time (int seconds) {
+        const int MINUTE = 60;
+        const int HOUR = 3600;
+        const int DAY = 86400;
+        string unit = "";
+
+        var settings = new Services.Settings ();
+        if (settings.round_to_minutes) {
+            // Only the presentation is rounded, stored times keep their precision
+            seconds = ((seconds + MINUTE / 2) / MINUTE) * MINUTE;
+
+            if (seconds < HOUR) {
+                // translators: A time in minutes, for example: 16m
+                return _("%dm").printf (seconds / MINUTE);
+            }
+
+            if (seconds < DAY) {
+                // translators: A time in hours and minutes, for example: 2h 16m
+                return _("%dh %dm").printf (seconds / HOUR, (seconds % HOUR) / MINUTE);
+            }
+        }
+
+        if (seconds < MINUTE) {
+            unit = _("seconds");
+            return @"$seconds $unit";
+        }
+
+        if (seconds < HOUR) {
+            unit = _("minutes");
+            return "%02d:%02d %s".printf (seconds / MINUTE, seconds % MINUTE, unit);
+        }
+
+        if (seconds < DAY) {
+            int hours = seconds / HOUR;
+            int minutes = (seconds % HOUR) / MINUTE;
+
+            if (hours == 1 && minutes == 0) {
+                unit = _("hour");
+            }
+            else {
+                unit = _("hours");
+            }
+
+            return "%d:%02d %s".printf (seconds / HOUR, minutes, unit);
+        }
+
+        int days = seconds / DAY;
+        if (days == 1) {
+            unit = _("day");
+        }
+        else {
+            unit = _("days");
+        }
+
+        return @"$days $unit";
+    }
+
+    // The time of the timer, with the minutes going past 60 unless the hours are shown
+    public string format_elapsed (int seconds, Services.TimerDisplay display) {
+        switch (display) {
+            case MINUTES_SECONDS:
+                return "%02d:%02d".printf (seconds / 60, seconds % 60);
+            case HOURS_MINUTES:
+                return "%02d:%02d".printf (seconds / 3600, (seconds % 3600) / 60);
+            case HOURS_MINUTES_SECONDS:
+                return "%02d:%02d:%02d".printf (seconds / 3600, (seconds % 3600) / 60, seconds % 60);
+            default:
+                assert_not_reached ();
+        }
+    }
+
+    /*
+     * Dates are compared by their calendar date in local time. Comparing elapsed seconds breaks on
+     * days that last 23 or 25 hours because of daylight saving transitions.
+     */
+    public bool same_day (DateTime a, DateTime b) {
+        DateTime local_a = a.to_local ();
+        DateTime local_b = b.to_local ();
+
+        return local_a.get_year () == local_b.get_year ()
+            && local_a.get_month () == local_b.get_month ()
+            && local_a.get_day_of_month () == local_b.get_day_of_month ();
+    }
+
+    public DateTime normalize_to_local_midnight (DateTime date) {
+        DateTime local = date.to_local ();
+        return new DateTime.local (local.get_year (), local.get_month (), local.get_day_of_month (), 0, 0, 0);
+    }
+
+    // The number of calendar days from one date to another, regardless of the length of each day
+    public int days_between (DateTime from, DateTime to) {
+        DateTime local_from = from.to_local ();
+        DateTime local_to = to.to_local ();
+
+        var utc_from = new DateTime.utc (local_from.get_year (), local_from.get_month (),
+                                         local_from.get_day_of_month (), 0, 0, 0);
+        var utc_to = new DateTime.utc (local_to.get_year (), local_to.get_month (),
+                                       local_to.get_day_of_month (), 0, 0, 0);
+
+        return (int) (utc_to.difference (utc_from) / TimeSpan.DAY);
+    }
+
+    /*
+     * Reads a date saved in any variant of ISO 8601. A date and time without a time zone is in
+     * local time, and a date without a time is at local midnight. Null is returned for anything
+     * else.
+     */
+    public DateTime? parse_date (string? text) {
+        if (text == null) {
+            return null;
+        }
+
+        var parsed = new DateTime.from_iso8601 (text, new TimeZone.local ());
+        if (parsed != null) {
+            return parsed;
+        }
+
+        int year, month, day;
+        if (text.strip ().scanf ("%d-%d-%d", out year, out month, out day) == 3) {
+            return new DateTime.local (year, month, day, 0, 0, 0);
+        }
+        return null;
+    }
+
+    /*
+     * A label for a date relative to the current date: today, yesterday, the day of the week
+     * within the last week, and the date in the format of the locale otherwise. The current date
+     * is now, or the date of now_local if it is given.
+     */
+    public string relative_date_label (DateTime date, DateTime? now_local = null) {
+        int days_ago = days_between (date, now_local ?? new DateTime.now_local ());
+
+        if (days_ago == 0) {
+            return _("Today");
+        }
+        if (days_ago == 1) {
+            return _("Yesterday");
+        }
+        if (days_ago > 1 && days_ago < 7) {
+            return date.to_local ().format ("%A");
+        }
+        return date.to_local ().format ("%x");
+    }
+
+    // Midnight of the first day of the week that contains date, with 1 for Monday to 7 for Sunday
+    public DateTime week_start (DateTime date, int first_day) {
+        DateTime midnight = normalize_to_local_midnight (date);
+        int offset = (midnight.get_day_of_week () - first_day + 7) % 7;
+
+        return normalize_to_local_midnight (midnight.add_days (-offset).add_hours (12));
+    }
+}
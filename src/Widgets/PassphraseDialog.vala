@@ -0,0 +1,34 @@
+/* PassphraseDialog.vala
+ *
+ * Copyright 2023 Diego Iván <diegoivan.mae@gmail.com>
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+public class Flowtime.PassphraseDialog : Adw.AlertDialog {
+    private Gtk.PasswordEntry entry = new Gtk.PasswordEntry () {
+        show_peek_icon = true,
+        activates_default = true
+    };
+
+    public PassphraseDialog (string heading, string body, string confirm_label) {
+        Object (heading: heading, body: body, close_response: "cancel", default_response: "confirm");
+
+        extra_child = entry;
+        add_response ("cancel", _("Cancel"));
+        add_response ("confirm", confirm_label);
+        set_response_appearance ("confirm", SUGGESTED);
+        set_response_enabled ("confirm", false);
+
+        entry.changed.connect (() => set_response_enabled ("confirm", entry.text != ""));
+    }
+
+    // The passphrase that was entered, or null if the dialog was cancelled
+    public async string? ask (Gtk.Widget? parent) {
+        string response = yield choose (parent, null);
+        if (response != "confirm") {
+            return null;
+        }
+        return entry.text;
+    }
+}
@@ -18,6 +18,98 @@ namespace Flowtime {
         private unowned Adw.SpinRow percentage_spinrow;
         [GtkChild]
         private unowned Adw.SwitchRow screensaver_row;
+        [GtkChild]
+        private unowned Adw.SwitchRow round_row;
+        [GtkChild]
+        private unowned Adw.ComboRow terminology_row;
+        [GtkChild]
+        private unowned Adw.ComboRow timer_display_row;
+        [GtkChild]
+        private unowned Adw.ComboRow policy_row;
+        [GtkChild]
+        private unowned Adw.SwitchRow bank_row;
+        [GtkChild]
+        private unowned Adw.ComboRow rounding_row;
+        [GtkChild]
+        private unowned Adw.SwitchRow auto_break_row;
+        [GtkChild]
+        private unowned Adw.SwitchRow confirm_break_row;
+        [GtkChild]
+        private unowned Adw.SwitchRow launch_row;
+        [GtkChild]
+        private unowned Adw.ComboRow startup_row;
+        [GtkChild]
+        private unowned Adw.SwitchRow sounds_row;
+        [GtkChild]
+        private unowned Gtk.Scale volume_scale;
+        [GtkChild]
+        private unowned Adw.SpinRow min_break_spinrow;
+        [GtkChild]
+        private unowned Adw.SpinRow max_break_spinrow;
+        [GtkChild]
+        private unowned Adw.SpinRow grace_spinrow;
+        [GtkChild]
+        private unowned Adw.SpinRow tick_spinrow;
+        [GtkChild]
+        private unowned Adw.SwitchRow notifications_row;
+        [GtkChild]
+        private unowned Adw.SpinRow nudge_spinrow;
+        [GtkChild]
+        private unowned Adw.SpinRow soft_cap_spinrow;
+        [GtkChild]
+        private unowned Adw.SwitchRow soft_cap_row;
+        [GtkChild]
+        private unowned Adw.SwitchRow summary_row;
+        [GtkChild]
+        private unowned Adw.SpinRow summary_spinrow;
+        [GtkChild]
+        private unowned Adw.ComboRow format_row;
+        [GtkChild]
+        private unowned Adw.SwitchRow encrypt_row;
+        [GtkChild]
+        private unowned Adw.SwitchRow archive_row;
+        [GtkChild]
+        private unowned Adw.SwitchRow include_archive_row;
+        [GtkChild]
+        private unowned Adw.SpinRow goal_spinrow;
+        [GtkChild]
+        private unowned Adw.SpinRow weekly_goal_spinrow;
+        [GtkChild]
+        private unowned Adw.ComboRow metric_row;
+        [GtkChild]
+        private unowned Adw.SwitchRow auto_export_row;
+        [GtkChild]
+        private unowned Adw.ComboRow export_format_row;
+        [GtkChild]
+        private unowned Adw.EntryRow export_path_row;
+        [GtkChild]
+        private unowned Adw.ComboRow backup_schedule_row;
+        [GtkChild]
+        private unowned Adw.EntryRow backup_path_row;
+        [GtkChild]
+        private unowned Adw.SpinRow backup_count_spinrow;
+        [GtkChild]
+        private unowned Adw.SpinRow focus_gap_spinrow;
+        [GtkChild]
+        private unowned Adw.SwitchRow deep_work_row;
+        [GtkChild]
+        private unowned Adw.SpinRow deep_work_spinrow;
+        [GtkChild]
+        private unowned Adw.SpinRow afternoon_spinrow;
+        [GtkChild]
+        private unowned Adw.SpinRow work_start_spinrow;
+        [GtkChild]
+        private unowned Adw.SpinRow work_end_spinrow;
+        [GtkChild]
+        private unowned Adw.SpinRow evening_spinrow;
+        [GtkChild]
+        private unowned Adw.ComboRow week_start_row;
+        [GtkChild]
+        private unowned Adw.SwitchRow skip_weekends_row;
+        [GtkChild]
+        private unowned Adw.SwitchRow midnight_row;
+        [GtkChild]
+        private unowned Adw.SwitchRow empty_days_row;
 
         private Services.Settings settings = new Services.Settings ();
 
@@ -37,6 +129,18 @@ namespace Flowtime {
                 SYNC_CREATE | BIDIRECTIONAL
             );
 
+            settings.bind_property ("confirm-break-start",
+                                    confirm_break_row, "active",
+                                    SYNC_CREATE | BIDIRECTIONAL);
+
+            settings.bind_property ("autostart-on-launch",
+                                    launch_row, "active",
+                                    SYNC_CREATE | BIDIRECTIONAL);
+
+            settings.bind_property ("auto-start-break",
+                                    auto_break_row, "active",
+                                    SYNC_CREATE | BIDIRECTIONAL);
+
             settings.bind_property ("months-saved",
                 months_spinrow, "value",
                 SYNC_CREATE | BIDIRECTIONAL
@@ -46,10 +150,194 @@ namespace Flowtime {
                                     screensaver_row, "active",
                                     SYNC_CREATE | BIDIRECTIONAL);
 
+            settings.bind_property ("play-sounds",
+                                    sounds_row, "active",
+                                    SYNC_CREATE | BIDIRECTIONAL);
+
+            settings.bind_property ("sound-volume",
+                                    volume_scale.adjustment, "value",
+                                    SYNC_CREATE | BIDIRECTIONAL);
+
+            settings.bind_property ("min-break-seconds",
+                                    min_break_spinrow, "value",
+                                    SYNC_CREATE | BIDIRECTIONAL);
+
+            settings.bind_property ("max-break-seconds",
+                                    max_break_spinrow, "value",
+                                    SYNC_CREATE | BIDIRECTIONAL);
+
+            settings.bind_property ("session-grace",
+                                    grace_spinrow, "value",
+                                    SYNC_CREATE | BIDIRECTIONAL);
+
+            settings.bind_property ("tick-interval",
+                                    tick_spinrow, "value",
+                                    SYNC_CREATE | BIDIRECTIONAL);
+
+            settings.bind_property ("send-notifications",
+                                    notifications_row, "active",
+                                    SYNC_CREATE | BIDIRECTIONAL);
+
+            settings.bind_property ("soft-cap",
+                                    soft_cap_spinrow, "value",
+                                    SYNC_CREATE | BIDIRECTIONAL);
+
+            settings.bind_property ("soft-cap-notification",
+                                    soft_cap_row, "active",
+                                    SYNC_CREATE | BIDIRECTIONAL);
+
+            settings.bind_property ("nudge-delay",
+                                    nudge_spinrow, "value",
+                                    SYNC_CREATE | BIDIRECTIONAL);
+
+            settings.bind_property ("daily-summary",
+                                    summary_row, "active",
+                                    SYNC_CREATE | BIDIRECTIONAL);
+
+            settings.bind_property ("summary-time",
+                                    summary_spinrow, "value",
+                                    SYNC_CREATE | BIDIRECTIONAL);
+
+            settings.bind_property ("bank-breaks",
+                                    bank_row, "active",
+                                    SYNC_CREATE | BIDIRECTIONAL);
+
+            settings.bind_property ("archive-old-data",
+                                    archive_row, "active",
+                                    SYNC_CREATE | BIDIRECTIONAL);
+
+            settings.bind_property ("include-archive",
+                                    include_archive_row, "active",
+                                    SYNC_CREATE | BIDIRECTIONAL);
+
+            settings.bind_property ("daily-goal",
+                                    goal_spinrow, "value",
+                                    SYNC_CREATE | BIDIRECTIONAL);
+
+            settings.bind_property ("weekly-goal",
+                                    weekly_goal_spinrow, "value",
+                                    SYNC_CREATE | BIDIRECTIONAL);
+
+            settings.bind_property ("focus-gap",
+                                    focus_gap_spinrow, "value",
+                                    SYNC_CREATE | BIDIRECTIONAL);
+
+            settings.bind_property ("work-hours-start",
+                                    work_start_spinrow, "value",
+                                    SYNC_CREATE | BIDIRECTIONAL);
+
+            settings.bind_property ("work-hours-end",
+                                    work_end_spinrow, "value",
+                                    SYNC_CREATE | BIDIRECTIONAL);
+
+            settings.bind_property ("afternoon-start",
+                                    afternoon_spinrow, "value",
+                                    SYNC_CREATE | BIDIRECTIONAL);
+
+            settings.bind_property ("evening-start",
+                                    evening_spinrow, "value",
+                                    SYNC_CREATE | BIDIRECTIONAL);
+
+            settings.bind_property ("deep-work-only",
+                                    deep_work_row, "active",
+                                    SYNC_CREATE | BIDIRECTIONAL);
+
+            settings.bind_property ("deep-work-minutes",
+                                    deep_work_spinrow, "value",
+                                    SYNC_CREATE | BIDIRECTIONAL);
+
+            settings.bind_property ("streak-skip-weekends",
+                                    skip_weekends_row, "active",
+                                    SYNC_CREATE | BIDIRECTIONAL);
+
+            settings.bind_property ("keep-empty-days",
+                                    empty_days_row, "active",
+                                    SYNC_CREATE | BIDIRECTIONAL);
+
+            settings.bind_property ("split-at-midnight",
+                                    midnight_row, "active",
+                                    SYNC_CREATE | BIDIRECTIONAL);
+
+            settings.bind_property ("round-to-minutes",
+                                    round_row, "active",
+                                    SYNC_CREATE | BIDIRECTIONAL);
+
             settings.bind_property ("break-percentage",
                                     percentage_spinrow, "value",
                                     SYNC_CREATE | BIDIRECTIONAL);
 
+            policy_row.selected = (uint) settings.break_policy;
+            policy_row.notify["selected"].connect (on_policy_selected);
+            on_policy_selected ();
+
+            rounding_row.selected = (uint) settings.break_rounding;
+            rounding_row.notify["selected"].connect (() => {
+                settings.break_rounding = (Services.BreakRounding) rounding_row.selected;
+            });
+
+            startup_row.selected = (uint) settings.startup_phase;
+            startup_row.notify["selected"].connect (() => {
+                settings.startup_phase = (Services.StartupPhase) startup_row.selected;
+            });
+
+            timer_display_row.selected = (uint) settings.timer_display;
+            timer_display_row.notify["selected"].connect (() => {
+                settings.timer_display = (Services.TimerDisplay) timer_display_row.selected;
+            });
+
+            terminology_row.selected = (uint) settings.terminology;
+            terminology_row.notify["selected"].connect (() => {
+                settings.terminology = (Services.Terminology) terminology_row.selected;
+            });
+
+            encrypt_row.active = settings.encrypt_statistics;
+            encrypt_row.notify["active"].connect (() => {
+                if (encrypt_row.active != settings.encrypt_statistics) {
+                    change_encryption.begin ();
+                }
+            });
+
+            settings.bind_property ("auto-export",
+                                    auto_export_row, "active",
+                                    SYNC_CREATE | BIDIRECTIONAL);
+
+            settings.bind_property ("auto-export-path",
+                                    export_path_row, "text",
+                                    SYNC_CREATE | BIDIRECTIONAL);
+
+            export_format_row.selected = (uint) settings.auto_export_format;
+            export_format_row.notify["selected"].connect (() => {
+                settings.auto_export_format = (Services.ExportFormat) export_format_row.selected;
+            });
+
+            settings.bind_property ("backup-path",
+                                    backup_path_row, "text",
+                                    SYNC_CREATE | BIDIRECTIONAL);
+
+            settings.bind_property ("backup-count",
+                                    backup_count_spinrow, "value",
+                                    SYNC_CREATE | BIDIRECTIONAL);
+
+            backup_schedule_row.selected = (uint) settings.backup_schedule;
+            backup_schedule_row.notify["selected"].connect (() => {
+                settings.backup_schedule = (Services.BackupSchedule) backup_schedule_row.selected;
+            });
+
+            metric_row.selected = (uint) settings.productive_day_metric;
+            metric_row.notify["selected"].connect (() => {
+                settings.productive_day_metric = (Services.ProductiveDayMetric) metric_row.selected;
+            });
+
+            format_row.selected = (uint) settings.storage_format;
+            format_row.notify["selected"].connect (() => {
+                settings.storage_format = (Services.StorageFormat) format_row.selected;
+            });
+
+            week_start_row.selected = settings.week_start - 1;
+            week_start_row.notify["selected"].connect (() => {
+                settings.week_start = (int) week_start_row.selected + 1;
+            });
+
             var tone_player = new Services.TonePlayer ();
             foreach (unowned string key in tone_player.get_tone_keys ()) {
                 var row = new SoundRow (key);
@@ -63,10 +351,45 @@ namespace Flowtime {
                     row.check_button.active = true;
                 }
 
+                sounds_row.bind_property ("active", row, "sensitive", SYNC_CREATE);
+
                 sounds_group.add (row);
             }
 
             screensaver_row.visible = screensaver == null ? false : screensaver.supported;
         }
+
+        private void on_policy_selected () {
+            settings.break_policy = (Services.BreakPolicy) policy_row.selected;
+            percentage_spinrow.sensitive = settings.break_policy == PERCENTAGE;
+        }
+
+        private async void change_encryption () {
+            var statistics = new Services.Statistics ();
+
+            try {
+                if (!encrypt_row.active) {
+                    statistics.disable_encryption ();
+                    return;
+                }
+
+                var dialog = new PassphraseDialog (
+                    _("Encrypt Statistics"),
+                    _("The passphrase will be asked for every time Flowtime starts. If you forget it, your statistics cannot be recovered"),
+                    _("Encrypt")
+                );
+
+                string? passphrase = yield dialog.ask (this);
+                if (passphrase == null) {
+                    encrypt_row.active = false;
+                    return;
+                }
+
+                statistics.enable_encryption (passphrase);
+            } catch (Services.StatisticsError e) {
+                warning (e.message);
+                encrypt_row.active = settings.encrypt_statistics;
+            }
+        }
     }
 }
@@ -11,6 +11,10 @@ public class Flowtime.StatInfo : Adw.Bin {
     private unowned Adw.PreferencesGroup overview_group;
     [GtkChild]
     private unowned Gtk.Stack content_stack;
+    [GtkChild]
+    private unowned Gtk.Label work_label;
+    [GtkChild]
+    private unowned Gtk.Label break_label;
 
     public unowned Models.InformationHolder info_state { get; private set; }
     private Services.Statistics statistics = new Services.Statistics ();
@@ -30,6 +34,24 @@ public class Flowtime.StatInfo : Adw.Bin {
         typeof (StatGraph).ensure ();
     }
 
+    construct {
+        statistics.notify["today"].connect (() => {
+            if (time_period == TODAY) {
+                set_state_from_period ();
+            }
+        });
+
+        var settings = new Services.Settings ();
+        settings.notify["terminology"].connect (update_legend);
+        update_legend ();
+    }
+
+    private void update_legend () {
+        var settings = new Services.Settings ();
+        work_label.label = settings.terminology.work_label ();
+        break_label.label = settings.terminology.break_label ();
+    }
+
     private void format_description () {
         if (info_state.worktime == 0 && info_state.breaktime == 0) {
             content_stack.visible_child_name = "empty";
@@ -37,8 +59,8 @@ public class Flowtime.StatInfo : Adw.Bin {
         }
         content_stack.visible_child_name = "info";
 
-        string formatted_worktime = format_time (info_state.worktime);
-        string formatted_breaktime = format_time (info_state.breaktime);
+        string formatted_worktime = TimeUtils.format_time (info_state.worktime);
+        string formatted_breaktime = TimeUtils.format_time (info_state.breaktime);
 
         // translators: This phrase is used in the context: You've worked HH:MM:SS...
         overview_group.description = _("You've worked %s and have taken a break during %s.").printf (
@@ -46,47 +68,6 @@ public class Flowtime.StatInfo : Adw.Bin {
         );
     }
 
-    private string format_time (int seconds) {
-        const int MINUTE = 60;
-        const int HOUR = 3600;
-        const int DAY = 86400;
-        string unit = "";
-
-        if (seconds < MINUTE) {
-            unit = _("seconds");
-            return @"$seconds $unit";
-        }
-
-        if (seconds < HOUR) {
-            unit = _("minutes");
-            return "%02d:%02d %s".printf (seconds / MINUTE, seconds % MINUTE, unit);
-        }
-
-        if (seconds < DAY) {
-            int hours = seconds / HOUR;
-            int minutes = (seconds % HOUR) / MINUTE;
-
-            if (hours == 1 && minutes == 0) {
-                unit = _("hour");
-            }
-            else {
-                unit = _("hours");
-            }
-
-            return "%d:%02d %s".printf (seconds / HOUR, minutes, unit);
-        }
-
-        int days = seconds / DAY;
-        if (days == 1) {
-            unit = _("day");
-        }
-        else {
-            unit = _("days");
-        }
-
-        return @"$days $unit";
-    }
-
     private void disconnect_information_holder () {
         info_state.notify["worktime"].disconnect (format_description);
         info_state.notify["breaktime"].disconnect (format_description);
@@ -99,7 +80,7 @@ public class Flowtime.StatInfo : Adw.Bin {
 
         switch (time_period) {
             case TODAY:
-                info_state = statistics.today;
+                info_state = statistics.today_period;
                 break;
             case WEEK:
                 info_state = statistics.week;
@@ -7,6 +7,9 @@
 
 [GtkTemplate (ui = "/io/github/diegoivanme/flowtime/statpage.ui")]
 public class Flowtime.StatPage : Adw.Bin {
+    [GtkChild]
+    private unowned Adw.PreferencesGroup overview_group;
+
     public Services.TimePeriod selected_period { get; private set; default = TODAY; }
 
     static construct {
@@ -14,6 +17,13 @@ public class Flowtime.StatPage : Adw.Bin {
         typeof (TimePeriodRow).ensure ();
     }
 
+    construct {
+        var statistics = new Services.Statistics ();
+        statistics.saved.connect ((time) => {
+            overview_group.description = _("Last saved %s").printf (time.format ("%R"));
+        });
+    }
+
     [GtkCallback]
     private void on_details_button_clicked () {
         var stat_window = new StatsWindow ();
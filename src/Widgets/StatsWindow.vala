@@ -13,6 +13,8 @@ namespace Flowtime {
         [GtkChild]
         private unowned StatList break_list;
         [GtkChild]
+        private unowned Gtk.Label tracked_label;
+        [GtkChild]
         private unowned Gtk.Button save_button;
         [GtkChild]
         private unowned Gtk.Stack content_stack;
@@ -25,7 +27,7 @@ namespace Flowtime {
             }
 
             foreach (Models.Day day in statistics.all_days) {
-                var work_object = new Models.StatObject (day.date, day.worktime);
+                var work_object = new Models.StatObject (day.date, day.counted_worktime ());
                 work_list.append (work_object);
 
                 var break_object = new Models.StatObject (day.date, day.breaktime);
@@ -34,6 +36,10 @@ namespace Flowtime {
 
             if (!statistics.all_days.is_empty ()) {
                 work_list.description = _("%s is your most productive day of the week").printf (statistics.productive_day);
+                double tracked_hours = statistics.total_tracked_hours ();
+                // translators: For example: You have tracked 120.5 hours with Flowtime
+                tracked_label.label = _("You have tracked %.1f hours with Flowtime").printf (tracked_hours);
+                tracked_label.visible = true;
             }
         }
 
@@ -44,7 +50,7 @@ namespace Flowtime {
             string format = "date,worktime,breatkime";
 
             foreach (Models.Day day in statistics.all_days) {
-                var work_object = new Models.StatObject (day.date, day.worktime);
+                var work_object = new Models.StatObject (day.date, day.counted_worktime ());
                 var break_object = new Models.StatObject (day.date, day.breaktime);
 
                 format += "\"%s\",\"%s\",\"%s\"\n".printf(work_object.date, work_object.time, break_object.time);
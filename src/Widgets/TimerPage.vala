@@ -14,6 +14,10 @@ namespace Flowtime {
         private unowned Gtk.Label time_label;
         [GtkChild]
         private unowned Gtk.Label stage_label;
+        [GtkChild]
+        private unowned Gtk.Label break_label;
+        [GtkChild]
+        private unowned Gtk.Label soft_cap_label;
 
         public Services.Timer _timer;
         public Services.Timer timer {
@@ -23,12 +27,20 @@ namespace Flowtime {
             set {
                 _timer = value;
                 timer.bind_property ("formatted-time", time_label, "label", SYNC_CREATE);
+                timer.bind_property ("over-soft-cap", soft_cap_label, "visible", SYNC_CREATE);
+                timer.notify["reminder-level"].connect (update_soft_cap_label);
 
                 timer.notify["running"].connect (on_running_changed);
                 timer.notify["mode"].connect (update_labels);
+                timer.updated.connect (update_break_label);
+                timer.break_waiting.connect (on_break_waiting);
+
+                var settings = new Services.Settings ();
+                settings.notify["terminology"].connect (update_labels);
 
                 on_running_changed ();
                 update_labels ();
+                update_break_label ();
             }
         }
 
@@ -39,11 +51,47 @@ namespace Flowtime {
         }
 
         private void update_labels () {
+            var settings = new Services.Settings ();
             if (timer.mode == WORK) {
-                stage_label.label = _("Work Stage");
+                stage_label.label = settings.terminology.work_stage_label ();
+                return;
+            }
+            stage_label.label = settings.terminology.break_stage_label ();
+        }
+
+        private void update_break_label () {
+            int remaining = timer.time_until_break ();
+            if (remaining > 0) {
+                // translators: A countdown, e.g. Break earned in 04:30
+                break_label.label = _("Break earned in %02d:%02d").printf (remaining / 60, remaining % 60);
+                break_label.visible = true;
+                return;
+            }
+
+            int earned = timer.earned_break_preview ();
+            break_label.visible = earned > 0;
+            if (earned > 0) {
+                // translators: The break that would be taken if work stopped now, e.g. 08:00 break earned
+                break_label.label = _("%02d:%02d break earned").printf (earned / 60, earned % 60);
+            }
+        }
+
+        private void update_soft_cap_label () {
+            if (timer.reminder_level == FIRM) {
+                soft_cap_label.label = _("Time for a break");
+                soft_cap_label.remove_css_class ("warning");
+                soft_cap_label.add_css_class ("error");
                 return;
             }
-            stage_label.label = _("Break Stage");
+
+            soft_cap_label.label = _("Consider taking a break");
+            soft_cap_label.remove_css_class ("error");
+            soft_cap_label.add_css_class ("warning");
+        }
+
+        private void on_break_waiting () {
+            pause_button.tooltip_text = _("Start Break");
+            pause_button.grab_focus ();
         }
 
         private void on_running_changed () {
@@ -62,9 +110,34 @@ namespace Flowtime {
                 timer.stop ();
                 return;
             }
+
+            if (timer.start_needs_confirmation ()) {
+                confirm_break.begin ();
+                return;
+            }
             timer.start ();
         }
 
+        // Only breaks started by hand are confirmed, the automatic ones start right away
+        private async void confirm_break () {
+            var dialog = new Adw.AlertDialog (
+                _("Start Break?"),
+                _("Your break lasts %02d:%02d").printf (timer.seconds / 60, timer.seconds % 60)
+            ) {
+                close_response = "cancel",
+                default_response = "start"
+            };
+
+            dialog.add_response ("cancel", _("Cancel"));
+            dialog.add_response ("start", _("Start Break"));
+            dialog.set_response_appearance ("start", SUGGESTED);
+
+            string response = yield dialog.choose (this, null);
+            if (response == "start" && timer.break_pending ()) {
+                timer.start ();
+            }
+        }
+
         [GtkCallback]
         private void on_next_button_clicked () {
             timer.next_mode ();
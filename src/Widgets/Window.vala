@@ -19,6 +19,12 @@ public class Flowtime.Window : Adw.ApplicationWindow {
     private unowned StatInfo overview_info;
     [GtkChild]
     private unowned Adw.NavigationView navigation_view;
+    [GtkChild]
+    private unowned Adw.Banner read_only_banner;
+    [GtkChild]
+    private unowned Adw.Banner locked_banner;
+    [GtkChild]
+    private unowned Adw.Banner sample_banner;
 
     private Adw.Animation hide_animation;
     private Adw.Animation show_animation;
@@ -26,6 +32,7 @@ public class Flowtime.Window : Adw.ApplicationWindow {
     private Adw.AnimationTarget switchers_target;
 
     private Services.Screensaver? screensaver = null;
+    private Services.SleepMonitor? sleep_monitor = null;
     private Services.BackgroundStatusReporter background_reporter;
     private Services.Alarm alarm;
 
@@ -59,6 +66,12 @@ public class Flowtime.Window : Adw.ApplicationWindow {
     construct {
         install_property_action ("win.distraction-free", "distraction-free");
 
+        var statistics = new Services.Statistics ();
+        statistics.notify["read-only"].connect (update_banners);
+        statistics.notify["locked"].connect (update_banners);
+        statistics.notify["sample-mode"].connect (update_banners);
+        update_banners ();
+
         content_target = new Adw.CallbackAnimationTarget (change_content_opacity);
         switchers_target = new Adw.CallbackAnimationTarget (change_switchers_opacity);
 
@@ -77,7 +90,69 @@ public class Flowtime.Window : Adw.ApplicationWindow {
         });
         distraction_free = false;
 
+        timer.restore_startup_phase ();
         init_services.begin ();
+
+        if (statistics.locked) {
+            Idle.add_once (() => unlock_statistics.begin ());
+        }
+
+        var settings = new Services.Settings ();
+        if (settings.auto_export_error != "") {
+            Idle.add_once (show_export_error);
+        }
+    }
+
+    private void show_export_error () {
+        var settings = new Services.Settings ();
+        var dialog = new Adw.AlertDialog (
+            _("Statistics Could Not Be Exported"),
+            _("The statistics could not be exported when Flowtime was closed: %s").printf (
+                settings.auto_export_error
+            )
+        );
+        dialog.add_response ("close", _("Close"));
+        dialog.present (this);
+
+        settings.auto_export_error = "";
+    }
+
+    // Locked statistics and the sample history are read-only too, but no time is lost there
+    private void update_banners () {
+        var statistics = new Services.Statistics ();
+        sample_banner.revealed = statistics.sample_mode;
+        locked_banner.revealed = statistics.locked && !statistics.sample_mode;
+        read_only_banner.revealed = statistics.read_only && !statistics.locked && !statistics.sample_mode;
+    }
+
+    [GtkCallback]
+    private void on_unlock_clicked () {
+        unlock_statistics.begin ();
+    }
+
+    [GtkCallback]
+    private void on_leave_sample_clicked () {
+        new Services.Statistics ().unload_sample ();
+    }
+
+    private async void unlock_statistics (string? error_message = null) {
+        string body = _("Your statistics are encrypted. Enter the passphrase to open them");
+        if (error_message != null) {
+            body = _("The passphrase could not open your statistics. Try again");
+        }
+
+        var dialog = new PassphraseDialog (_("Unlock Statistics"), body, _("Unlock"));
+        string? passphrase = yield dialog.ask (this);
+        if (passphrase == null) {
+            return;
+        }
+
+        try {
+            new Services.Statistics ().unlock (passphrase);
+        } catch (Services.StatisticsError e) {
+            warning (e.message);
+            yield unlock_statistics (e.message);
+        }
     }
 
     private async void init_services () {
@@ -87,6 +162,12 @@ public class Flowtime.Window : Adw.ApplicationWindow {
         var color_provider = new Services.ColorProvider ();
         timer.bind_property ("mode", color_provider, "mode", SYNC_CREATE);
 
+        try {
+            sleep_monitor = yield new Services.SleepMonitor (timer);
+        } catch (Error e) {
+            warning ("Timer will not be paused on suspend: %s", e.message);
+        }
+
         try {
             screensaver = yield new Services.Screensaver (timer);
         } catch (Error e) {
@@ -129,6 +210,10 @@ public class Flowtime.Window : Adw.ApplicationWindow {
         navigation_view.activate_action_variant ("navigation.push", "overview");
     }
 
+    public void start_on_launch () {
+        timer.start_on_launch ();
+    }
+
     public void show_preferences () {
         new PreferencesWindow (this, screensaver);
     }
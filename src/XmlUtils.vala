@@ -16,4 +16,16 @@ namespace Flowtime.XmlUtils {
         }
         return n;
     }
+
+    /*
+     * Pretty-printed or hand-edited files may wrap values in whitespace and newlines, which must
+     * not cause the value to be lost. Empty content is read as zero.
+     */
+    public int parse_int_content (Xml.Node* n) {
+        string content = n->get_content ().strip ();
+        if (content == "") {
+            return 0;
+        }
+        return int.parse (content);
+    }
 }
@@ -0,0 +1,476 @@
+/* BackendTest.vala
+ *
+ * Copyright 2023 Diego Iván <diegoivan.mae@gmail.com>
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+namespace Flowtime.Tests {
+    // The end tag of worktime is missing, which libxml finds at the third line
+    private const string BROKEN_FILE = """<statistics version="1">
+  <day date="2023-03-01T00:00:00+01">
+    <worktime>3600</breaktime>
+  </day>
+</statistics>
+""";
+
+    private void test_parse_error_position () {
+        var backend = new Services.XmlBackend ();
+        try {
+            delete backend.parse (BROKEN_FILE, "statistics.xml");
+            assert_not_reached ();
+        } catch (Services.StatisticsError e) {
+            assert_true (e.code == Services.StatisticsError.PARSE);
+            assert_true (e.message.has_prefix ("statistics.xml cannot be parsed at line 3, column "));
+        }
+    }
+
+    private void save_document (Services.StorageBackend backend, string document)
+                                throws Services.StatisticsError {
+        Xml.Doc* doc = new Services.XmlBackend ().parse (document, "fixture");
+        try {
+            backend.save (doc, data_path (backend.filename));
+        } finally {
+            delete doc;
+        }
+    }
+
+    // Saves the days in the file of the backend, as if they had been saved in its format before
+    private void write_with_backend (Services.StorageBackend backend, string days) {
+        try {
+            save_document (backend, @"<statistics version=\"1\" milestone=\"0\">$days</statistics>");
+        } catch (Services.StatisticsError e) {
+            error (e.message);
+        }
+    }
+
+    private Xml.Doc* load_with_backend (Services.StorageBackend backend) {
+        try {
+            return backend.load (data_path (backend.filename));
+        } catch (Services.StatisticsError e) {
+            error (e.message);
+        }
+    }
+
+    private void test_round_trip (Services.StorageFormat format) {
+        var settings = new Services.Settings ();
+        settings.storage_format = format;
+        Services.StorageBackend backend = format.create_backend ();
+
+        string session = session_xml (days_ago (1).add_hours (9), 3600, 600);
+        write_with_backend (backend, day_xml (days_ago (1), 3600, 600, session + "<note>  007</note>"));
+
+        var statistics = new Services.Statistics ();
+        assert_null (statistics.load_error);
+
+        Models.Day? yesterday = statistics.find_day (days_ago (1));
+        assert_nonnull (yesterday);
+        assert_cmpint (yesterday.worktime, EQ, 3600);
+        assert_cmpint (yesterday.breaktime, EQ, 600);
+        assert_cmpstr (yesterday.note, EQ, "  007");
+        assert_cmpuint (yesterday.sessions.length (), EQ, 1);
+        assert_cmpint (yesterday.sessions.data.duration, EQ, 3600);
+        assert_cmpint (yesterday.sessions.data.break_taken, EQ, 600);
+        assert_true (yesterday.sessions.data.start.equal (days_ago (1).add_hours (9)));
+
+        statistics.add_time_to_mode (WORK, 1500);
+        statistics.add_pausetime (45);
+        try {
+            statistics.set_note (new DateTime.now_local (), "0042\n");
+        } catch (Services.StatisticsError e) {
+            assert_no_error (e);
+        }
+
+        Xml.Doc* doc = load_with_backend (backend);
+        Models.Day? saved = find_saved_day (doc, days_ago (1));
+        assert_nonnull (saved);
+        assert_cmpint (saved.worktime, EQ, 3600);
+        assert_cmpstr (saved.note, EQ, "  007");
+        assert_cmpint (saved.sessions.data.duration, EQ, 3600);
+
+        Models.Day? today = find_saved_day (doc, new DateTime.now_local ());
+        assert_nonnull (today);
+        assert_cmpint (today.worktime, EQ, 1500);
+        assert_cmpint (today.pausetime, EQ, 45);
+        assert_cmpstr (today.note, EQ, "0042\n");
+    }
+
+    private void test_round_trip_xml () {
+        if (!isolate ()) {
+            return;
+        }
+        test_round_trip (XML);
+    }
+
+    private void test_round_trip_json () {
+        if (!isolate ()) {
+            return;
+        }
+        test_round_trip (JSON);
+    }
+
+    // Files saved in another format are moved to the selected one as they are read
+    private void test_migrate_on_load () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.storage_format = JSON;
+        write_statistics (day_xml (days_ago (1), 3600, 600));
+
+        var statistics = new Services.Statistics ();
+        assert_nonnull (statistics.find_day (days_ago (1)));
+        assert_false (FileUtils.test (data_path ("statistics.xml"), EXISTS));
+
+        Models.Day? saved = find_saved_day (load_with_backend (new Services.JsonBackend ()), days_ago (1));
+        assert_nonnull (saved);
+        assert_cmpint (saved.worktime, EQ, 3600);
+    }
+
+    private void test_migrate_on_change () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.storage_format = XML;
+        write_statistics (day_xml (days_ago (1), 3600, 600));
+
+        var statistics = new Services.Statistics ();
+        settings.storage_format = JSON;
+        assert_false (FileUtils.test (data_path ("statistics.xml"), EXISTS));
+        assert_nonnull (find_saved_day (load_with_backend (new Services.JsonBackend ()), days_ago (1)));
+
+        // The statistics keep being saved in the new format
+        statistics.add_time_to_mode (WORK, 1500);
+        Models.Day? today = find_saved_day (load_with_backend (new Services.JsonBackend ()),
+                                            new DateTime.now_local ());
+        assert_nonnull (today);
+        assert_cmpint (today.worktime, EQ, 1500);
+    }
+
+    // Statistics that cannot be saved stay in their format, and are still read from their file
+    private void test_migrate_read_only () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.storage_format = JSON;
+        write_statistics (day_xml (days_ago (1), 3600, 600));
+        lock_statistics ();
+
+        var statistics = new Services.Statistics ();
+        assert_true (statistics.read_only);
+        assert_true (FileUtils.test (data_path ("statistics.xml"), EXISTS));
+        assert_false (FileUtils.test (data_path ("statistics.json"), EXISTS));
+        assert_nonnull (statistics.data_last_modified ());
+        assert_cmpint ((int) statistics.data_health ().file_size, EQ, read_data ("statistics.xml").length);
+    }
+
+    private void test_round_trip_sqlite () {
+        if (!isolate ()) {
+            return;
+        }
+        test_round_trip (SQLITE);
+    }
+
+    private void test_sqlite_range () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var backend = new Services.SqliteBackend ();
+        write_with_backend (backend,
+            day_xml (days_ago (10), 600) +
+            day_xml (days_ago (5), 500) +
+            day_xml (days_ago (3).add_hours (14), 300) +
+            day_xml (days_ago (1), 100) +
+            // Days with an invalid date are not indexed, but they are never left out
+            "<day date=\"yesterday\"><worktime>42</worktime><breaktime>0</breaktime></day>"
+        );
+
+        Xml.Doc* doc = null;
+        Xml.Doc* unbounded = null;
+        try {
+            doc = backend.load_days_between (data_path (backend.filename), days_ago (5), days_ago (1));
+            unbounded = backend.load_days_between (data_path (backend.filename), days_ago (5));
+        } catch (Services.StatisticsError e) {
+            assert_no_error (e);
+        }
+
+        Xml.Node* root = doc->get_root_element ();
+        assert_cmpstr (root->get_prop ("version"), EQ, "1");
+
+        string[] dates = {};
+        for (Xml.Node* i = root->children; i != null; i = i->next) {
+            dates += i->get_prop ("date");
+        }
+        assert_cmpint (dates.length, EQ, 3);
+        assert_cmpstr (dates[0], EQ, "yesterday");
+        assert_cmpstr (dates[1], EQ, days_ago (5).format_iso8601 ());
+        assert_cmpstr (dates[2], EQ, days_ago (3).add_hours (14).format_iso8601 ());
+        delete doc;
+
+        // Without an end every day from the start on is read
+        assert_nonnull (find_saved_day (unbounded, days_ago (1)));
+        assert_null (find_saved_day (unbounded, days_ago (10)));
+    }
+
+    // The days older than the months saved are not read out of the database
+    private void test_sqlite_retention () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.storage_format = SQLITE;
+        settings.months_saved = 1;
+        settings.archive_old_data = false;
+        write_with_backend (new Services.SqliteBackend (),
+            day_xml (days_ago (30), 3600) + day_xml (days_ago (29), 1800) + day_xml (days_ago (1), 600)
+        );
+
+        var statistics = new Services.Statistics ();
+        assert_null (statistics.find_day (days_ago (30)));
+        assert_nonnull (statistics.find_day (days_ago (29)));
+        assert_nonnull (statistics.find_day (days_ago (1)));
+    }
+
+    private void test_sqlite_import () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.storage_format = SQLITE;
+
+        string session = session_xml (days_ago (2).add_hours (9), 3600, 600);
+        write_statistics (
+            day_xml (days_ago (2), 3600, 600, session + "<note>Report</note>", " rest=\"true\"") +
+            day_xml (days_ago (1), 1800),
+            " milestone=\"10\""
+        );
+        string imported_xml = read_data ("statistics.xml");
+
+        var statistics = new Services.Statistics ();
+        assert_false (FileUtils.test (data_path ("statistics.xml"), EXISTS));
+        assert_cmpint (statistics.find_day (days_ago (2)).worktime, EQ, 3600);
+
+        Xml.Doc* doc = load_with_backend (new Services.SqliteBackend ());
+        assert_cmpstr (doc->get_root_element ()->get_prop ("milestone"), EQ, "10");
+
+        Models.Day? imported = find_saved_day (doc, days_ago (2));
+        assert_nonnull (imported);
+        assert_cmpint (imported.breaktime, EQ, 600);
+        assert_true (imported.rest_day);
+        assert_cmpstr (imported.note, EQ, "Report");
+        assert_cmpuint (imported.sessions.length (), EQ, 1);
+        assert_cmpint (imported.sessions.data.break_taken, EQ, 600);
+        assert_cmpint (find_saved_day (doc, days_ago (1)).worktime, EQ, 1800);
+
+        // The XML file is only imported once
+        write_data ("statistics.xml", imported_xml);
+        try {
+            var backend = new Services.SqliteBackend ();
+            backend.import_xml (data_path ("statistics.xml"), data_path (backend.filename));
+            assert_not_reached ();
+        } catch (Services.StatisticsError e) {
+            assert_true (e.code == Services.StatisticsError.NOT_EMPTY);
+        }
+    }
+
+    // A save that fails halfway leaves the statistics saved before
+    private void test_sqlite_rollback () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var backend = new Services.SqliteBackend ();
+        write_with_backend (backend, day_xml (days_ago (2), 3600));
+
+        Sqlite.Database db;
+        Sqlite.Database.open (data_path (backend.filename), out db);
+        db.exec ("""
+            CREATE TRIGGER fail BEFORE INSERT ON elements WHEN NEW.content LIKE '%fail%'
+            BEGIN SELECT RAISE(ABORT, 'the row is refused'); END;
+        """);
+
+        try {
+            string refused = day_xml (days_ago (0), 60, 0, "<note>fail</note>");
+            save_document (backend, "<statistics version=\"2\">%s%s</statistics>".printf (
+                day_xml (days_ago (1), 1800), refused
+            ));
+            assert_not_reached ();
+        } catch (Services.StatisticsError e) {
+            assert_true (e.code == Services.StatisticsError.IO);
+        }
+
+        Xml.Doc* doc = load_with_backend (backend);
+        assert_cmpstr (doc->get_root_element ()->get_prop ("version"), EQ, "1");
+        assert_nonnull (find_saved_day (doc, days_ago (2)));
+        assert_null (find_saved_day (doc, days_ago (1)));
+    }
+
+    /*
+     * Run with -m perf. Compares reading the last month out of three years of days through the
+     * index of the database with reading the whole XML file and looking for the month in it.
+     */
+    private void test_range_benchmark () {
+        if (!Test.perf ()) {
+            Test.skip ("Benchmarks are only run in performance mode");
+            return;
+        }
+
+        string directory;
+        try {
+            directory = DirUtils.make_tmp ("flowtime-benchmark-XXXXXX");
+        } catch (FileError e) {
+            error (e.message);
+        }
+        string xml_path = Path.build_filename (directory, "statistics.xml");
+        string db_path = Path.build_filename (directory, "statistics.db");
+
+        var days = new StringBuilder ();
+        for (int i = 0; i < 3 * 365; i++) {
+            string sessions = session_xml (days_ago (i).add_hours (9), 3600, 600)
+                              + session_xml (days_ago (i).add_hours (11), 2700, 300);
+            days.append (day_xml (days_ago (i), 6300, 900, sessions + "<note>A day of work</note>"));
+        }
+
+        try {
+            Xml.Doc* doc = new Services.XmlBackend ().parse (
+                "<statistics version=\"1\">%s</statistics>".printf (days.str), "benchmark"
+            );
+            doc->save_file (xml_path);
+            new Services.SqliteBackend ().save (doc, db_path);
+            delete doc;
+
+            DateTime month_start = days_ago (29);
+            var timer = new GLib.Timer ();
+
+            Xml.Doc* xml_doc = new Services.XmlBackend ().load (xml_path);
+            uint xml_days = 0;
+            for (Xml.Node* i = xml_doc->get_root_element ()->children; i != null; i = i->next) {
+                DateTime? date = TimeUtils.parse_date (i->get_prop ("date"));
+                if (date != null && date.compare (month_start) >= 0) {
+                    xml_days++;
+                }
+            }
+            double xml_seconds = timer.elapsed ();
+            delete xml_doc;
+
+            timer.start ();
+            Xml.Doc* db_doc = new Services.SqliteBackend ().load_days_between (db_path, month_start);
+            uint db_days = 0;
+            for (Xml.Node* i = db_doc->get_root_element ()->children; i != null; i = i->next) {
+                db_days++;
+            }
+            double db_seconds = timer.elapsed ();
+            delete db_doc;
+
+            assert_cmpuint (db_days, EQ, xml_days);
+            Test.minimized_result (xml_seconds, "Last month read from XML in %.4f seconds", xml_seconds);
+            Test.minimized_result (db_seconds, "Last month read from SQLite in %.4f seconds", db_seconds);
+        } catch (Services.StatisticsError e) {
+            assert_no_error (e);
+        }
+
+        FileUtils.remove (xml_path);
+        FileUtils.remove (db_path);
+        DirUtils.remove (directory);
+    }
+
+    private Xml.Doc* load_encrypted (string passphrase) throws Services.StatisticsError {
+        var backend = new Services.EncryptedBackend (passphrase);
+        return backend.load (data_path (backend.filename));
+    }
+
+    private void test_encrypted_round_trip () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var backend = new Services.EncryptedBackend ("correct horse");
+        write_with_backend (backend, day_xml (days_ago (1), 3600, 600, "<note>Private</note>"));
+
+        // Nothing of the statistics can be read from the file
+        string contents = read_data (Services.EncryptedBackend.FILENAME);
+        assert_true (contents.has_prefix ("FLOWTIME-ENCRYPTED-1\n"));
+        assert_false (contents.contains ("Private"));
+
+        try {
+            Models.Day? day = find_saved_day (load_encrypted ("correct horse"), days_ago (1));
+            assert_nonnull (day);
+            assert_cmpint (day.worktime, EQ, 3600);
+            assert_cmpint (day.breaktime, EQ, 600);
+            assert_cmpstr (day.note, EQ, "Private");
+        } catch (Services.StatisticsError e) {
+            error (e.message);
+        }
+    }
+
+    private void test_encrypted_wrong_passphrase () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var backend = new Services.EncryptedBackend ("correct horse");
+        write_with_backend (backend, day_xml (days_ago (1), 3600));
+
+        try {
+            load_encrypted ("battery staple");
+            assert_not_reached ();
+        } catch (Services.StatisticsError e) {
+            assert_true (e.code == Services.StatisticsError.PARSE);
+        }
+    }
+
+    private void test_encrypted_truncated () {
+        if (!isolate ()) {
+            return;
+        }
+
+        // The marker is there, but the file ends before the salt and the nonce
+        write_data (Services.EncryptedBackend.FILENAME, "FLOWTIME-ENCRYPTED-1\nshort");
+        try {
+            load_encrypted ("correct horse");
+            assert_not_reached ();
+        } catch (Services.StatisticsError e) {
+            assert_true (e.code == Services.StatisticsError.PARSE);
+        }
+
+        // Statistics that were never encrypted are not taken for encrypted ones
+        string plain = "<?xml version=\"1.0\"?>\n<statistics version=\"1\">%s</statistics>\n";
+        write_data (Services.EncryptedBackend.FILENAME, plain.printf (day_xml (days_ago (1), 3600)));
+        try {
+            load_encrypted ("correct horse");
+            assert_not_reached ();
+        } catch (Services.StatisticsError e) {
+            assert_true (e.code == Services.StatisticsError.PARSE);
+        }
+    }
+
+    public static int main (string[] args) {
+        Test.init (ref args);
+
+        Test.add_func ("/xml-backend/parse-error-position", test_parse_error_position);
+        Test.add_func ("/json-backend/round-trip", test_round_trip_json);
+        Test.add_func ("/xml-backend/round-trip", test_round_trip_xml);
+        Test.add_func ("/statistics/storage-format/migrate-on-load", test_migrate_on_load);
+        Test.add_func ("/statistics/storage-format/migrate-on-change", test_migrate_on_change);
+        Test.add_func ("/statistics/storage-format/migrate-read-only", test_migrate_read_only);
+        Test.add_func ("/sqlite-backend/round-trip", test_round_trip_sqlite);
+        Test.add_func ("/sqlite-backend/range", test_sqlite_range);
+        Test.add_func ("/sqlite-backend/retention", test_sqlite_retention);
+        Test.add_func ("/sqlite-backend/import", test_sqlite_import);
+        Test.add_func ("/sqlite-backend/rollback", test_sqlite_rollback);
+        Test.add_func ("/sqlite-backend/range-benchmark", test_range_benchmark);
+        Test.add_func ("/encrypted-backend/round-trip", test_encrypted_round_trip);
+        Test.add_func ("/encrypted-backend/wrong-passphrase", test_encrypted_wrong_passphrase);
+        Test.add_func ("/encrypted-backend/truncated", test_encrypted_truncated);
+
+        return Test.run ();
+    }
+}
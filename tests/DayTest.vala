@@ -0,0 +1,139 @@
+/* DayTest.vala
+ *
+ * Copyright 2023 Diego Iván <diegoivan.mae@gmail.com>
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+namespace Flowtime.Tests {
+    private void test_break_balance () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.break_rounding = NONE;
+        settings.min_break_seconds = 60;
+        settings.max_break_seconds = 3600;
+        settings.break_percentage = 25;
+
+        var day = new Models.Day.unsaved (days_ago (1));
+        day.add_session (3600);
+        day.worktime = 3600;
+        day.breaktime = 1000;
+
+        settings.break_policy = PERCENTAGE;
+        assert_cmpint (day.break_balance (), EQ, 100);
+
+        settings.break_policy = FLOWTIME;
+        assert_cmpint (day.break_balance (), EQ, 400);
+
+        day.breaktime = 0;
+        assert_cmpint (day.break_balance (), EQ, -600);
+    }
+
+    // Days saved before sessions were recorded earn their break from the total worktime
+    private void test_break_balance_without_sessions () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.break_policy = PERCENTAGE;
+        settings.break_percentage = 25;
+        settings.break_rounding = NONE;
+
+        var day = new Models.Day.unsaved (days_ago (1));
+        day.worktime = 7200;
+        day.breaktime = 1200;
+        assert_cmpint (day.break_balance (), EQ, -600);
+    }
+
+    private void test_break_balance_no_work () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var day = new Models.Day.unsaved (days_ago (1));
+        assert_cmpint (day.break_balance (), EQ, 0);
+
+        day.breaktime = 300;
+        assert_cmpint (day.break_balance (), EQ, 300);
+    }
+
+    private void test_activity_saved () {
+        if (!isolate ()) {
+            return;
+        }
+
+        DateTime date = days_ago (1);
+        var day = new Models.Day.unsaved (date);
+        day.add_session (1800, date.add_hours (9).add_minutes (42));
+        day.add_session (3600, date.add_hours (17).add_minutes (45));
+
+        // Read again from the node, as it is when the statistics are loaded
+        var reloaded = new Models.Day.from_xml (day.node);
+        assert_true (reloaded.first_activity.equal (date.add_hours (9).add_minutes (12)));
+        assert_true (reloaded.last_activity.equal (date.add_hours (17).add_minutes (45)));
+        assert_cmpstr (reloaded.work_span_display (), EQ, "09:12–17:45");
+    }
+
+    private void test_activity_legacy () {
+        if (!isolate ()) {
+            return;
+        }
+
+        string contents = day_xml (days_ago (1), 3600);
+        Xml.Doc* doc = Xml.Parser.parse_memory (contents, contents.length);
+        var day = new Models.Day.from_xml (doc->get_root_element ());
+
+        assert_null (day.first_activity);
+        assert_null (day.last_activity);
+        assert_cmpstr (day.work_span_display (), EQ, "");
+    }
+
+    private void test_energy_saved () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var day = new Models.Day.unsaved (days_ago (1));
+        assert_cmpuint (day.energy, EQ, 0);
+        assert_null (day.node->get_prop ("energy"));
+
+        day.energy = 4;
+        assert_cmpstr (day.node->get_prop ("energy"), EQ, "4");
+        assert_cmpuint (new Models.Day.from_xml (day.node).energy, EQ, 4);
+
+        // Removing the rating removes the attribute too
+        day.energy = 0;
+        assert_null (day.node->get_prop ("energy"));
+        assert_cmpuint (new Models.Day.from_xml (day.node).energy, EQ, 0);
+    }
+
+    private void test_energy_invalid () {
+        if (!isolate ()) {
+            return;
+        }
+
+        foreach (unowned string rating in new string[] { "0", "6", "-1", "high" }) {
+            string contents = day_xml (days_ago (1), 3600, 0, "", " energy=\"%s\"".printf (rating));
+            Xml.Doc* doc = Xml.Parser.parse_memory (contents, contents.length);
+            assert_cmpuint (new Models.Day.from_xml (doc->get_root_element ()).energy, EQ, 0);
+        }
+    }
+
+    public static int main (string[] args) {
+        Test.init (ref args);
+
+        Test.add_func ("/day/break-balance", test_break_balance);
+        Test.add_func ("/day/break-balance/without-sessions", test_break_balance_without_sessions);
+        Test.add_func ("/day/break-balance/no-work", test_break_balance_no_work);
+        Test.add_func ("/day/activity/saved", test_activity_saved);
+        Test.add_func ("/day/activity/legacy", test_activity_legacy);
+        Test.add_func ("/day/energy/saved", test_energy_saved);
+        Test.add_func ("/day/energy/invalid", test_energy_invalid);
+
+        return Test.run ();
+    }
+}
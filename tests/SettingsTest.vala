@@ -0,0 +1,75 @@
+/* SettingsTest.vala
+ *
+ * Copyright 2023 Diego Iván <diegoivan.mae@gmail.com>
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+namespace Flowtime.Tests {
+    // Installs older than the break percentage saved the break as a divisor of the worktime
+    private void test_migrate_divisor () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var legacy = new GLib.Settings (Config.APP_ID);
+        legacy.set_int ("settings-version", 0);
+        legacy.set_int ("break-divisor", 5);
+
+        var settings = new Services.Settings ();
+        assert_true (settings.break_percentage == 20);
+        assert_cmpint (legacy.get_int ("break-divisor"), EQ, -1);
+        assert_cmpint (legacy.get_int ("settings-version"), EQ, 1);
+    }
+
+    private void test_migrated_once () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var legacy = new GLib.Settings (Config.APP_ID);
+        legacy.set_int ("settings-version", 1);
+        legacy.set_int ("break-divisor", 5);
+        legacy.set_double ("break-percentage", 30);
+
+        var settings = new Services.Settings ();
+        assert_true (settings.break_percentage == 30);
+        assert_cmpint (legacy.get_int ("break-divisor"), EQ, 5);
+    }
+
+    private void test_terminology () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.terminology = WORK;
+        Services.TimerMode work = WORK;
+        Services.TimerMode break_mode = BREAK;
+        assert_cmpstr (work.to_string (), EQ, "Work");
+        assert_cmpstr (break_mode.to_string (), EQ, "Break");
+
+        settings.terminology = STUDY;
+        assert_cmpstr (work.to_string (), EQ, "Study");
+        assert_cmpstr (break_mode.to_string (), EQ, "Rest");
+        assert_cmpstr (settings.terminology.work_stage_label (), EQ, "Study Stage");
+        assert_cmpstr (settings.terminology.break_stage_label (), EQ, "Rest Stage");
+
+        // Only the labels change, the statistics keep their elements
+        var statistics = new Services.Statistics ();
+        statistics.add_time_to_mode (WORK, 600);
+        string saved = read_data ("statistics.xml");
+        assert_true (saved.contains ("<worktime>600</worktime>"));
+        assert_false (saved.contains ("study"));
+    }
+
+    public static int main (string[] args) {
+        Test.init (ref args);
+
+        Test.add_func ("/settings/migrate-divisor", test_migrate_divisor);
+        Test.add_func ("/settings/migrated-once", test_migrated_once);
+        Test.add_func ("/settings/terminology", test_terminology);
+
+        return Test.run ();
+    }
+}
@@ -0,0 +1,2920 @@
+/* StatisticsTest.vala
+ *
+ * Copyright 2023 Diego Iván <diegoivan.mae@gmail.com>
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+namespace Flowtime.Tests {
+    private void test_pausetime_saved () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var statistics = new Services.Statistics ();
+        statistics.add_pausetime (90);
+        statistics.add_pausetime (30);
+
+        Models.Day? saved = saved_day (new DateTime.now_local ());
+        assert_nonnull (saved);
+        assert_cmpint (saved.pausetime, EQ, 120);
+        assert_cmpint (saved.breaktime, EQ, 0);
+        assert_cmpint (saved.breaks, EQ, 0);
+    }
+
+    private void test_pausetime_missing () {
+        if (!isolate ()) {
+            return;
+        }
+
+        // Days saved before the paused time was recorded have no element for it
+        write_statistics (day_xml (days_ago (1), 3600, 900));
+
+        var statistics = new Services.Statistics ();
+        Models.Day? yesterday = statistics.find_day (days_ago (1));
+        assert_nonnull (yesterday);
+        assert_cmpint (yesterday.pausetime, EQ, 0);
+        assert_cmpint (yesterday.breaktime, EQ, 900);
+    }
+
+    private void test_pausetime_not_break () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.break_policy = PERCENTAGE;
+        settings.break_percentage = 25;
+
+        var statistics = new Services.Statistics ();
+        statistics.add_time_to_mode (WORK, 3600);
+        statistics.add_time_to_mode (BREAK, 450);
+        statistics.add_pausetime (450);
+
+        // Half of the earned break was taken, being paused does not make up for the rest
+        assert_cmpint (statistics.today.breaktime, EQ, 450);
+        assert_cmpint (statistics.get_time_from_mode_and_period (BREAK, TODAY), EQ, 450);
+        assert_true (statistics.today.break_compliance () == 0.5);
+    }
+
+    private void test_pausetime_from_timer () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var statistics = new Services.Statistics ();
+        var timer = new Services.Timer ();
+        timer.start ();
+        timer.stop ();
+        Thread.usleep (1100000);
+        timer.start ();
+        timer.stop ();
+
+        assert_cmpint (statistics.today.pausetime, GE, 1);
+        assert_cmpint (statistics.today.breaktime, EQ, 0);
+    }
+
+    private void test_goal_hits () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.daily_goal = 240;
+        settings.non_work_days = 0;
+
+        write_statistics (
+            day_xml (days_ago (5), 14399) +
+            day_xml (days_ago (4), 14400) +
+            day_xml (days_ago (3), 20000, 1200) +
+            day_xml (days_ago (2), 100) +
+            // A day without any time is not counted against the goal
+            day_xml (days_ago (1), 0)
+        );
+
+        var statistics = new Services.Statistics ();
+        assert_cmpuint (statistics.goal_hit_count (), EQ, 2);
+        assert_true (statistics.goal_hit_rate () == 0.5);
+
+        // The goal set at the moment is the one used
+        settings.daily_goal = 1;
+        assert_cmpuint (statistics.goal_hit_count (), EQ, 4);
+        assert_true (statistics.goal_hit_rate () == 1);
+    }
+
+    private void test_goal_hits_empty () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var statistics = new Services.Statistics ();
+        assert_cmpuint (statistics.goal_hit_count (), EQ, 0);
+        assert_true (statistics.goal_hit_rate () == 0);
+    }
+
+    private void test_sessions_saved () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var statistics = new Services.Statistics ();
+        statistics.add_time_to_mode (WORK, 1500);
+        statistics.add_time_to_mode (BREAK, 300);
+        statistics.add_time_to_mode (WORK, 2700);
+
+        Models.Day? saved = saved_day (new DateTime.now_local ());
+        assert_nonnull (saved);
+        assert_cmpuint (saved.sessions.length (), EQ, 2);
+        assert_cmpint (saved.sessions.nth_data (0).duration, EQ, 1500);
+        assert_cmpint (saved.sessions.nth_data (0).break_taken, EQ, 300);
+        assert_cmpint (saved.sessions.nth_data (1).duration, EQ, 2700);
+
+        // The daily total can be derived from the sessions
+        assert_cmpint (saved.sessions_worktime (), EQ, saved.worktime);
+    }
+
+    private void test_longest_session () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_statistics (
+            day_xml (days_ago (3), 5400, 0, "<session dur=\"1800\"/><session dur=\"3600\"/>") +
+            // Days saved before sessions were recorded have none to compare
+            day_xml (days_ago (2), 20000) +
+            day_xml (days_ago (1), 3000, 0, session_xml (days_ago (1).add_hours (9), 3000))
+        );
+
+        var statistics = new Services.Statistics ();
+        assert_cmpint (statistics.longest_session (), EQ, 3600);
+
+        statistics.add_time_to_mode (WORK, 4000);
+        assert_cmpint (statistics.longest_session (), EQ, 4000);
+    }
+
+    private void test_longest_session_none () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_statistics (day_xml (days_ago (1), 20000));
+
+        var statistics = new Services.Statistics ();
+        assert_null (statistics.longest_session ());
+    }
+
+    private void test_dense_days () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_statistics (
+            day_xml (days_ago (6), 3600) +
+            day_xml (days_ago (3), 1800, 300) +
+            day_xml (days_ago (1), 7200)
+        );
+
+        var statistics = new Services.Statistics ();
+        uint saved_days = statistics.all_days.length ();
+
+        var now = new DateTime.now_local ();
+        List<Models.Day> dense = statistics.dense_days (days_ago (6).add_hours (15), now);
+        assert_cmpuint (dense.length (), EQ, 7);
+
+        int[] worktimes = { 3600, 0, 0, 1800, 0, 7200, 0 };
+        for (int i = 0; i < 7; i++) {
+            Models.Day day = dense.nth_data (i);
+            assert_true (day.date.equal (days_ago (6 - i)));
+            assert_cmpint (day.worktime, EQ, worktimes[i]);
+        }
+        assert_cmpint (dense.nth_data (3).breaktime, EQ, 300);
+
+        // The filled in dates are not added to the history
+        assert_cmpuint (statistics.all_days.length (), EQ, saved_days);
+        assert_null (statistics.find_day (days_ago (5)));
+    }
+
+    private void test_dense_days_dst () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var statistics = new Services.Statistics ();
+        List<Models.Day> dense = statistics.dense_days (new DateTime.local (2023, 3, 24, 12, 0, 0),
+                                                        new DateTime.local (2023, 3, 28, 0, 0, 0));
+        assert_cmpuint (dense.length (), EQ, 5);
+        for (int i = 0; i < 5; i++) {
+            assert_true (dense.nth_data (i).date.equal (new DateTime.local (2023, 3, 24 + i, 0, 0, 0)));
+        }
+
+        assert_cmpuint (statistics.dense_days (new DateTime.local (2023, 3, 2, 0, 0, 0),
+                                               new DateTime.local (2023, 3, 1, 0, 0, 0)).length (), EQ, 0);
+    }
+
+    private void test_snapshot_variant () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_statistics (day_xml (days_ago (2), 3600) + day_xml (days_ago (1), 1800));
+
+        var statistics = new Services.Statistics ();
+        statistics.add_time_to_mode (WORK, 1500);
+        statistics.add_time_to_mode (BREAK, 300);
+
+        Variant snapshot = statistics.snapshot_variant ();
+        assert_cmpstr (snapshot.get_type_string (), EQ, "(iiu)");
+
+        int worktime, breaktime;
+        uint days;
+        snapshot.get ("(iiu)", out worktime, out breaktime, out days);
+        assert_cmpint (worktime, EQ, 1500);
+        assert_cmpint (breaktime, EQ, 300);
+        assert_cmpuint (days, EQ, 3);
+
+        // The same snapshot is exported over DBus
+        var service = new Services.DBusService ();
+        assert_true (service.snapshot.equal (snapshot));
+    }
+
+    private void test_today_notify () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var statistics = new Services.Statistics ();
+        int worktime_notified = 0;
+        int breaktime_notified = 0;
+        int display_notified = 0;
+        statistics.notify["today-worktime"].connect (() => worktime_notified++);
+        statistics.notify["today-breaktime"].connect (() => breaktime_notified++);
+        statistics.notify["today-worktime-display"].connect (() => display_notified++);
+
+        statistics.add_time_to_mode (WORK, 1500);
+        assert_cmpint (worktime_notified, EQ, 1);
+        assert_cmpint (display_notified, EQ, 1);
+        assert_cmpint (statistics.today_worktime, EQ, 1500);
+        assert_cmpstr (statistics.today_worktime_display, EQ, TimeUtils.format_time (1500));
+
+        statistics.add_time_to_mode (BREAK, 300);
+        assert_cmpint (breaktime_notified, EQ, 2);
+        assert_cmpint (statistics.today_breaktime, EQ, 300);
+    }
+
+    private void test_whitespace_values () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_statistics ("""
+            <day date="%s">
+                <worktime> 3600 </worktime>
+                <breaktime>
+                    900
+                </breaktime>
+                <pausetime>  </pausetime>
+            </day>
+        """.printf (days_ago (1).format_iso8601 ()));
+
+        var statistics = new Services.Statistics ();
+        Models.Day? yesterday = statistics.find_day (days_ago (1));
+        assert_nonnull (yesterday);
+        assert_cmpint (yesterday.worktime, EQ, 3600);
+        assert_cmpint (yesterday.breaktime, EQ, 900);
+        assert_cmpint (yesterday.pausetime, EQ, 0);
+    }
+
+    private void test_worktime_range () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.update_interval = 0;
+        settings.deep_work_only = false;
+
+        write_statistics (
+            day_xml (days_ago (4), 3600) +
+            day_xml (days_ago (3), 7200) +
+            day_xml (days_ago (2), 10800) +
+            day_xml (days_ago (1), 14400)
+        );
+
+        var statistics = new Services.Statistics ();
+        ListModel model = statistics.days_model_in_worktime_range (7200, 14400);
+        assert_cmpuint (model.get_n_items (), EQ, 3);
+        for (uint i = 0; i < model.get_n_items (); i++) {
+            var day = (Models.Day) model.get_item (i);
+            assert_cmpint (day.worktime, GE, 7200);
+            assert_cmpint (day.worktime, LE, 14400);
+        }
+
+        // The model follows the changes of the statistics
+        statistics.add_time_to_mode (WORK, 7200);
+        assert_cmpuint (model.get_n_items (), EQ, 4);
+    }
+
+    private void test_worktime_range_empty () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_statistics (day_xml (days_ago (1), 3600));
+
+        var statistics = new Services.Statistics ();
+        assert_cmpuint (statistics.days_model_in_worktime_range (3601, 7200).get_n_items (), EQ, 0);
+        assert_cmpuint (statistics.days_model_in_worktime_range (3600, 3600).get_n_items (), EQ, 1);
+    }
+
+    private void test_data_health () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_statistics (
+            day_xml (days_ago (5), 3600) +
+            day_xml (days_ago (3), 3600, -60) +
+            day_xml (days_ago (2), 3600, 86000) +
+            day_xml (days_ago (1), 1800) +
+            day_xml (days_ago (1), 1800)
+        );
+
+        var statistics = new Services.Statistics ();
+        assert_cmpint (statistics.verify ().length, EQ, 3);
+
+        Models.DataHealth health = statistics.data_health ();
+        // Today is added to the saved days
+        assert_cmpuint (health.day_count, EQ, 6);
+        assert_cmpuint (health.warning_count, EQ, 3);
+        assert_true (health.earliest_date.equal (days_ago (5)));
+        assert_true (TimeUtils.same_day (health.latest_date, new DateTime.now_local ()));
+        assert_cmpint ((int) health.file_size, EQ, read_data ("statistics.xml").length);
+    }
+
+    private void test_active_dates () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.active_threshold = 60;
+        settings.deep_work_only = false;
+
+        // Saved in no particular order, with a day below the threshold and an empty day
+        write_statistics (
+            day_xml (days_ago (2), 3600) +
+            day_xml (days_ago (5).add_hours (10), 1800) +
+            day_xml (days_ago (4), 59, 600) +
+            day_xml (days_ago (3), 0) +
+            day_xml (days_ago (1), 60)
+        );
+
+        var statistics = new Services.Statistics ();
+        List<DateTime> dates = statistics.active_dates ();
+        assert_cmpuint (dates.length (), EQ, 3);
+        assert_true (dates.nth_data (0).equal (days_ago (5)));
+        assert_true (dates.nth_data (1).equal (days_ago (2)));
+        assert_true (dates.nth_data (2).equal (days_ago (1)));
+    }
+
+    private void test_archive_split () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.deep_work_only = false;
+        settings.include_archive = false;
+
+        write_statistics (
+            day_xml (days_ago (45), 3600) +
+            day_xml (days_ago (40), 1800) +
+            day_xml (days_ago (5), 600)
+        );
+
+        var statistics = new Services.Statistics ();
+        try {
+            statistics.archive_older_than (1);
+        } catch (Services.StatisticsError e) {
+            assert_no_error (e);
+        }
+
+        assert_null (statistics.find_day (days_ago (45)));
+        assert_null (statistics.find_day (days_ago (40)));
+        assert_nonnull (statistics.find_day (days_ago (5)));
+        assert_null (saved_day (days_ago (45)));
+        assert_nonnull (saved_day (days_ago (5)));
+
+        Models.Day? archived = saved_day (days_ago (45), "statistics-archive.xml");
+        assert_nonnull (archived);
+        assert_cmpint (archived.worktime, EQ, 3600);
+        assert_nonnull (saved_day (days_ago (40), "statistics-archive.xml"));
+        assert_null (saved_day (days_ago (5), "statistics-archive.xml"));
+
+        assert_cmpint (statistics.get_time_from_mode_and_period (WORK, ALL), EQ, 600);
+        settings.include_archive = true;
+        assert_cmpint (statistics.get_time_from_mode_and_period (WORK, ALL), EQ, 6000);
+    }
+
+    private void test_archive_aggregation () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.deep_work_only = false;
+        settings.archive_old_data = true;
+        settings.include_archive = true;
+
+        string archive = "<?xml version=\"1.0\"?>\n<statistics>%s</statistics>\n";
+        write_data ("statistics-archive.xml", archive.printf (day_xml (days_ago (100), 7200, 600)));
+        // The day older than the months saved is moved to the archive as it is loaded
+        write_statistics (day_xml (days_ago (70), 3600, 300) + day_xml (days_ago (5), 1800, 60));
+
+        var statistics = new Services.Statistics ();
+        assert_null (statistics.find_day (days_ago (70)));
+        assert_nonnull (saved_day (days_ago (70), "statistics-archive.xml"));
+        assert_nonnull (saved_day (days_ago (100), "statistics-archive.xml"));
+
+        assert_cmpint (statistics.get_time_from_mode_and_period (WORK, ALL), EQ, 12600);
+        assert_cmpint (statistics.get_time_from_mode_and_period (BREAK, ALL), EQ, 960);
+
+        settings.include_archive = false;
+        assert_cmpint (statistics.get_time_from_mode_and_period (WORK, ALL), EQ, 1800);
+        assert_cmpint (statistics.get_time_from_mode_and_period (BREAK, ALL), EQ, 60);
+    }
+
+    private void test_parse_error_reported () {
+        if (!isolate ()) {
+            return;
+        }
+
+        // The day is never closed, which libxml finds at the third line
+        write_data ("statistics.xml",
+                    "<statistics version=\"1\">\n<day date=\"2023-03-01\">\n</statistics>\n");
+
+        var statistics = new Services.Statistics ();
+        assert_nonnull (statistics.load_error);
+        assert_true ("cannot be parsed at line 3, column " in statistics.load_error);
+    }
+
+    // The week is made to start the given days before today
+    private void start_week_days_ago (int days) {
+        var settings = new Services.Settings ();
+        settings.week_start = days_ago (days).get_day_of_week ();
+    }
+
+    private void test_week_forecast () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.deep_work_only = false;
+        start_week_days_ago (3);
+
+        write_statistics (
+            // The last day of the previous week
+            day_xml (days_ago (4), 36000) +
+            day_xml (days_ago (3), 3600) +
+            day_xml (days_ago (2), 7200) +
+            day_xml (days_ago (1), 0)
+        );
+
+        var statistics = new Services.Statistics ();
+        statistics.add_time_to_mode (WORK, 3600);
+        assert_cmpuint (statistics.week_forecast (), EQ, 25200);
+    }
+
+    private void test_week_forecast_last_day () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.deep_work_only = false;
+        start_week_days_ago (6);
+
+        write_statistics (day_xml (days_ago (6), 3600));
+
+        var statistics = new Services.Statistics ();
+        statistics.add_time_to_mode (WORK, 7200);
+        assert_cmpuint (statistics.week_forecast (), EQ, 10800);
+    }
+
+    // A single unfinished day says little, so the previous week is followed instead
+    private void test_week_forecast_first_day () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.deep_work_only = false;
+        start_week_days_ago (0);
+
+        write_statistics (day_xml (days_ago (7), 7000) + day_xml (days_ago (3), 7000));
+
+        var statistics = new Services.Statistics ();
+        statistics.add_time_to_mode (WORK, 1000);
+        assert_cmpuint (statistics.week_forecast (), EQ, 13000);
+    }
+
+    private void test_day_split () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_statistics (
+            day_xml (days_ago (3), 1800, 1800) +
+            day_xml (days_ago (2), 2700, 900) +
+            day_xml (days_ago (1), 0)
+        );
+
+        var statistics = new Services.Statistics ();
+        double work, rest;
+
+        assert_true (statistics.day_split (days_ago (3), out work, out rest));
+        assert_true (work == 0.5 && rest == 0.5);
+
+        assert_true (statistics.day_split (days_ago (2), out work, out rest));
+        assert_true (work == 0.75 && rest == 0.25);
+
+        assert_true (statistics.day_split (days_ago (1), out work, out rest));
+        assert_true (work == 0 && rest == 0);
+
+        assert_false (statistics.day_split (days_ago (4), out work, out rest));
+    }
+
+    private void test_compare_weeks () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.deep_work_only = false;
+        start_week_days_ago (20);
+
+        write_statistics (
+            day_xml (days_ago (20), 3600) +
+            day_xml (days_ago (18), 1800) +
+            day_xml (days_ago (13), 7200) +
+            day_xml (days_ago (11), 1800) +
+            day_xml (days_ago (8), 600)
+        );
+
+        var statistics = new Services.Statistics ();
+        Models.WeekComparison comparison = statistics.compare_weeks (days_ago (17), days_ago (9));
+        assert_cmpint (comparison.week_a_total, EQ, 5400);
+        assert_cmpint (comparison.week_b_total, EQ, 9600);
+        assert_cmpint (comparison.delta, EQ, 4200);
+
+        int[] deltas = { 3600, 0, 0, 0, 0, 600, 0 };
+        for (int i = 0; i < 7; i++) {
+            assert_cmpint (comparison.weekday_deltas[i], EQ, deltas[i]);
+        }
+
+        // Two anchors in the same week compare the week with itself
+        comparison = statistics.compare_weeks (days_ago (20), days_ago (14));
+        assert_cmpint (comparison.week_a_total, EQ, 5400);
+        assert_cmpint (comparison.delta, EQ, 0);
+        foreach (int delta in comparison.weekday_deltas) {
+            assert_cmpint (delta, EQ, 0);
+        }
+    }
+
+    private void test_sessions_model () {
+        if (!isolate ()) {
+            return;
+        }
+
+        DateTime morning = days_ago (2).add_hours (9);
+        write_statistics (
+            day_xml (days_ago (2), 5400, 900,
+                     session_xml (morning, 3600, 600) + session_xml (morning.add_hours (2), 1800, 300)) +
+            // Days saved before sessions were recorded have no detail
+            day_xml (days_ago (1), 3600)
+        );
+
+        var statistics = new Services.Statistics ();
+        ListModel model = statistics.sessions_model (days_ago (2));
+        assert_cmpuint (model.get_n_items (), EQ, 2);
+
+        var first = (Models.Session) model.get_item (0);
+        assert_true (first.start.equal (morning));
+        assert_cmpint (first.duration, EQ, 3600);
+        assert_cmpint (first.break_taken, EQ, 600);
+
+        var second = (Models.Session) model.get_item (1);
+        assert_true (second.start.equal (morning.add_hours (2)));
+        assert_cmpint (second.duration, EQ, 1800);
+        assert_cmpint (second.break_taken, EQ, 300);
+
+        assert_cmpuint (statistics.sessions_model (days_ago (1)).get_n_items (), EQ, 0);
+        assert_cmpuint (statistics.sessions_model (days_ago (3)).get_n_items (), EQ, 0);
+    }
+
+    private void test_newer_version () {
+        if (!isolate ()) {
+            return;
+        }
+
+        string days = day_xml (days_ago (1), 3600) + "<goal hours=\"40\"/>";
+        string document = "<?xml version=\"1.0\"?>\n<statistics version=\"99\">%s</statistics>\n";
+        write_data ("statistics.xml", document.printf (days));
+        string contents = read_data ("statistics.xml");
+
+        var statistics = new Services.Statistics ();
+        assert_true (statistics.read_only);
+        assert_nonnull (statistics.load_error);
+        assert_true ("version 99" in statistics.load_error);
+        assert_cmpint (statistics.find_day (days_ago (1)).worktime, EQ, 3600);
+
+        // Nothing that the newer version wrote is lost
+        statistics.add_time_to_mode (WORK, 1500);
+        assert_cmpstr (read_data ("statistics.xml"), EQ, contents);
+
+        try {
+            statistics.set_note (days_ago (1), "Note");
+            assert_not_reached ();
+        } catch (Services.StatisticsError e) {
+            assert_true (e.code == Services.StatisticsError.READ_ONLY);
+        }
+    }
+
+    private void test_breaks_saved () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var statistics = new Services.Statistics ();
+        statistics.add_time_to_mode (BREAK, 300);
+        statistics.add_time_to_mode (BREAK, 600);
+        // A break that was skipped right away is not counted
+        statistics.add_time_to_mode (BREAK, 0);
+
+        Models.Day? saved = saved_day (new DateTime.now_local ());
+        assert_nonnull (saved);
+        assert_cmpint (saved.breaks, EQ, 2);
+        assert_cmpint (saved.breaktime, EQ, 900);
+    }
+
+    private void test_average_break_length () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_statistics (
+            day_xml (days_ago (3), 3600, 900, "<breaks>3</breaks>") +
+            day_xml (days_ago (2), 3600, 600, "<breaks>1</breaks>") +
+            // Days saved before breaks were counted cannot be divided, so they are left out
+            day_xml (days_ago (1), 3600, 5000)
+        );
+
+        var statistics = new Services.Statistics ();
+        assert_cmpint (statistics.find_day (days_ago (1)).breaks, EQ, 0);
+        assert_true (statistics.average_break_length () == 375);
+
+        statistics.add_time_to_mode (BREAK, 500);
+        assert_true (statistics.average_break_length () == 400);
+    }
+
+    private void test_average_break_length_none () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_statistics (day_xml (days_ago (1), 3600, 900));
+
+        var statistics = new Services.Statistics ();
+        assert_true (statistics.average_break_length () == 0);
+    }
+
+    private void test_recompute () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_statistics (
+            day_xml (days_ago (40), 1000, 100) +
+            day_xml (days_ago (10), 9000, 200) +
+            day_xml (days_ago (2), 2000, 300)
+        );
+
+        var statistics = new Services.Statistics ();
+        assert_cmpstr (statistics.productive_day, EQ, days_ago (10).format ("%A"));
+
+        // Several days are changed directly, and the periods are only brought up to date at the end
+        statistics.find_day (days_ago (10)).worktime = 500;
+        statistics.find_day (days_ago (2)).worktime = 7000;
+        statistics.find_day (days_ago (2)).breaktime = 600;
+        assert_cmpint (statistics.week.worktime, EQ, 2000);
+
+        statistics.recompute ();
+        assert_cmpint (statistics.total.worktime, EQ, 8500);
+        assert_cmpint (statistics.total.breaktime, EQ, 900);
+        assert_cmpint (statistics.month.worktime, EQ, 7500);
+        assert_cmpint (statistics.week.worktime, EQ, 7000);
+        assert_cmpint (statistics.week.breaktime, EQ, 600);
+        assert_cmpstr (statistics.productive_day, EQ, days_ago (2).format ("%A"));
+    }
+
+    // How many days ago the Monday of the previous week was
+    private int last_monday_days_ago () {
+        return new DateTime.now_local ().get_day_of_week () - 1 + 7;
+    }
+
+    private void test_streak_skips_weekend () {
+        if (!isolate ()) {
+            return;
+        }
+
+        int monday = last_monday_days_ago ();
+        // Thursday, Friday and Monday are worked, the weekend between them is not
+        write_statistics (
+            day_xml (days_ago (monday + 4), 3600) +
+            day_xml (days_ago (monday + 3), 3600) +
+            day_xml (days_ago (monday), 3600)
+        );
+
+        var settings = new Services.Settings ();
+        var statistics = new Services.Statistics ();
+        assert_cmpuint (statistics.longest_streak (), EQ, 2);
+
+        settings.streak_skip_weekends = true;
+        assert_cmpuint (statistics.longest_streak (), EQ, 3);
+    }
+
+    private void test_streak_skips_configured_days () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_statistics (day_xml (days_ago (3), 3600) + day_xml (days_ago (0), 3600));
+
+        var settings = new Services.Settings ();
+        var statistics = new Services.Statistics ();
+        assert_cmpuint (statistics.current_streak (), EQ, 1);
+
+        // Every day of the week but those two is a non-work day
+        int work_days = (1 << (days_ago (3).get_day_of_week () - 1)) |
+                        (1 << (days_ago (0).get_day_of_week () - 1));
+        settings.non_work_days = 127 & ~work_days;
+        settings.streak_skip_weekends = true;
+        assert_cmpuint (statistics.current_streak (), EQ, 2);
+    }
+
+    private void test_goal_hit_rate_skips_weekend () {
+        if (!isolate ()) {
+            return;
+        }
+
+        int monday = last_monday_days_ago ();
+        write_statistics (
+            day_xml (days_ago (monday + 3), 5 * 3600) +
+            day_xml (days_ago (monday + 2), 3600) +
+            day_xml (days_ago (monday), 5 * 3600)
+        );
+
+        var settings = new Services.Settings ();
+        settings.daily_goal = 240;
+        var statistics = new Services.Statistics ();
+        assert_true (statistics.goal_hit_rate () == 2.0 / 3);
+
+        // The hour worked on Saturday no longer counts against the goal
+        settings.streak_skip_weekends = true;
+        assert_true (statistics.goal_hit_rate () == 1);
+    }
+
+    private void test_import_directory () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_statistics (day_xml (days_ago (3), 3600));
+        string dir = data_path ("export");
+        DirUtils.create_with_parents (dir, 0755);
+
+        write_data ("export/monday.xml", day_xml (days_ago (2), 1200, 300));
+        write_data ("export/broken.xml", "<day date=");
+        write_data ("export/other.xml", "<?xml version=\"1.0\"?>\n<sessions/>\n");
+        write_data ("export/broken.json", "{ \"statistics\": ");
+        write_data ("export/readme.txt", "Not statistics");
+        try {
+            Xml.Doc* doc = new Services.XmlBackend ().parse (
+                "<statistics version=\"1\">%s%s</statistics>".printf (
+                    day_xml (days_ago (3), 600), day_xml (days_ago (1), 1800)
+                ),
+                "fixture"
+            );
+            new Services.JsonBackend ().save (doc, Path.build_filename (dir, "tuesday.json"));
+            delete doc;
+        } catch (Services.StatisticsError e) {
+            error (e.message);
+        }
+
+        var statistics = new Services.Statistics ();
+        Models.MergeReport report;
+        try {
+            report = statistics.import_directory (dir);
+        } catch (Services.StatisticsError e) {
+            error (e.message);
+        }
+
+        // The bad files are reported, and do not keep the good ones from being imported
+        assert_cmpuint (report.added, EQ, 2);
+        assert_cmpuint (report.merged, EQ, 1);
+        assert_cmpint (report.errors.length, EQ, 3);
+        foreach (string message in report.errors) {
+            assert_true (message.has_prefix ("broken.") || message.has_prefix ("other.xml"));
+        }
+
+        assert_cmpint (statistics.find_day (days_ago (3)).worktime, EQ, 4200);
+        assert_cmpint (statistics.find_day (days_ago (2)).breaktime, EQ, 300);
+        assert_cmpint (saved_day (days_ago (1)).worktime, EQ, 1800);
+    }
+
+    private void test_longest_focus_run () {
+        if (!isolate ()) {
+            return;
+        }
+
+        DateTime date = days_ago (1);
+        string sessions = session_xml (date.add_hours (9), 1800) +
+                          // Three minutes after the first session ended
+                          session_xml (date.add_hours (9).add_minutes (33), 1200) +
+                          session_xml (date.add_hours (11), 2400);
+        write_statistics (day_xml (date, 5400, 0, sessions));
+
+        var settings = new Services.Settings ();
+        settings.focus_gap = 5;
+        var statistics = new Services.Statistics ();
+        Models.Day day = statistics.find_day (date);
+        assert_cmpint (statistics.longest_focus_run (day), EQ, 3000);
+
+        // A shorter gap splits the first two sessions, so the last one is the longest run
+        settings.focus_gap = 2;
+        assert_cmpint (statistics.longest_focus_run (day), EQ, 2400);
+
+        var empty = new Models.Day.unsaved (date);
+        assert_cmpint (statistics.longest_focus_run (empty), EQ, 0);
+    }
+
+    private void test_export_markdown () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_statistics (
+            day_xml (days_ago (3), 3600, 0, "<note>Left out</note>") +
+            day_xml (days_ago (2), 3600, 600, "<note>Reading | writing</note>") +
+            day_xml (days_ago (1), 1800, 300)
+        );
+
+        var statistics = new Services.Statistics ();
+        string path = data_path ("journal.md");
+        try {
+            // The range is given at times of the day other than midnight
+            statistics.export_markdown (path, days_ago (2).add_hours (12), days_ago (1).add_hours (8));
+        } catch (Services.StatisticsError e) {
+            error (e.message);
+        }
+
+        string[] lines = read_data ("journal.md").split ("\n");
+        assert_cmpstr (lines[0], EQ, "| Date | Worktime | Breaktime | Notes |");
+        assert_cmpstr (lines[1], EQ, "| --- | --- | --- | --- |");
+        assert_true (lines[2].has_prefix ("| %s |".printf (days_ago (2).format ("%F"))));
+        assert_true (lines[2].has_suffix (" | Reading \\| writing |"));
+        assert_true (lines[3].has_prefix ("| %s |".printf (days_ago (1).format ("%F"))));
+        assert_cmpstr (lines[4], EQ, "");
+        assert_true (lines[5].has_prefix ("**Total Worktime:** %s,".printf (TimeUtils.format_time (5400))));
+    }
+
+    private void test_updates_throttled () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.update_interval = 1;
+        var statistics = new Services.Statistics ();
+
+        int updates = 0;
+        int worktime_notified = 0;
+        statistics.updated.connect (() => updates++);
+        statistics.notify["today-worktime"].connect (() => worktime_notified++);
+
+        // A second of work saved on every tick, as the timer does
+        for (int i = 0; i < 10; i++) {
+            statistics.add_time_to_mode (WORK, 1, false);
+        }
+
+        assert_cmpint (statistics.today_worktime, EQ, 10);
+        assert_cmpint (worktime_notified, EQ, 10);
+        assert_cmpint (updates, EQ, 1);
+
+        // The changes made within the interval are emitted once, when it is over
+        var loop = new MainLoop ();
+        Timeout.add (1500, () => {
+            loop.quit ();
+            return Source.REMOVE;
+        });
+        loop.run ();
+        assert_cmpint (updates, EQ, 2);
+
+        // Once the interval is over, the next change is emitted right away
+        statistics.add_time_to_mode (WORK, 1, false);
+        assert_cmpint (updates, EQ, 3);
+    }
+
+    private void test_weekly_series_gaps () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.week_start = 1;
+        DateTime this_week = TimeUtils.week_start (new DateTime.now_local (), 1);
+        write_statistics (
+            day_xml (this_week.add_days (-21), 3600) +
+            day_xml (this_week.add_days (-20), 1800) +
+            day_xml (this_week, 600)
+        );
+
+        var statistics = new Services.Statistics ();
+        Models.SeriesPoint[] series = statistics.weekly_series (5);
+        assert_cmpint (series.length, EQ, 5);
+
+        // From the oldest week, with the weeks without time as zero
+        double[] expected = { 0, 5400, 0, 0, 600 };
+        for (int i = 0; i < series.length; i++) {
+            assert_true (series[i].value == expected[i]);
+            assert_true (series[i].date.equal (TimeUtils.week_start (this_week.add_days (7 * (i - 4)), 1)));
+        }
+
+        assert_cmpint (statistics.weekly_series (0).length, EQ, 0);
+    }
+
+    private void test_weekly_series_new_year () {
+        if (!isolate ()) {
+            return;
+        }
+
+        int year = new DateTime.now_local ().get_year ();
+        var new_years_eve = new DateTime.local (year - 1, 12, 31, 0, 0, 0);
+        var new_year = new DateTime.local (year, 1, 1, 0, 0, 0);
+
+        // The week starts the day before new year's eve, so both days are part of it
+        var settings = new Services.Settings ();
+        settings.week_start = new_years_eve.add_days (-1).get_day_of_week ();
+        var statistics = new Services.Statistics ();
+
+        // The days are older than the statistics keep, so they are only added to the loaded ones
+        var eve_day = new Models.Day.unsaved (new_years_eve);
+        eve_day.worktime = 3600;
+        var new_year_day = new Models.Day.unsaved (new_year);
+        new_year_day.worktime = 1200;
+        statistics.all_days.append (eve_day);
+        statistics.all_days.append (new_year_day);
+
+        DateTime week = TimeUtils.week_start (new_year, settings.week_start);
+        bool found = false;
+        foreach (Models.SeriesPoint point in statistics.weekly_series (60)) {
+            if (point.date.equal (week)) {
+                assert_true (point.value == 4800);
+                found = true;
+            } else {
+                assert_true (point.value == 0);
+            }
+        }
+        assert_true (found);
+    }
+
+    private void test_rest_day_saved () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_statistics (day_xml (days_ago (1), 3600));
+        var statistics = new Services.Statistics ();
+        assert_false (statistics.find_day (days_ago (1)).rest_day);
+
+        try {
+            statistics.set_rest_day (days_ago (2), true);
+            assert_true (saved_day (days_ago (2)).rest_day);
+            assert_true (read_data ("statistics.xml").contains ("rest=\"true\""));
+
+            statistics.set_rest_day (days_ago (2), false);
+        } catch (Services.StatisticsError e) {
+            error (e.message);
+        }
+
+        assert_false (saved_day (days_ago (2)).rest_day);
+        assert_false (read_data ("statistics.xml").contains ("rest="));
+    }
+
+    private void test_rest_day_streak () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_statistics (day_xml (days_ago (3), 3600) + day_xml (days_ago (1), 3600));
+        var statistics = new Services.Statistics ();
+        assert_cmpuint (statistics.current_streak (), EQ, 1);
+
+        try {
+            statistics.set_rest_day (days_ago (2), true);
+        } catch (Services.StatisticsError e) {
+            error (e.message);
+        }
+
+        // The rest day neither breaks the streak nor makes it longer
+        assert_cmpuint (statistics.current_streak (), EQ, 2);
+        assert_cmpuint (statistics.longest_streak (), EQ, 2);
+    }
+
+    private void test_most_improved_weekday () {
+        if (!isolate ()) {
+            return;
+        }
+
+        // Four weeks of an hour a day, but the day of the week of yesterday got longer lately
+        string days = "";
+        for (int i = 27; i > 0; i--) {
+            days += day_xml (days_ago (i), (i == 8 || i == 1) ? 5400 : 3600);
+        }
+        write_statistics (days);
+
+        var statistics = new Services.Statistics ();
+        int weekday;
+        double delta;
+        assert_true (statistics.most_improved_weekday (out weekday, out delta));
+        assert_cmpint (weekday, EQ, days_ago (1).get_day_of_week ());
+        assert_true (delta == 0.5);
+    }
+
+    private void test_most_improved_short_history () {
+        if (!isolate ()) {
+            return;
+        }
+
+        string days = "";
+        for (int i = 10; i > 0; i--) {
+            days += day_xml (days_ago (i), 600 * i);
+        }
+        write_statistics (days);
+
+        var statistics = new Services.Statistics ();
+        int weekday;
+        double delta;
+        assert_false (statistics.most_improved_weekday (out weekday, out delta));
+        assert_cmpint (weekday, EQ, 0);
+    }
+
+    private void test_locked_read_only () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_statistics (day_xml (days_ago (1), 3600));
+        lock_statistics ();
+        string saved = read_data ("statistics.xml");
+
+        var statistics = new Services.Statistics ();
+        assert_true (statistics.read_only);
+        assert_nonnull (statistics.load_error);
+        // The days are still shown
+        assert_cmpint (statistics.find_day (days_ago (1)).worktime, EQ, 3600);
+
+        try {
+            statistics.set_note (days_ago (1), "Written by the second instance");
+            assert_not_reached ();
+        } catch (Services.StatisticsError e) {
+            assert_true (e.code == Services.StatisticsError.READ_ONLY);
+        }
+
+        try {
+            statistics.set_rest_day (days_ago (2), true);
+            assert_not_reached ();
+        } catch (Services.StatisticsError e) {
+            assert_true (e.code == Services.StatisticsError.READ_ONLY);
+        }
+
+        try {
+            statistics.import_directory (Environment.get_user_data_dir ());
+            assert_not_reached ();
+        } catch (Services.StatisticsError e) {
+            assert_true (e.code == Services.StatisticsError.READ_ONLY);
+        }
+
+        // Neither the file nor the lock of the other instance are touched
+        statistics.add_time_to_mode (WORK, 600);
+        assert_cmpstr (read_data ("statistics.xml"), EQ, saved);
+        assert_cmpstr (read_data ("statistics.lock"), EQ, "%s:1".printf (Environment.get_host_name ()));
+    }
+
+    /*
+     * One day of the week has the highest average, another the longest single day and another the
+     * most days with work
+     */
+    private void write_productive_days () {
+        write_statistics (
+            day_xml (days_ago (8), 8 * 3600) +
+            day_xml (days_ago (1), 1800) +
+            day_xml (days_ago (2), 5 * 3600) +
+            day_xml (days_ago (17), 3600) +
+            day_xml (days_ago (10), 3600) +
+            day_xml (days_ago (3), 3600)
+        );
+    }
+
+    private void test_productive_day_metric (Services.ProductiveDayMetric metric, int days) {
+        write_productive_days ();
+        var settings = new Services.Settings ();
+        settings.productive_day_metric = metric;
+
+        var statistics = new Services.Statistics ();
+        assert_cmpstr (statistics.productive_day, EQ, days_ago (days).format ("%A"));
+    }
+
+    private void test_productive_day_average () {
+        if (!isolate ()) {
+            return;
+        }
+        test_productive_day_metric (AVERAGE_WORKTIME, 2);
+    }
+
+    private void test_productive_day_frequency () {
+        if (!isolate ()) {
+            return;
+        }
+        test_productive_day_metric (FREQUENCY, 3);
+    }
+
+    private void test_productive_day_peak () {
+        if (!isolate ()) {
+            return;
+        }
+        test_productive_day_metric (PEAK_DAY, 1);
+    }
+
+    private void test_productive_day_changed () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_productive_days ();
+        var settings = new Services.Settings ();
+        settings.productive_day_metric = AVERAGE_WORKTIME;
+        var statistics = new Services.Statistics ();
+
+        settings.productive_day_metric = PEAK_DAY;
+        assert_cmpstr (statistics.productive_day, EQ, days_ago (1).format ("%A"));
+    }
+
+    private void test_time_to_milestone () {
+        if (!isolate ()) {
+            return;
+        }
+
+        // An hour and a half every day of the last 30, today included, which is 45 hours
+        string days = "";
+        for (int i = 29; i >= 0; i--) {
+            days += day_xml (days_ago (i), 5400);
+        }
+        write_statistics (days);
+
+        var statistics = new Services.Statistics ();
+        int64? reached = statistics.time_to_milestone (45);
+        assert_nonnull (reached);
+        assert_cmpint ((int) reached, EQ, 0);
+
+        // 55 hours are left, which take 36 days and a part of another
+        int64? left = statistics.time_to_milestone (100);
+        assert_nonnull (left);
+        assert_cmpint ((int) left, EQ, 37);
+    }
+
+    private void test_time_to_milestone_no_recent_work () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_statistics (day_xml (days_ago (40), 3600));
+
+        var statistics = new Services.Statistics ();
+        assert_null (statistics.time_to_milestone (10));
+    }
+
+    private void test_saved_signal () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.storage_format = XML;
+        var statistics = new Services.Statistics ();
+
+        int saves = 0;
+        DateTime? last_saved = null;
+        statistics.saved.connect ((time) => {
+            saves++;
+            last_saved = time;
+        });
+
+        statistics.save ();
+        assert_cmpint (saves, EQ, 1);
+        assert_nonnull (last_saved);
+        assert_true (new DateTime.now_local ().difference (last_saved) < TimeSpan.MINUTE);
+
+        statistics.add_time_to_mode (WORK, 600);
+        assert_cmpint (saves, EQ, 2);
+
+        try {
+            statistics.set_note (days_ago (1), "Saved once");
+        } catch (Services.StatisticsError e) {
+            error (e.message);
+        }
+        assert_cmpint (saves, EQ, 3);
+
+        // Moving the statistics to another format saves them too
+        settings.storage_format = JSON;
+        assert_cmpint (saves, EQ, 4);
+    }
+
+    private void test_dump_xml () {
+        if (!isolate ()) {
+            return;
+        }
+
+        DateTime date = days_ago (1);
+        string children = session_xml (date.add_hours (9), 3600) + "<note>Draft</note>";
+        write_statistics (day_xml (date, 3600, 600, children));
+
+        var statistics = new Services.Statistics ();
+        statistics.add_time_to_mode (WORK, 1200);
+        string saved = read_data ("statistics.xml");
+
+        Xml.Doc* doc;
+        try {
+            string dump = statistics.dump_xml ();
+            doc = new Services.XmlBackend ().parse (dump, "dump");
+        } catch (Services.StatisticsError e) {
+            error (e.message);
+        }
+
+        // The dump reads back as the statistics in memory, notes included
+        Models.Day? yesterday = find_saved_day (doc, date);
+        assert_nonnull (yesterday);
+        assert_cmpint (yesterday.worktime, EQ, 3600);
+        assert_cmpint (yesterday.breaktime, EQ, 600);
+        assert_cmpstr (yesterday.note, EQ, "Draft");
+        assert_cmpuint (yesterday.sessions.length (), EQ, 1);
+        assert_cmpint (find_saved_day (doc, new DateTime.now_local ()).worktime, EQ, 1200);
+
+        // Nothing is written
+        assert_cmpstr (read_data ("statistics.xml"), EQ, saved);
+    }
+
+    private void test_freeze_keeps_streak () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_statistics (day_xml (days_ago (3), 3600) + day_xml (days_ago (1), 3600));
+        var statistics = new Services.Statistics ();
+        assert_cmpuint (statistics.current_streak (), EQ, 1);
+
+        try {
+            assert_true (statistics.use_freeze (days_ago (2)));
+            // A frozen day cannot be frozen again
+            assert_false (statistics.use_freeze (days_ago (2)));
+        } catch (Services.StatisticsError e) {
+            error (e.message);
+        }
+
+        assert_cmpuint (statistics.current_streak (), EQ, 2);
+        assert_cmpint (statistics.streak_info ().freezes_available, EQ, 1);
+        assert_true (saved_day (days_ago (2)).frozen);
+    }
+
+    private void test_freezes_run_out () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var statistics = new Services.Statistics ();
+        try {
+            assert_true (statistics.use_freeze (days_ago (5)));
+            assert_true (statistics.use_freeze (days_ago (4)));
+            assert_false (statistics.use_freeze (days_ago (3)));
+        } catch (Services.StatisticsError e) {
+            error (e.message);
+        }
+
+        assert_cmpint (statistics.streak_info ().freezes_available, EQ, 0);
+        assert_null (statistics.find_day (days_ago (3)));
+        assert_true (read_data ("statistics.xml").contains ("freezes=\"0\""));
+    }
+
+    private void test_freezes_refilled_on_day_change () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_statistics (day_xml (days_ago (1), 3600), " freezes=\"0\" freeze-month=\"2000-01\"");
+        var statistics = new Services.Statistics ();
+
+        try {
+            string dump_before = statistics.dump_xml ();
+            // The freezes of an earlier month are back, but asking for them changes nothing
+            assert_cmpint (statistics.streak_info ().freezes_available, EQ, 2);
+            assert_cmpstr (statistics.dump_xml (), EQ, dump_before);
+
+            statistics.day_changed ();
+            string dump = statistics.dump_xml ();
+            string month = new DateTime.now_local ().format ("%Y-%m");
+            assert_true (dump.contains ("freeze-month=\"%s\"".printf (month)));
+            assert_true (dump.contains ("freezes=\"2\""));
+        } catch (Services.StatisticsError e) {
+            error (e.message);
+        }
+    }
+
+    // Saves the days as a JSON backup in the data directory
+    private void write_json_backup (string filename, string days) {
+        try {
+            Xml.Doc* doc = new Services.XmlBackend ().parse (
+                "<statistics version=\"1\">%s</statistics>".printf (days), "fixture"
+            );
+            new Services.JsonBackend ().save (doc, data_path (filename));
+            delete doc;
+        } catch (Services.StatisticsError e) {
+            error (e.message);
+        }
+    }
+
+    private void test_restore_requires_force () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_statistics (day_xml (days_ago (1), 3600));
+        write_json_backup ("backup.json", day_xml (days_ago (2), 1800, 300));
+        var statistics = new Services.Statistics ();
+
+        try {
+            statistics.restore_from_json (data_path ("backup.json"));
+            assert_not_reached ();
+        } catch (Services.StatisticsError e) {
+            assert_true (e.code == Services.StatisticsError.NOT_EMPTY);
+        }
+        assert_cmpint (saved_day (days_ago (1)).worktime, EQ, 3600);
+
+        try {
+            statistics.restore_from_json (data_path ("backup.json"), true);
+        } catch (Services.StatisticsError e) {
+            error (e.message);
+        }
+
+        // The saved history is replaced by the one of the backup
+        assert_null (statistics.find_day (days_ago (1)));
+        assert_cmpint (statistics.find_day (days_ago (2)).worktime, EQ, 1800);
+        assert_null (saved_day (days_ago (1)));
+        assert_cmpint (saved_day (days_ago (2)).breaktime, EQ, 300);
+    }
+
+    private void test_restore_empty_history () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_json_backup ("backup.json", day_xml (days_ago (2), 1800));
+        var statistics = new Services.Statistics ();
+
+        try {
+            statistics.restore_from_json (data_path ("backup.json"));
+        } catch (Services.StatisticsError e) {
+            error (e.message);
+        }
+
+        assert_cmpint (statistics.find_day (days_ago (2)).worktime, EQ, 1800);
+        assert_cmpint (saved_day (days_ago (2)).worktime, EQ, 1800);
+    }
+
+    private void test_top_days () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_statistics (
+            day_xml (days_ago (5), 3600) +
+            day_xml (days_ago (4), 7200) +
+            day_xml (days_ago (1), 3600) +
+            day_xml (days_ago (3), 3600) +
+            day_xml (days_ago (2), 1800)
+        );
+
+        var statistics = new Services.Statistics ();
+        List<Models.Day> top = statistics.top_days (3);
+        assert_cmpuint (top.length (), EQ, 3);
+
+        // The days with the same worktime are ordered from the earliest
+        int[] expected = { 4, 5, 3 };
+        for (int i = 0; i < expected.length; i++) {
+            assert_true (TimeUtils.same_day (top.nth_data (i).date, days_ago (expected[i])));
+        }
+    }
+
+    private void test_top_days_short_history () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_statistics (day_xml (days_ago (1), 600));
+
+        var statistics = new Services.Statistics ();
+        // Yesterday and today are all there is
+        List<Models.Day> top = statistics.top_days (10);
+        assert_cmpuint (top.length (), EQ, 2);
+        assert_true (TimeUtils.same_day (top.data.date, days_ago (1)));
+        assert_cmpuint (statistics.top_days (0).length (), EQ, 0);
+    }
+
+    private void test_worktime_stddev () {
+        if (!isolate ()) {
+            return;
+        }
+
+        // A mean of 5000 seconds with a population standard deviation of 2000, worked out by hand
+        int[] worktimes = { 2000, 4000, 4000, 4000, 5000, 5000, 7000, 9000 };
+        string days = "";
+        for (int i = 0; i < worktimes.length; i++) {
+            days += day_xml (days_ago (i + 1), worktimes[i]);
+        }
+        // A day without work is not part of the habit
+        days += day_xml (days_ago (10), 0, 600);
+        write_statistics (days);
+
+        var statistics = new Services.Statistics ();
+        assert_true (statistics.worktime_stddev () == 2000);
+        assert_true (statistics.worktime_variation () == 0.4);
+    }
+
+    private void test_worktime_stddev_single_day () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_statistics (day_xml (days_ago (1), 3600));
+
+        var statistics = new Services.Statistics ();
+        assert_true (statistics.worktime_stddev () == 0);
+        assert_true (statistics.worktime_variation () == 0);
+    }
+
+    private void test_merge_conflict () {
+        if (!isolate ()) {
+            return;
+        }
+
+        string conflicted = string.join ("\n",
+            "<?xml version=\"1.0\"?>",
+            "<statistics version=\"1\">",
+            day_xml (days_ago (3), 1200),
+            "<<<<<<< HEAD",
+            day_xml (days_ago (2), 3600),
+            "=======",
+            day_xml (days_ago (2), 1800),
+            day_xml (days_ago (1), 600),
+            ">>>>>>> laptop",
+            "</statistics>",
+            ""
+        );
+        write_data ("statistics.xml", conflicted);
+
+        var statistics = new Services.Statistics ();
+        assert_null (statistics.load_error);
+
+        // The days of both sides are added up, and the day they share is kept once
+        assert_cmpint (statistics.find_day (days_ago (3)).worktime, EQ, 1200);
+        assert_cmpint (statistics.find_day (days_ago (2)).worktime, EQ, 5400);
+        assert_cmpint (statistics.find_day (days_ago (1)).worktime, EQ, 600);
+
+        assert_false (read_data ("statistics.xml").contains ("<<<<<<<"));
+        assert_cmpint (saved_day (days_ago (2)).worktime, EQ, 5400);
+        assert_cmpstr (read_data ("statistics.xml.conflict"), EQ, conflicted);
+    }
+
+    private void test_milestone_once () {
+        if (!isolate ()) {
+            return;
+        }
+
+        // Ten minutes short of ten hours
+        write_statistics (day_xml (days_ago (1), 35400), " milestone=\"0\"");
+        var statistics = new Services.Statistics ();
+
+        uint[] reached = {};
+        statistics.milestone_reached.connect ((hours) => reached += hours);
+
+        statistics.add_time_to_mode (WORK, 300);
+        assert_cmpint (reached.length, EQ, 0);
+
+        statistics.add_time_to_mode (WORK, 300);
+        assert_cmpint (reached.length, EQ, 1);
+        assert_cmpuint (reached[0], EQ, 10);
+        assert_cmpint (statistics.milestones_reached_this_session ().length, EQ, 1);
+        assert_true (read_data ("statistics.xml").contains ("milestone=\"10\""));
+
+        statistics.add_time_to_mode (WORK, 600);
+        assert_cmpint (reached.length, EQ, 1);
+    }
+
+    private void test_milestone_not_fired_again () {
+        if (!isolate ()) {
+            return;
+        }
+
+        // The ten hours were celebrated before, and some time was removed since then
+        write_statistics (day_xml (days_ago (1), 35000), " milestone=\"10\"");
+        var statistics = new Services.Statistics ();
+
+        uint[] reached = {};
+        statistics.milestone_reached.connect ((hours) => reached += hours);
+
+        statistics.add_time_to_mode (WORK, 1200);
+        assert_cmpint (reached.length, EQ, 0);
+        assert_cmpint (statistics.milestones_reached_this_session ().length, EQ, 0);
+    }
+
+    // The dates of the days in the statistics file, in the order they are saved
+    private string[] saved_dates () {
+        Xml.Doc* doc = Xml.Parser.parse_file (data_path ("statistics.xml"));
+        string[] dates = {};
+        for (Xml.Node* i = doc->get_root_element ()->children; i != null; i = i->next) {
+            if (i->type == ELEMENT_NODE && i->name == "day") {
+                dates += i->get_prop ("date");
+            }
+        }
+        delete doc;
+        return dates;
+    }
+
+    private void test_normalize_file () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.keep_empty_days = true;
+        write_statistics (
+            day_xml (days_ago (1).add_hours (15), 3600) +
+            day_xml (days_ago (3), 1200) +
+            day_xml (days_ago (2), 0) +
+            day_xml (days_ago (3).add_hours (9), 600)
+        );
+
+        var statistics = new Services.Statistics ();
+        try {
+            statistics.normalize_file ();
+        } catch (Services.StatisticsError e) {
+            error (e.message);
+        }
+
+        // Sorted, at midnight, with the days of the same date merged and the empty day dropped
+        string[] dates = saved_dates ();
+        assert_cmpint (dates.length, EQ, 3);
+        assert_cmpstr (dates[0], EQ, days_ago (3).format_iso8601 ());
+        assert_cmpstr (dates[1], EQ, days_ago (1).format_iso8601 ());
+        assert_cmpstr (dates[2], EQ, days_ago (0).format_iso8601 ());
+        assert_cmpint (saved_day (days_ago (3)).worktime, EQ, 1800);
+
+        // Normalizing again leaves the file as it is
+        string tidy = read_data ("statistics.xml");
+        try {
+            statistics.normalize_file ();
+        } catch (Services.StatisticsError e) {
+            error (e.message);
+        }
+        assert_cmpstr (read_data ("statistics.xml"), EQ, tidy);
+    }
+
+    private void test_export_histogram () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_statistics (
+            day_xml (days_ago (5), 10 * 60) +
+            day_xml (days_ago (4), 25 * 60) +
+            day_xml (days_ago (3), 29 * 60) +
+            day_xml (days_ago (2), 95 * 60) +
+            // Days without work are left out
+            day_xml (days_ago (1), 0, 600)
+        );
+
+        var statistics = new Services.Statistics ();
+        try {
+            statistics.export_histogram_csv (data_path ("histogram.csv"), 30);
+        } catch (Services.StatisticsError e) {
+            error (e.message);
+        }
+
+        // The empty bucket between the days is written too
+        assert_cmpstr (read_data ("histogram.csv"), EQ, "minutes,days\n0,3\n30,0\n60,0\n90,1\n");
+    }
+
+    private void test_export_histogram_no_bucket () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var statistics = new Services.Statistics ();
+        try {
+            statistics.export_histogram_csv (data_path ("histogram.csv"), 0);
+            assert_not_reached ();
+        } catch (Services.StatisticsError e) {
+            assert_true (e.code == Services.StatisticsError.INVALID_ARGUMENT);
+        }
+        assert_false (FileUtils.test (data_path ("histogram.csv"), EXISTS));
+    }
+
+    private void test_out_of_range_clamped () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.max_day_worktime = 24;
+        settings.skip_out_of_range_days = false;
+        write_statistics (day_xml (days_ago (2), 2000000000) + day_xml (days_ago (1), 3600));
+
+        var statistics = new Services.Statistics ();
+        assert_cmpint (statistics.total.worktime, EQ, 24 * 3600 + 3600);
+        // Only the counted worktime is clamped, the saved one is left as it is
+        Models.Day broken = statistics.find_day (days_ago (2));
+        assert_cmpint (broken.worktime, EQ, 2000000000);
+        assert_cmpint (broken.counted_worktime (), EQ, 24 * 3600);
+
+        statistics.add_time_to_mode (WORK, 60);
+        assert_cmpint (saved_day (days_ago (2)).worktime, EQ, 2000000000);
+
+        // The longest worktime of a day can be set for unusual uses
+        settings.max_day_worktime = 48;
+        assert_cmpint (statistics.total.worktime, EQ, 48 * 3600 + 3600 + 60);
+    }
+
+    private void test_out_of_range_skipped () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.skip_out_of_range_days = true;
+        write_statistics (
+            day_xml (days_ago (3), -500) +
+            day_xml (days_ago (2), 2000000000) +
+            day_xml (days_ago (1), 3600)
+        );
+
+        var statistics = new Services.Statistics ();
+        assert_null (statistics.find_day (days_ago (3)));
+        assert_null (statistics.find_day (days_ago (2)));
+        assert_cmpint (statistics.total.worktime, EQ, 3600);
+
+        // The skipped days stay saved, so they can still be repaired by hand
+        statistics.add_time_to_mode (WORK, 60);
+        assert_nonnull (saved_day (days_ago (2)));
+    }
+
+    private void test_streak_info () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.update_interval = 0;
+        // Four days in a row that ended a week ago, and three days until yesterday
+        string days = "";
+        foreach (int i in new int[] { 10, 9, 8, 7, 3, 2, 1 }) {
+            days += day_xml (days_ago (i), 3600);
+        }
+        write_statistics (days);
+
+        var statistics = new Services.Statistics ();
+        Models.StreakInfo info = statistics.streak_info ();
+        assert_cmpuint (info.current, EQ, 3);
+        assert_cmpuint (info.longest, EQ, 4);
+        assert_true (info.at_risk);
+        assert_cmpint (info.freezes_available, EQ, 2);
+
+        int changes = 0;
+        Models.StreakInfo? changed = null;
+        statistics.streak_changed.connect ((new_info) => {
+            changes++;
+            changed = new_info;
+        });
+
+        // Working today extends the streak and takes it out of risk
+        statistics.add_time_to_mode (WORK, 3600);
+        assert_cmpint (changes, EQ, 1);
+        assert_cmpuint (changed.current, EQ, 4);
+        assert_false (changed.at_risk);
+
+        // Nothing is emitted while the streak stays the same
+        statistics.add_time_to_mode (WORK, 600);
+        assert_cmpint (changes, EQ, 1);
+    }
+
+    private void test_empty_days_dropped () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.keep_empty_days = false;
+        write_statistics (
+            day_xml (days_ago (3), 3600) +
+            day_xml (days_ago (2), 0) +
+            day_xml (days_ago (1), 3600)
+        );
+
+        var statistics = new Services.Statistics ();
+        statistics.add_time_to_mode (WORK, 60);
+        assert_null (statistics.find_day (days_ago (2)));
+        assert_null (saved_day (days_ago (2)));
+
+        // The date without a record still breaks the streak
+        assert_cmpuint (statistics.current_streak (), EQ, 2);
+        assert_cmpuint (statistics.longest_streak (), EQ, 2);
+    }
+
+    private void test_empty_days_kept () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.keep_empty_days = true;
+        write_statistics (day_xml (days_ago (2), 0) + day_xml (days_ago (1), 3600));
+
+        var statistics = new Services.Statistics ();
+        statistics.add_time_to_mode (WORK, 60);
+        assert_nonnull (saved_day (days_ago (2)));
+    }
+
+    private void test_cumulative_series () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_statistics (
+            day_xml (days_ago (10), 1000) +
+            day_xml (days_ago (5), 600) +
+            day_xml (days_ago (3), 300) +
+            day_xml (days_ago (1), 5000)
+        );
+
+        var statistics = new Services.Statistics ();
+        // The range starts at a time of the day other than midnight
+        Models.SeriesPoint[] series = statistics.cumulative_series (days_ago (6).add_hours (10),
+                                                                    days_ago (2));
+
+        // The days before the range are counted, and the dates without a record keep the total
+        double[] expected = { 1000, 1600, 1600, 1900, 1900 };
+        assert_cmpint (series.length, EQ, expected.length);
+        for (int i = 0; i < series.length; i++) {
+            assert_true (series[i].date.equal (days_ago (6 - i)));
+            assert_true (series[i].value == expected[i]);
+        }
+    }
+
+    private void test_deep_work_only () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.deep_work_only = false;
+        settings.deep_work_minutes = 20;
+
+        // Five minutes of the day were saved before sessions were recorded
+        DateTime date = days_ago (1);
+        string sessions = session_xml (date.add_hours (9), 1800) +
+                          session_xml (date.add_hours (10), 600) +
+                          session_xml (date.add_hours (11), 300);
+        write_statistics (day_xml (date, 3000, 0, sessions));
+
+        var statistics = new Services.Statistics ();
+        Models.Day day = statistics.find_day (date);
+        assert_cmpint (day.counted_worktime (), EQ, 3000);
+        assert_cmpint (statistics.total.worktime, EQ, 3000);
+
+        // The short sessions are left out of the totals, but stay saved
+        settings.deep_work_only = true;
+        assert_cmpint (day.counted_worktime (), EQ, 2100);
+        assert_cmpint (statistics.total.worktime, EQ, 2100);
+        assert_cmpint (day.worktime, EQ, 3000);
+        assert_cmpuint (day.sessions.length (), EQ, 3);
+
+        settings.deep_work_minutes = 10;
+        assert_cmpint (statistics.total.worktime, EQ, 2700);
+    }
+
+    private void test_export_day_json () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.daily_goal = 45;
+
+        DateTime date = days_ago (1);
+        string sessions = session_xml (date.add_hours (9), 1800, 450) +
+                          session_xml (date.add_hours (10), 1200, 300);
+        write_statistics (day_xml (date, 3000, 750, sessions + "<note>A good day</note>"));
+
+        var statistics = new Services.Statistics ();
+        var parser = new Json.Parser ();
+        try {
+            statistics.export_day_json (date, data_path ("day.json"));
+            parser.load_from_file (data_path ("day.json"));
+        } catch (Error e) {
+            error (e.message);
+        }
+
+        Json.Object exported = parser.get_root ().get_object ();
+        assert_cmpstr (exported.get_string_member ("date"), EQ, date.format ("%F"));
+        assert_cmpint ((int) exported.get_int_member ("worktime"), EQ, 3000);
+        assert_cmpint ((int) exported.get_int_member ("breaktime"), EQ, 750);
+        assert_cmpstr (exported.get_string_member ("note"), EQ, "A good day");
+        assert_true (exported.get_boolean_member ("goal_met"));
+        assert_true (exported.has_member ("break_compliance"));
+        assert_true (exported.has_member ("earned_break"));
+        assert_true (exported.has_member ("longest_focus_run"));
+
+        Json.Array exported_sessions = exported.get_array_member ("sessions");
+        assert_cmpuint (exported_sessions.get_length (), EQ, 2);
+        Json.Object first = exported_sessions.get_object_element (0);
+        assert_cmpint ((int) first.get_int_member ("duration"), EQ, 1800);
+        assert_cmpint ((int) first.get_int_member ("break"), EQ, 450);
+        assert_true (first.has_member ("start"));
+    }
+
+    private void test_export_day_json_absent () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var statistics = new Services.Statistics ();
+        try {
+            statistics.export_day_json (days_ago (3), data_path ("day.json"));
+            assert_not_reached ();
+        } catch (Services.StatisticsError e) {
+            assert_true (e.code == Services.StatisticsError.NOT_FOUND);
+        }
+        assert_false (FileUtils.test (data_path ("day.json"), EXISTS));
+    }
+
+    private void check_blank_file (string contents) {
+        write_data ("statistics.xml", contents);
+
+        var statistics = new Services.Statistics ();
+        assert_null (statistics.load_error);
+        assert_cmpuint (statistics.all_days.length (), EQ, 1);
+        assert_nonnull (statistics.find_day (new DateTime.now_local ()));
+
+        // A fresh file is saved in the place of the blank one
+        assert_nonnull (saved_day (new DateTime.now_local ()));
+    }
+
+    private void test_zero_byte_file () {
+        if (!isolate ()) {
+            return;
+        }
+        check_blank_file ("");
+    }
+
+    private void test_whitespace_file () {
+        if (!isolate ()) {
+            return;
+        }
+        check_blank_file ("\xEF\xBB\xBF  \n\t\n");
+    }
+
+    private void test_break_adherence_series () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.break_policy = PERCENTAGE;
+        settings.break_percentage = 25;
+        settings.break_rounding = NONE;
+        settings.min_break_seconds = 60;
+        settings.max_break_seconds = 3600;
+
+        // Two days ago has no record, and today has no work yet
+        write_statistics (day_xml (days_ago (3), 3600, 1800) + day_xml (days_ago (1), 3600, 450));
+
+        var statistics = new Services.Statistics ();
+        Models.SeriesPoint[] series = statistics.break_adherence_series (4);
+        assert_cmpint (series.length, EQ, 4);
+
+        double[] expected = { 1, 1, 0.5, 1 };
+        for (int i = 0; i < series.length; i++) {
+            assert_true (series[i].date.equal (days_ago (3 - i)));
+            assert_true (series[i].value == expected[i]);
+        }
+
+        assert_cmpint (statistics.break_adherence_series (1).length, EQ, 1);
+        assert_cmpint (statistics.break_adherence_series (0).length, EQ, 0);
+    }
+
+    // What the application does on shutdown, alongside the final save
+    private void test_auto_export () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.auto_export = false;
+        settings.auto_export_path = data_path ("export.csv");
+        settings.auto_export_format = CSV;
+        write_statistics (day_xml (days_ago (1), 3600, 600));
+
+        var statistics = new Services.Statistics ();
+        statistics.auto_export ();
+        assert_false (FileUtils.test (data_path ("export.csv"), EXISTS));
+
+        settings.auto_export = true;
+        statistics.auto_export ();
+        string exported = read_data ("export.csv");
+        assert_true (exported.has_prefix ("date,worktime,breaktime,pausetime\n"));
+        assert_true ("%s,3600,600,0\n".printf (days_ago (1).format ("%F")) in exported);
+        assert_cmpstr (settings.auto_export_error, EQ, "");
+    }
+
+    // The export is skipped without stopping the shutdown, and the failure is kept for the next launch
+    private void test_auto_export_unwritable () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.auto_export = true;
+        settings.auto_export_path = data_path ("missing/export.json");
+        settings.auto_export_format = JSON;
+
+        var statistics = new Services.Statistics ();
+        statistics.auto_export ();
+        assert_cmpstr (settings.auto_export_error, NE, "");
+
+        settings.auto_export_path = data_path ("export.json");
+        statistics.auto_export ();
+        assert_true (FileUtils.test (data_path ("export.json"), EXISTS));
+        assert_cmpstr (settings.auto_export_error, EQ, "");
+    }
+
+    private void test_daypart_split () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.afternoon_start = 12;
+        settings.evening_start = 18;
+
+        // Sessions starting on the boundaries, and crossing them and midnight
+        DateTime date = days_ago (2);
+        string sessions = session_xml (date.add_hours (9), 3600) +
+                          session_xml (date.add_hours (12), 1800) +
+                          session_xml (date.add_hours (17).add_minutes (30), 3600) +
+                          session_xml (date.add_hours (18), 1200) +
+                          session_xml (date.add_hours (23).add_minutes (30), 3600) +
+                          "<session dur=\"600\" break=\"0\"/>";
+        write_statistics (day_xml (date, 14400, 0, sessions));
+
+        var statistics = new Services.Statistics ();
+        uint64 morning, afternoon, evening;
+        statistics.daypart_split (out morning, out afternoon, out evening);
+        assert_cmpuint ((uint) morning, EQ, 5400);
+        assert_cmpuint ((uint) afternoon, EQ, 3600);
+        assert_cmpuint ((uint) evening, EQ, 4800);
+
+        // A later afternoon moves the session at noon to the morning
+        settings.afternoon_start = 13;
+        statistics.daypart_split (out morning, out afternoon, out evening);
+        assert_cmpuint ((uint) morning, EQ, 7200);
+        assert_cmpuint ((uint) afternoon, EQ, 1800);
+        assert_cmpuint ((uint) evening, EQ, 4800);
+    }
+
+    private void test_activity_feed () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.daily_goal = 60;
+
+        // The goal is only reached two days ago, which is also the best day so far
+        write_statistics (day_xml (days_ago (3), 1800) + day_xml (days_ago (2), 3600) +
+                          day_xml (days_ago (1), 2400));
+
+        var statistics = new Services.Statistics ();
+        ListModel feed = statistics.activity_feed (10);
+        assert_cmpuint (feed.get_n_items (), EQ, 2);
+
+        var best = (Models.FeedItem) feed.get_item (0);
+        assert_cmpstr (best.icon_name, EQ, "starred-symbolic");
+        assert_true (best.message.has_prefix ("New best day"));
+        assert_true (best.timestamp.equal (days_ago (2)));
+
+        var goal = (Models.FeedItem) feed.get_item (1);
+        assert_cmpstr (goal.message, EQ, "Reached the daily goal");
+        assert_true (goal.timestamp.equal (days_ago (2)));
+
+        // Only the newest events are kept
+        feed = statistics.activity_feed (1);
+        assert_cmpuint (feed.get_n_items (), EQ, 1);
+        assert_cmpstr (((Models.FeedItem) feed.get_item (0)).icon_name, EQ, "starred-symbolic");
+    }
+
+    private void test_normalize_dates () {
+        if (!isolate ()) {
+            return;
+        }
+
+        // A date without a time, a time without a time zone and a time with a fraction of a second
+        DateTime first = days_ago (2);
+        DateTime second = days_ago (1);
+        string session = "<session start=\"%s\" dur=\"1800\" break=\"0\"/>".printf (
+            first.add_hours (9).format ("%FT%T")
+        );
+        string first_day = day_xml (first, 1800, 0, session);
+        string second_day = day_xml (second, 3600);
+        write_statistics (first_day.replace (first.format_iso8601 (), first.format ("%F")) +
+                          second_day.replace (second.format_iso8601 (), second.format ("%FT%T.000%:z")));
+
+        var statistics = new Services.Statistics ();
+        assert_cmpuint (statistics.normalize_dates (), EQ, 3);
+        assert_cmpuint (statistics.normalize_dates (), EQ, 0);
+
+        string saved = read_data ("statistics.xml");
+        assert_true ("date=\"%s\"".printf (first.format_iso8601 ()) in saved);
+        assert_true ("date=\"%s\"".printf (second.format_iso8601 ()) in saved);
+        assert_true ("start=\"%s\"".printf (first.add_hours (9).format_iso8601 ()) in saved);
+        assert_cmpint (saved_day (first).worktime, EQ, 1800);
+        assert_true (saved_day (first).sessions.data.start.equal (first.add_hours (9)));
+    }
+
+    private void test_punchcard () {
+        if (!isolate ()) {
+            return;
+        }
+
+        DateTime monday = days_ago (last_monday_days_ago ());
+        DateTime sunday = days_ago (last_monday_days_ago () + 1);
+        string monday_sessions = session_xml (monday.add_hours (9), 1800) +
+                                 session_xml (monday.add_hours (9).add_minutes (45), 600) +
+                                 session_xml (monday.add_hours (23).add_minutes (30), 3600) +
+                                 "<session dur=\"600\" break=\"0\"/>";
+        write_statistics (day_xml (sunday, 1200, 0, session_xml (sunday.add_hours (12), 1200)) +
+                          day_xml (monday, 6600, 0, monday_sessions));
+
+        var statistics = new Services.Statistics ();
+        uint[,] cells = statistics.punchcard ();
+        assert_cmpint (cells.length[0], EQ, 7);
+        assert_cmpint (cells.length[1], EQ, 24);
+
+        // A session is counted in the hour it started, even if it goes past midnight
+        assert_cmpuint (cells[0, 9], EQ, 2400);
+        assert_cmpuint (cells[0, 23], EQ, 3600);
+        assert_cmpuint (cells[6, 12], EQ, 1200);
+
+        uint total = 0;
+        for (int weekday = 0; weekday < 7; weekday++) {
+            for (int hour = 0; hour < 24; hour++) {
+                total += cells[weekday, hour];
+            }
+        }
+        assert_cmpuint (total, EQ, 7200);
+    }
+
+    private void test_break_ratio () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.deep_work_only = false;
+        settings.break_policy = PERCENTAGE;
+        settings.break_percentage = 25;
+        settings.break_rounding = NONE;
+        settings.min_break_seconds = 60;
+        settings.max_break_seconds = 3600;
+
+        write_statistics (day_xml (days_ago (2), 3600, 900) + day_xml (days_ago (1), 3600, 450));
+
+        var statistics = new Services.Statistics ();
+        assert_true (statistics.realized_break_ratio () == 0.1875);
+        assert_true (statistics.planned_break_ratio () == 0.25);
+        assert_cmpstr (statistics.break_ratio_insight (), EQ, "You are resting less than planned");
+    }
+
+    // Only the counted worktime is compared with the breaks, without the short sessions
+    private void test_break_ratio_deep_work () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.deep_work_only = true;
+        settings.deep_work_minutes = 20;
+
+        DateTime date = days_ago (1);
+        string sessions = session_xml (date.add_hours (9), 1800, 450) +
+                          session_xml (date.add_hours (10), 600, 150);
+        write_statistics (day_xml (date, 2400, 600, sessions));
+
+        var statistics = new Services.Statistics ();
+        assert_true (statistics.realized_break_ratio () == 600.0 / 1800);
+    }
+
+    private void test_break_ratio_without_work () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var statistics = new Services.Statistics ();
+        assert_true (statistics.realized_break_ratio () == 0);
+        assert_true (statistics.planned_break_ratio () == 0);
+        assert_null (statistics.break_ratio_insight ());
+    }
+
+    // A database like the one of GNOME Pomodoro, with the entries given as start, state and elapsed
+    private void write_pomodoro_db (string filename, string entries) {
+        Sqlite.Database db;
+        Sqlite.Database.open (data_path (filename), out db);
+        string sql = """
+            CREATE TABLE entries (datetime_string TEXT, state TEXT, elapsed INTEGER);
+            INSERT INTO entries VALUES %s;
+        """.printf (entries);
+        assert_cmpint (db.exec (sql), EQ, Sqlite.OK);
+    }
+
+    private string pomodoro_entry (DateTime start, string state, int elapsed) {
+        return "('%s', '%s', %d)".printf (start.format_iso8601 (), state, elapsed);
+    }
+
+    private void test_import_gnome_pomodoro () {
+        if (!isolate ()) {
+            return;
+        }
+
+        DateTime first = days_ago (2).add_hours (9);
+        DateTime second = days_ago (1).add_hours (10);
+        write_pomodoro_db ("pomodoro.sqlite", string.joinv (", ", {
+            pomodoro_entry (first, "pomodoro", 1500),
+            pomodoro_entry (first.add_minutes (25), "short-break", 300),
+            pomodoro_entry (first.add_minutes (30), "pomodoro", 1500),
+            pomodoro_entry (first.add_minutes (55), "long-break", 900),
+            pomodoro_entry (second, "pomodoro", 1200),
+            pomodoro_entry (second.add_minutes (20), "null", 600),
+            pomodoro_entry (second.add_minutes (30), "pomodoro", 0)
+        }));
+        write_statistics (day_xml (days_ago (1), 3600, 600));
+
+        var statistics = new Services.Statistics ();
+        Models.MergeReport report;
+        try {
+            report = statistics.import_gnome_pomodoro (data_path ("pomodoro.sqlite"));
+        } catch (Services.StatisticsError e) {
+            error (e.message);
+        }
+        assert_cmpuint (report.added, EQ, 1);
+        assert_cmpuint (report.merged, EQ, 1);
+
+        // Every pomodoro becomes a session, with the breaks after it as its break
+        Models.Day imported = statistics.find_day (days_ago (2));
+        assert_cmpint (imported.worktime, EQ, 3000);
+        assert_cmpint (imported.breaktime, EQ, 1200);
+        assert_cmpint (imported.breaks, EQ, 2);
+        assert_cmpuint (imported.sessions.length (), EQ, 2);
+        assert_cmpint (imported.sessions.nth_data (0).break_taken, EQ, 300);
+        assert_cmpint (imported.sessions.nth_data (1).break_taken, EQ, 900);
+        assert_true (imported.sessions.nth_data (1).start.equal (first.add_minutes (30)));
+
+        // Entries of other states, or without elapsed time, are left out
+        assert_cmpint (saved_day (days_ago (1)).worktime, EQ, 4800);
+        assert_cmpint (saved_day (days_ago (1)).breaktime, EQ, 600);
+    }
+
+    private void test_import_gnome_pomodoro_invalid () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_data ("pomodoro.sqlite", "");
+        var statistics = new Services.Statistics ();
+        try {
+            statistics.import_gnome_pomodoro (data_path ("pomodoro.sqlite"));
+            assert_not_reached ();
+        } catch (Services.StatisticsError e) {
+            assert_true (e.code == Services.StatisticsError.PARSE);
+        }
+    }
+
+    private void test_week_progress () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.week_start = 1;
+        settings.weekly_goal = 600;
+
+        // The day before the week started is not counted
+        DateTime week = TimeUtils.week_start (new DateTime.now_local (), 1);
+        write_statistics (day_xml (TimeUtils.normalize_to_local_midnight (week.add_hours (-12)), 36000));
+
+        var statistics = new Services.Statistics ();
+        assert_cmpuint (statistics.weekly_goal (), EQ, 600);
+        assert_true (statistics.week_progress () == 0);
+
+        int reached = 0;
+        statistics.weekly_goal_reached.connect (() => reached++);
+
+        statistics.add_time_to_mode (WORK, 3 * 3600);
+        assert_true (statistics.week_progress () == 0.3);
+        assert_cmpint (reached, EQ, 0);
+
+        statistics.add_time_to_mode (WORK, 7 * 3600);
+        assert_true (statistics.week_progress () == 1);
+        assert_cmpint (reached, EQ, 1);
+        assert_true ("weekly-goal=\"%s\"".printf (week.format ("%F")) in read_data ("statistics.xml"));
+
+        // An exceeded week goes over the goal, and is only celebrated once
+        statistics.add_time_to_mode (WORK, 5 * 3600);
+        assert_true (statistics.week_progress () == 1.5);
+        assert_cmpint (reached, EQ, 1);
+    }
+
+    private void test_offhours_worktime () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.work_hours_start = 9 * 60;
+        settings.work_hours_end = 17 * 60;
+
+        // Inside the work hours, across their start and their end, and outside of them
+        DateTime date = days_ago (1);
+        string sessions = session_xml (date.add_hours (10), 3600) +
+                          session_xml (date.add_hours (8).add_minutes (30), 3600) +
+                          session_xml (date.add_hours (16).add_minutes (30), 3600) +
+                          session_xml (date.add_hours (20), 1800) +
+                          "<session dur=\"3600\" break=\"0\"/>";
+        write_statistics (day_xml (date, 16200, 0, sessions));
+
+        var statistics = new Services.Statistics ();
+        assert_cmpuint ((uint) statistics.offhours_worktime (), EQ, 5400);
+        assert_cmpstr (statistics.offhours_text (), EQ, "43% of your work is off-hours");
+    }
+
+    // Work hours that end before they start go past midnight
+    private void test_offhours_overnight () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.work_hours_start = 22 * 60;
+        settings.work_hours_end = 6 * 60;
+
+        DateTime date = days_ago (2);
+        string sessions = session_xml (date.add_hours (23), 7200) +
+                          session_xml (days_ago (1).add_hours (5).add_minutes (30), 3600);
+        write_statistics (day_xml (date, 10800, 0, sessions));
+
+        var statistics = new Services.Statistics ();
+        assert_cmpuint ((uint) statistics.offhours_worktime (), EQ, 1800);
+    }
+
+    private void test_offhours_without_sessions () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_statistics (day_xml (days_ago (1), 3600));
+        var statistics = new Services.Statistics ();
+        assert_cmpuint ((uint) statistics.offhours_worktime (), EQ, 0);
+        assert_null (statistics.offhours_text ());
+    }
+
+    private void test_gap_days () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.streak_skip_weekends = false;
+        settings.active_threshold = 1800;
+
+        // Five days ago has no record, four days ago is below the threshold and three is a rest day
+        write_statistics (day_xml (days_ago (6), 3600) + day_xml (days_ago (4), 600) +
+                          day_xml (days_ago (3), 0, 0, "", " rest=\"true\"") +
+                          day_xml (days_ago (2), 3600) + day_xml (days_ago (1), 3600));
+
+        var statistics = new Services.Statistics ();
+        List<DateTime> gaps = statistics.gap_days (days_ago (6), days_ago (1).add_hours (12));
+        assert_cmpuint (gaps.length (), EQ, 2);
+        assert_true (gaps.nth_data (0).equal (days_ago (5)));
+        assert_true (gaps.nth_data (1).equal (days_ago (4)));
+
+        settings.active_threshold = 0;
+        gaps = statistics.gap_days (days_ago (6), days_ago (1));
+        assert_cmpuint (gaps.length (), EQ, 1);
+        assert_true (gaps.nth_data (0).equal (days_ago (5)));
+
+        // A range of active days has no gaps
+        assert_cmpuint (statistics.gap_days (days_ago (2), days_ago (1)).length (), EQ, 0);
+    }
+
+    // An active day whose first session started at the time
+    private string day_started_at (int days, int hour, int minute) {
+        DateTime start = days_ago (days).add_hours (hour).add_minutes (minute);
+        return day_xml (days_ago (days), 3600, 0, session_xml (start, 3600));
+    }
+
+    private void test_typical_start_time () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.active_threshold = 1800;
+
+        // The early and late days do not move the median, neither does a day below the threshold
+        // The sessions of a day are not always saved in order
+        string unordered = session_xml (days_ago (3).add_hours (14), 1800) +
+                           session_xml (days_ago (3).add_hours (9).add_minutes (15), 1800);
+        write_statistics (day_started_at (5, 5, 0) + day_started_at (4, 9, 0) +
+                          day_xml (days_ago (3), 3600, 0, unordered) +
+                          day_started_at (2, 9, 30) + day_started_at (1, 23, 0) +
+                          day_xml (days_ago (6), 600, 0, session_xml (days_ago (6).add_hours (3), 600)));
+
+        var statistics = new Services.Statistics ();
+        int hour, minute;
+        assert_true (statistics.typical_start_time (out hour, out minute));
+        assert_cmpint (hour, EQ, 9);
+        assert_cmpint (minute, EQ, 15);
+    }
+
+    // With an even number of days the median is between the two middle starts
+    private void test_typical_start_time_even () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_statistics (day_started_at (4, 9, 0) + day_started_at (3, 9, 10) +
+                          day_started_at (2, 9, 30) + day_started_at (1, 22, 0));
+
+        var statistics = new Services.Statistics ();
+        int hour, minute;
+        assert_true (statistics.typical_start_time (out hour, out minute));
+        assert_cmpint (hour, EQ, 9);
+        assert_cmpint (minute, EQ, 20);
+    }
+
+    private void test_typical_start_time_insufficient () {
+        if (!isolate ()) {
+            return;
+        }
+
+        // A day saved before sessions were recorded has no start
+        write_statistics (day_started_at (2, 9, 0) + day_started_at (1, 9, 30) +
+                          day_xml (days_ago (3), 3600));
+
+        var statistics = new Services.Statistics ();
+        int hour, minute;
+        assert_false (statistics.typical_start_time (out hour, out minute));
+    }
+
+    private void test_dashboard_snapshot () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.daily_goal = 60;
+
+        write_statistics (day_xml (days_ago (9), 5400, 900) + day_xml (days_ago (2), 1800, 300) +
+                          day_xml (days_ago (1), 3600, 600));
+
+        var statistics = new Services.Statistics ();
+        statistics.add_time_to_mode (WORK, 1800);
+        statistics.add_time_to_mode (BREAK, 300);
+
+        var now = new DateTime.now_local ();
+        Models.DashboardSnapshot snapshot = statistics.dashboard_snapshot ();
+        assert_cmpint (snapshot.today_worktime, EQ, statistics.today_worktime);
+        assert_cmpint (snapshot.today_worktime, EQ, 1800);
+        assert_cmpint (snapshot.today_breaktime, EQ, statistics.today_breaktime);
+        assert_cmpuint (snapshot.current_streak, EQ, statistics.current_streak ());
+        assert_cmpuint (snapshot.longest_streak, EQ, statistics.longest_streak ());
+        assert_cmpint (snapshot.week_worktime, EQ, statistics.week.worktime);
+        assert_cmpint (snapshot.week_trend, EQ, statistics.compare_weeks (now.add_days (-7), now).delta);
+        assert_cmpstr (snapshot.productive_day, EQ, statistics.productive_day);
+        assert_true (snapshot.goal_progress == 0.5);
+
+        assert_nonnull (snapshot.best_day);
+        assert_true (snapshot.best_day.equal (days_ago (9)));
+        assert_cmpint (snapshot.best_day_worktime, EQ, 5400);
+    }
+
+    private void check_metric (Services.Statistics statistics, string id, double expected) {
+        double? value = statistics.metric_by_id (id);
+        assert_nonnull (value);
+        assert_true (value == expected);
+    }
+
+    private void test_metric_by_id () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.daily_goal = 60;
+        settings.weekly_goal = 600;
+
+        write_statistics (day_xml (days_ago (1), 3600, 600));
+        var statistics = new Services.Statistics ();
+        statistics.add_time_to_mode (WORK, 1800);
+        statistics.add_time_to_mode (BREAK, 300);
+
+        check_metric (statistics, "today-worktime", 1800);
+        check_metric (statistics, "today-breaktime", 300);
+        check_metric (statistics, "streak", statistics.current_streak ());
+        check_metric (statistics, "longest-streak", statistics.longest_streak ());
+        check_metric (statistics, "week-worktime", statistics.week.worktime);
+        check_metric (statistics, "weekly-average", statistics.week.worktime / 7.0);
+        check_metric (statistics, "month-worktime", statistics.month.worktime);
+        check_metric (statistics, "total-worktime", 5400);
+        check_metric (statistics, "goal-progress", 0.5);
+        check_metric (statistics, "week-progress", statistics.week_progress ());
+
+        // The identifiers of a newer version, or mistyped ones, are not an error
+        assert_null (statistics.metric_by_id ("yearly-worktime"));
+        assert_null (statistics.metric_by_id (""));
+        assert_null (statistics.metric_by_id ("Streak"));
+    }
+
+    private void test_break_skip_rate () {
+        if (!isolate ()) {
+            return;
+        }
+
+        DateTime date = days_ago (1);
+        DateTime today = days_ago (0);
+        string sessions = session_xml (date.add_hours (9), 1800, 300) +
+                          session_xml (date.add_hours (10), 1800) +
+                          session_xml (date.add_hours (11), 1800, 300);
+        // The break of the last session of today may still be taken
+        string today_sessions = session_xml (today.add_hours (9), 1800, 300) +
+                                session_xml (today.add_hours (10), 1800);
+        write_statistics (day_xml (date, 5400, 600, sessions) + day_xml (today, 3600, 300, today_sessions));
+
+        var statistics = new Services.Statistics ();
+        assert_true (statistics.break_skip_rate () == 0.25);
+    }
+
+    private void test_break_skip_rate_always_skipped () {
+        if (!isolate ()) {
+            return;
+        }
+
+        DateTime date = days_ago (2);
+        write_statistics (day_xml (date, 3600, 0, session_xml (date.add_hours (9), 1800) +
+                                                  session_xml (date.add_hours (10), 1800)));
+
+        var statistics = new Services.Statistics ();
+        assert_true (statistics.break_skip_rate () == 1);
+    }
+
+    private void test_break_skip_rate_without_sessions () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_statistics (day_xml (days_ago (1), 3600, 600));
+
+        var statistics = new Services.Statistics ();
+        assert_true (statistics.break_skip_rate () == 0);
+    }
+
+    private void test_sample_mode () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_statistics (day_xml (days_ago (1), 3600, 600));
+        var statistics = new Services.Statistics ();
+        string saved = read_data ("statistics.xml");
+
+        statistics.load_sample ();
+        assert_true (statistics.sample_mode);
+        assert_cmpuint (statistics.all_days.length (), GT, 30);
+        assert_cmpint (statistics.total.worktime, GT, 3600);
+
+        // The made up history goes back more than a month
+        bool older_than_month = false;
+        foreach (unowned Models.Day day in statistics.all_days) {
+            older_than_month |= TimeUtils.days_between (day.date, new DateTime.now_local ()) > 31;
+        }
+        assert_true (older_than_month);
+
+        // The sample history is the same every time it is loaded
+        int sample_worktime = statistics.total.worktime;
+        statistics.unload_sample ();
+        statistics.load_sample ();
+        assert_cmpint (statistics.total.worktime, EQ, sample_worktime);
+
+        // Nothing reaches the file of the user while the sample is shown
+        statistics.add_time_to_mode (WORK, 600);
+        statistics.save ();
+        try {
+            statistics.set_note (days_ago (1), "Sample");
+            assert_not_reached ();
+        } catch (Services.StatisticsError e) {
+            assert_true (e.code == Services.StatisticsError.READ_ONLY);
+        }
+        assert_cmpstr (read_data ("statistics.xml"), EQ, saved);
+
+        statistics.unload_sample ();
+        assert_false (statistics.sample_mode);
+        assert_cmpint (statistics.find_day (days_ago (1)).worktime, EQ, 3600);
+        assert_cmpstr (statistics.find_day (days_ago (1)).note, EQ, "");
+
+        statistics.add_time_to_mode (WORK, 600);
+        assert_cmpint (saved_day (new DateTime.now_local ()).worktime, EQ, 600);
+    }
+
+    private void test_total_tracked_hours () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_statistics (day_xml (days_ago (3), 3600, 900) + day_xml (days_ago (1), 5400, 1800));
+
+        var statistics = new Services.Statistics ();
+        assert_true (statistics.total_tracked_hours () == 3.25);
+
+        // The time of today counts as soon as it is tracked
+        statistics.add_time_to_mode (WORK, 1350);
+        assert_true (statistics.total_tracked_hours () == 3.625);
+    }
+
+    private void test_set_energy () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_statistics (day_xml (days_ago (1), 3600));
+        var statistics = new Services.Statistics ();
+
+        try {
+            statistics.set_energy (days_ago (1), 5);
+            statistics.set_energy (days_ago (2), 2);
+        } catch (Services.StatisticsError e) {
+            error (e.message);
+        }
+        assert_cmpuint (saved_day (days_ago (1)).energy, EQ, 5);
+        assert_cmpuint (saved_day (days_ago (2)).energy, EQ, 2);
+
+        try {
+            statistics.set_energy (days_ago (1), 0);
+        } catch (Services.StatisticsError e) {
+            error (e.message);
+        }
+        assert_cmpuint (saved_day (days_ago (1)).energy, EQ, 0);
+        assert_cmpint (saved_day (days_ago (1)).worktime, EQ, 3600);
+    }
+
+    private void test_worktime_by_energy () {
+        if (!isolate ()) {
+            return;
+        }
+
+        // Two days rated 4 are added up, and the day without a rating is left out
+        write_statistics (day_xml (days_ago (4), 1800, 0, "", " energy=\"1\"") +
+                          day_xml (days_ago (3), 7200, 0, "", " energy=\"4\"") +
+                          day_xml (days_ago (2), 5400, 0, "", " energy=\"4\"") +
+                          day_xml (days_ago (1), 3600));
+
+        var statistics = new Services.Statistics ();
+        uint64[] worktime = statistics.worktime_by_energy ();
+        assert_cmpint (worktime.length, EQ, 5);
+        assert_cmpuint ((uint) worktime[0], EQ, 1800);
+        assert_cmpuint ((uint) worktime[1], EQ, 0);
+        assert_cmpuint ((uint) worktime[2], EQ, 0);
+        assert_cmpuint ((uint) worktime[3], EQ, 12600);
+        assert_cmpuint ((uint) worktime[4], EQ, 0);
+    }
+
+    private void test_backup_due () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        var statistics = new Services.Statistics ();
+        settings.backup_path = data_path ("backups");
+        settings.backup_schedule = DAILY;
+        var now = new DateTime.local (2023, 3, 8, 0, 30, 0);
+
+        // The first backup is due right away
+        settings.last_backup = "";
+        assert_true (statistics.backup_due (now));
+
+        // Days are counted on the calendar, so a backup late in the evening is due after midnight
+        settings.last_backup = "2023-03-07T23:00:00+01:00";
+        assert_true (statistics.backup_due (now));
+        assert_false (statistics.backup_due (new DateTime.local (2023, 3, 7, 23, 59, 0)));
+
+        settings.backup_schedule = WEEKLY;
+        assert_false (statistics.backup_due (new DateTime.local (2023, 3, 13, 23, 0, 0)));
+        assert_true (statistics.backup_due (new DateTime.local (2023, 3, 14, 0, 0, 0)));
+
+        settings.backup_schedule = NEVER;
+        assert_false (statistics.backup_due (now));
+
+        settings.backup_schedule = DAILY;
+        settings.backup_path = "";
+        assert_false (statistics.backup_due (now));
+    }
+
+    private bool backup_exists (string name) {
+        return FileUtils.test (data_path (Path.build_filename ("backups", name)), EXISTS);
+    }
+
+    private void test_prune_backups () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var statistics = new Services.Statistics ();
+        DirUtils.create_with_parents (data_path ("backups"), 0755);
+        string[] backups = {
+            "flowtime-backup-20230105-090000.json", "flowtime-backup-20230101-090000.json",
+            "flowtime-backup-20230104-090000.json", "flowtime-backup-20230102-180000.json"
+        };
+        foreach (unowned string name in backups) {
+            write_data (Path.build_filename ("backups", name), "{}");
+        }
+        write_data (Path.build_filename ("backups", "notes.txt"), "");
+        write_data (Path.build_filename ("backups", "flowtime-backup-old.csv"), "");
+
+        try {
+            statistics.prune_backups (data_path ("backups"), 2);
+        } catch (Services.StatisticsError e) {
+            error (e.message);
+        }
+
+        // The newest are kept, and files that are not backups are left alone
+        string[] kept = {
+            "flowtime-backup-20230104-090000.json", "flowtime-backup-20230105-090000.json",
+            "notes.txt", "flowtime-backup-old.csv"
+        };
+        foreach (unowned string name in backups) {
+            assert_true (backup_exists (name) == (name in kept));
+        }
+        foreach (unowned string name in kept) {
+            assert_true (backup_exists (name));
+        }
+    }
+
+    private void test_backup () {
+        if (!isolate ()) {
+            return;
+        }
+
+        write_statistics (day_xml (days_ago (1), 3600));
+        var statistics = new Services.Statistics ();
+        var settings = new Services.Settings ();
+        settings.backup_path = data_path ("backups");
+        settings.backup_count = 1;
+        settings.backup_schedule = DAILY;
+        settings.last_backup = "";
+        DirUtils.create_with_parents (data_path ("backups"), 0755);
+        write_data (Path.build_filename ("backups", "flowtime-backup-20230101-090000.json"), "{}");
+
+        try {
+            statistics.backup ();
+        } catch (Services.StatisticsError e) {
+            error (e.message);
+        }
+
+        assert_false (statistics.backup_due (new DateTime.now_local ()));
+        assert_false (backup_exists ("flowtime-backup-20230101-090000.json"));
+
+        uint count = 0;
+        try {
+            Dir dir = Dir.open (data_path ("backups"));
+            for (string? name = dir.read_name (); name != null; name = dir.read_name ()) {
+                assert_true (name.has_prefix ("flowtime-backup-") && name.has_suffix (".json"));
+                count++;
+            }
+        } catch (FileError e) {
+            error (e.message);
+        }
+        assert_cmpuint (count, EQ, 1);
+    }
+
+    private void test_compliant_day_rate () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.break_policy = PERCENTAGE;
+        settings.break_percentage = 25;
+        settings.break_rounding = NONE;
+        settings.min_break_seconds = 60;
+        settings.max_break_seconds = 3600;
+
+        // Every day earns a break of 900 seconds, the day with only a break is left out
+        write_statistics (day_xml (days_ago (5), 3600, 900) + day_xml (days_ago (4), 3600, 720) +
+                          day_xml (days_ago (3), 3600, 450) + day_xml (days_ago (2), 3600) +
+                          day_xml (days_ago (1), 0, 600));
+
+        var statistics = new Services.Statistics ();
+        assert_true (statistics.compliant_day_rate (0) == 1);
+        assert_true (statistics.compliant_day_rate (0.5) == 0.75);
+        assert_true (statistics.compliant_day_rate (0.8) == 0.5);
+        assert_true (statistics.compliant_day_rate (1) == 0.25);
+        assert_true (statistics.compliant_day_rate (1.01) == 0);
+    }
+
+    private void test_compliant_day_rate_without_work () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var statistics = new Services.Statistics ();
+        assert_true (statistics.compliant_day_rate (0.8) == 0);
+    }
+
+    private void test_data_last_modified () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var before = new DateTime.now_local ().add_seconds (-1);
+        var statistics = new Services.Statistics ();
+        statistics.add_time_to_mode (WORK, 60);
+
+        DateTime? modified = statistics.data_last_modified ();
+        assert_nonnull (modified);
+        assert_true (modified.compare (before) >= 0);
+        assert_true (modified.compare (new DateTime.now_local ().add_seconds (1)) <= 0);
+
+        // The time is read again from the file every time
+        var changed = new DateTime.local (2023, 3, 1, 10, 0, 0);
+        try {
+            var info = new FileInfo ();
+            info.set_modification_date_time (changed);
+            File.new_for_path (data_path ("statistics.xml")).set_attributes_from_info (info, NONE);
+        } catch (Error e) {
+            error (e.message);
+        }
+        assert_true (statistics.data_last_modified ().equal (changed));
+
+        FileUtils.remove (data_path ("statistics.xml"));
+        assert_null (statistics.data_last_modified ());
+    }
+
+    public static int main (string[] args) {
+        Test.init (ref args);
+
+        Test.add_func ("/statistics/pausetime/saved", test_pausetime_saved);
+        Test.add_func ("/statistics/pausetime/missing", test_pausetime_missing);
+        Test.add_func ("/statistics/pausetime/not-break", test_pausetime_not_break);
+        Test.add_func ("/statistics/pausetime/from-timer", test_pausetime_from_timer);
+        Test.add_func ("/statistics/goal/hits", test_goal_hits);
+        Test.add_func ("/statistics/goal/empty", test_goal_hits_empty);
+        Test.add_func ("/statistics/sessions/saved", test_sessions_saved);
+        Test.add_func ("/statistics/sessions/longest", test_longest_session);
+        Test.add_func ("/statistics/sessions/longest-none", test_longest_session_none);
+        Test.add_func ("/statistics/dense-days", test_dense_days);
+        Test.add_func ("/statistics/dense-days/dst", test_dense_days_dst);
+        Test.add_func ("/statistics/snapshot-variant", test_snapshot_variant);
+        Test.add_func ("/statistics/today-notify", test_today_notify);
+        Test.add_func ("/statistics/whitespace-values", test_whitespace_values);
+        Test.add_func ("/statistics/worktime-range", test_worktime_range);
+        Test.add_func ("/statistics/worktime-range/empty", test_worktime_range_empty);
+        Test.add_func ("/statistics/data-health", test_data_health);
+        Test.add_func ("/statistics/active-dates", test_active_dates);
+        Test.add_func ("/statistics/archive/split", test_archive_split);
+        Test.add_func ("/statistics/archive/aggregation", test_archive_aggregation);
+        Test.add_func ("/statistics/parse-error", test_parse_error_reported);
+        Test.add_func ("/statistics/week-forecast", test_week_forecast);
+        Test.add_func ("/statistics/week-forecast/last-day", test_week_forecast_last_day);
+        Test.add_func ("/statistics/week-forecast/first-day", test_week_forecast_first_day);
+        Test.add_func ("/statistics/day-split", test_day_split);
+        Test.add_func ("/statistics/compare-weeks", test_compare_weeks);
+        Test.add_func ("/statistics/sessions/model", test_sessions_model);
+        Test.add_func ("/statistics/newer-version", test_newer_version);
+        Test.add_func ("/statistics/breaks/saved", test_breaks_saved);
+        Test.add_func ("/statistics/breaks/average-length", test_average_break_length);
+        Test.add_func ("/statistics/breaks/average-length-none", test_average_break_length_none);
+        Test.add_func ("/statistics/recompute", test_recompute);
+        Test.add_func ("/statistics/streak/skip-weekend", test_streak_skips_weekend);
+        Test.add_func ("/statistics/streak/skip-configured-days", test_streak_skips_configured_days);
+        Test.add_func ("/statistics/goal-hit-rate/skip-weekend", test_goal_hit_rate_skips_weekend);
+        Test.add_func ("/statistics/import-directory", test_import_directory);
+        Test.add_func ("/statistics/longest-focus-run", test_longest_focus_run);
+        Test.add_func ("/statistics/export-markdown", test_export_markdown);
+        Test.add_func ("/statistics/updates-throttled", test_updates_throttled);
+        Test.add_func ("/statistics/weekly-series/gaps", test_weekly_series_gaps);
+        Test.add_func ("/statistics/weekly-series/new-year", test_weekly_series_new_year);
+        Test.add_func ("/statistics/rest-day/saved", test_rest_day_saved);
+        Test.add_func ("/statistics/rest-day/streak", test_rest_day_streak);
+        Test.add_func ("/statistics/most-improved-weekday", test_most_improved_weekday);
+        Test.add_func ("/statistics/most-improved-weekday/short", test_most_improved_short_history);
+        Test.add_func ("/statistics/locked-read-only", test_locked_read_only);
+        Test.add_func ("/statistics/productive-day/average-worktime", test_productive_day_average);
+        Test.add_func ("/statistics/productive-day/frequency", test_productive_day_frequency);
+        Test.add_func ("/statistics/productive-day/peak-day", test_productive_day_peak);
+        Test.add_func ("/statistics/productive-day/changed", test_productive_day_changed);
+        Test.add_func ("/statistics/time-to-milestone", test_time_to_milestone);
+        Test.add_func ("/statistics/time-to-milestone/no-recent-work", test_time_to_milestone_no_recent_work);
+        Test.add_func ("/statistics/saved-signal", test_saved_signal);
+        Test.add_func ("/statistics/dump-xml", test_dump_xml);
+        Test.add_func ("/statistics/freeze/keeps-streak", test_freeze_keeps_streak);
+        Test.add_func ("/statistics/freeze/run-out", test_freezes_run_out);
+        Test.add_func ("/statistics/freeze/refilled-on-day-change", test_freezes_refilled_on_day_change);
+        Test.add_func ("/statistics/restore-json/requires-force", test_restore_requires_force);
+        Test.add_func ("/statistics/restore-json/empty-history", test_restore_empty_history);
+        Test.add_func ("/statistics/top-days", test_top_days);
+        Test.add_func ("/statistics/top-days/short-history", test_top_days_short_history);
+        Test.add_func ("/statistics/worktime-stddev", test_worktime_stddev);
+        Test.add_func ("/statistics/worktime-stddev/single-day", test_worktime_stddev_single_day);
+        Test.add_func ("/statistics/merge-conflict", test_merge_conflict);
+        Test.add_func ("/statistics/milestone/once", test_milestone_once);
+        Test.add_func ("/statistics/milestone/not-fired-again", test_milestone_not_fired_again);
+        Test.add_func ("/statistics/normalize-file", test_normalize_file);
+        Test.add_func ("/statistics/export-histogram", test_export_histogram);
+        Test.add_func ("/statistics/export-histogram/no-bucket", test_export_histogram_no_bucket);
+        Test.add_func ("/statistics/out-of-range/clamped", test_out_of_range_clamped);
+        Test.add_func ("/statistics/out-of-range/skipped", test_out_of_range_skipped);
+        Test.add_func ("/statistics/streak-info", test_streak_info);
+        Test.add_func ("/statistics/empty-days/dropped", test_empty_days_dropped);
+        Test.add_func ("/statistics/empty-days/kept", test_empty_days_kept);
+        Test.add_func ("/statistics/cumulative-series", test_cumulative_series);
+        Test.add_func ("/statistics/deep-work-only", test_deep_work_only);
+        Test.add_func ("/statistics/export-day-json", test_export_day_json);
+        Test.add_func ("/statistics/export-day-json/absent", test_export_day_json_absent);
+        Test.add_func ("/statistics/blank-file/zero-bytes", test_zero_byte_file);
+        Test.add_func ("/statistics/blank-file/whitespace", test_whitespace_file);
+        Test.add_func ("/statistics/break-adherence-series", test_break_adherence_series);
+        Test.add_func ("/statistics/auto-export", test_auto_export);
+        Test.add_func ("/statistics/auto-export/unwritable", test_auto_export_unwritable);
+        Test.add_func ("/statistics/daypart-split", test_daypart_split);
+        Test.add_func ("/statistics/activity-feed", test_activity_feed);
+        Test.add_func ("/statistics/normalize-dates", test_normalize_dates);
+        Test.add_func ("/statistics/punchcard", test_punchcard);
+        Test.add_func ("/statistics/break-ratio", test_break_ratio);
+        Test.add_func ("/statistics/break-ratio/deep-work", test_break_ratio_deep_work);
+        Test.add_func ("/statistics/break-ratio/without-work", test_break_ratio_without_work);
+        Test.add_func ("/statistics/import-gnome-pomodoro", test_import_gnome_pomodoro);
+        Test.add_func ("/statistics/import-gnome-pomodoro/invalid", test_import_gnome_pomodoro_invalid);
+        Test.add_func ("/statistics/week-progress", test_week_progress);
+        Test.add_func ("/statistics/offhours", test_offhours_worktime);
+        Test.add_func ("/statistics/offhours/overnight", test_offhours_overnight);
+        Test.add_func ("/statistics/offhours/without-sessions", test_offhours_without_sessions);
+        Test.add_func ("/statistics/gap-days", test_gap_days);
+        Test.add_func ("/statistics/typical-start-time", test_typical_start_time);
+        Test.add_func ("/statistics/typical-start-time/even", test_typical_start_time_even);
+        Test.add_func ("/statistics/typical-start-time/insufficient", test_typical_start_time_insufficient);
+        Test.add_func ("/statistics/dashboard-snapshot", test_dashboard_snapshot);
+        Test.add_func ("/statistics/metric-by-id", test_metric_by_id);
+        Test.add_func ("/statistics/break-skip-rate", test_break_skip_rate);
+        Test.add_func ("/statistics/break-skip-rate/always-skipped", test_break_skip_rate_always_skipped);
+        Test.add_func ("/statistics/break-skip-rate/without-sessions", test_break_skip_rate_without_sessions);
+        Test.add_func ("/statistics/sample-mode", test_sample_mode);
+        Test.add_func ("/statistics/total-tracked-hours", test_total_tracked_hours);
+        Test.add_func ("/statistics/energy/saved", test_set_energy);
+        Test.add_func ("/statistics/energy/worktime", test_worktime_by_energy);
+        Test.add_func ("/statistics/backup/due", test_backup_due);
+        Test.add_func ("/statistics/backup/prune", test_prune_backups);
+        Test.add_func ("/statistics/backup", test_backup);
+        Test.add_func ("/statistics/compliant-day-rate", test_compliant_day_rate);
+        Test.add_func ("/statistics/compliant-day-rate/without-work", test_compliant_day_rate_without_work);
+        Test.add_func ("/statistics/data-last-modified", test_data_last_modified);
+
+        return Test.run ();
+    }
+}
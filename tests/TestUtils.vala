@@ -0,0 +1,109 @@
+/* TestUtils.vala
+ *
+ * Copyright 2023 Diego Iván <diegoivan.mae@gmail.com>
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+namespace Flowtime.Tests {
+    /*
+     * The statistics and the settings are single instances, so a test that uses them is run again
+     * in a process of its own, with a data directory of its own. True is returned in that process,
+     * where the test must be run, and false once the process has passed.
+     */
+    public bool isolate () {
+        if (!Test.subprocess ()) {
+            Test.trap_subprocess (null, 0, INHERIT_STDERR);
+            Test.trap_assert_passed ();
+            return false;
+        }
+
+        try {
+            Environment.set_variable ("XDG_DATA_HOME", DirUtils.make_tmp ("flowtime-test-XXXXXX"), true);
+        } catch (FileError e) {
+            error ("The data directory cannot be created: %s", e.message);
+        }
+
+        // Damaged statistics are warned about on purpose, only criticals make a test fail
+        Log.set_always_fatal (LogLevelFlags.LEVEL_ERROR | LogLevelFlags.LEVEL_CRITICAL);
+        return true;
+    }
+
+    public string data_path (string filename) {
+        return Path.build_filename (Environment.get_user_data_dir (), filename);
+    }
+
+    // Midnight of the date some days before today
+    public DateTime days_ago (int days) {
+        return TimeUtils.normalize_to_local_midnight (new DateTime.now_local ().add_days (-days));
+    }
+
+    // A day as it is saved in the statistics file
+    public string day_xml (DateTime date, int worktime, int breaktime = 0, string children = "",
+                           string attributes = "") {
+        return "<day date=\"%s\"%s><worktime>%d</worktime><breaktime>%d</breaktime>%s</day>".printf (
+            date.format_iso8601 (), attributes, worktime, breaktime, children
+        );
+    }
+
+    // A session as it is saved in a day, started at start
+    public string session_xml (DateTime start, int duration, int break_taken = 0) {
+        return "<session start=\"%s\" dur=\"%d\" break=\"%d\"/>".printf (
+            start.format_iso8601 (), duration, break_taken
+        );
+    }
+
+    // Writes a statistics file with the days, to be read by the statistics once they are created
+    public void write_statistics (string days, string attributes = "") {
+        string document = "<?xml version=\"1.0\"?>\n<statistics version=\"1\"%s>%s</statistics>\n";
+        write_data ("statistics.xml", document.printf (attributes, days));
+    }
+
+    public void write_data (string filename, string contents) {
+        try {
+            DirUtils.create_with_parents (Environment.get_user_data_dir (), 0755);
+            FileUtils.set_contents (data_path (filename), contents);
+        } catch (FileError e) {
+            error ("%s cannot be written: %s", filename, e.message);
+        }
+    }
+
+    // Makes the statistics look like they are used by another instance of Flowtime
+    public void lock_statistics () {
+        write_data ("statistics.lock", "%s:1".printf (Environment.get_host_name ()));
+    }
+
+    public string read_data (string filename) {
+        try {
+            string contents;
+            FileUtils.get_contents (data_path (filename), out contents);
+            return contents;
+        } catch (FileError e) {
+            error ("%s cannot be read: %s", filename, e.message);
+        }
+    }
+
+    /*
+     * The day of a date read again from the statistics file, to check what was saved. The document
+     * is never freed, as the day keeps pointing into it.
+     */
+    public Models.Day? saved_day (DateTime date, string filename = "statistics.xml") {
+        Xml.Doc* doc = Xml.Parser.parse_file (data_path (filename));
+        assert_nonnull (doc);
+        return find_saved_day (doc, date);
+    }
+
+    public Models.Day? find_saved_day (Xml.Doc* doc, DateTime date) {
+        for (Xml.Node* i = doc->get_root_element ()->children; i != null; i = i->next) {
+            if (i->type != ELEMENT_NODE || i->name != "day") {
+                continue;
+            }
+
+            var day = new Models.Day.from_xml (i);
+            if (TimeUtils.same_day (day.date, date)) {
+                return day;
+            }
+        }
+        return null;
+    }
+}
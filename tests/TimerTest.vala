@@ -0,0 +1,991 @@
+/* TimerTest.vala
+ *
+ * Copyright 2023 Diego Iván <diegoivan.mae@gmail.com>
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+namespace Flowtime.Tests {
+    private class TickingTimer : Services.Timer {
+        public bool tick () {
+            return timeout ();
+        }
+    }
+
+    // A timer that only changes stage when asked to, with breaks of a quarter of the work
+    private TickingTimer manual_timer () {
+        var settings = new Services.Settings ();
+        settings.autostart = false;
+        settings.auto_start_break = false;
+        settings.session_grace = 0;
+        settings.break_policy = PERCENTAGE;
+        settings.break_percentage = 25;
+        settings.break_rounding = NONE;
+        settings.min_break_seconds = 60;
+        settings.max_break_seconds = 3600;
+
+        return new TickingTimer ();
+    }
+
+    private void test_break_for () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.break_percentage = 25;
+
+        Services.BreakPolicy flowtime = FLOWTIME;
+        assert_cmpint (flowtime.break_for (0), EQ, 0);
+        assert_cmpint (flowtime.break_for (25 * 60), EQ, 5 * 60);
+        assert_cmpint (flowtime.break_for (25 * 60 + 1), EQ, 8 * 60);
+        assert_cmpint (flowtime.break_for (90 * 60), EQ, 10 * 60);
+        assert_cmpint (flowtime.break_for (3 * 3600), EQ, 15 * 60);
+        assert_cmpint (flowtime.earned_after (), EQ, 25 * 60);
+
+        Services.BreakPolicy percentage = PERCENTAGE;
+        assert_cmpint (percentage.break_for (0), EQ, 0);
+        assert_cmpint (percentage.break_for (3600), EQ, 900);
+        assert_cmpint (percentage.earned_after (), EQ, 240);
+    }
+
+    private void test_time_until_break () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.break_policy = PERCENTAGE;
+        settings.break_percentage = 25;
+
+        var timer = new Services.Timer ();
+        assert_cmpint (timer.time_until_break (), EQ, 240);
+        timer.seconds = 100;
+        assert_cmpint (timer.time_until_break (), EQ, 140);
+        timer.seconds = 240;
+        assert_cmpint (timer.time_until_break (), EQ, 0);
+        timer.seconds = 1000;
+        assert_cmpint (timer.time_until_break (), EQ, 0);
+
+        settings.break_policy = FLOWTIME;
+        timer.seconds = 0;
+        assert_cmpint (timer.time_until_break (), EQ, 25 * 60);
+        timer.seconds = 10 * 60;
+        assert_cmpint (timer.time_until_break (), EQ, 15 * 60);
+        timer.seconds = 25 * 60;
+        assert_cmpint (timer.time_until_break (), EQ, 0);
+    }
+
+    // No break is owed while the break itself is running
+    private void test_time_until_break_in_break () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var timer = new Services.Timer ();
+        timer.seconds = 60;
+        timer.next_mode ();
+        assert_true (timer.mode == BREAK);
+        assert_cmpint (timer.time_until_break (), EQ, 0);
+    }
+
+    private void test_banked_break () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        var timer = manual_timer ();
+        settings.bank_breaks = true;
+
+        timer.seconds = 3600;
+        timer.next_mode ();
+        assert_cmpint (timer.seconds, EQ, 900);
+
+        // The break is skipped with 5 minutes left
+        timer.seconds = 300;
+        timer.next_mode ();
+        assert_cmpint (timer.banked_break (), EQ, 300);
+
+        // Pausing the work stage keeps the bank
+        timer.start ();
+        timer.stop ();
+        assert_cmpint (timer.banked_break (), EQ, 300);
+
+        timer.seconds = 3600;
+        assert_cmpint (timer.earned_break_preview (), EQ, 1200);
+        timer.next_mode ();
+        assert_cmpint (timer.seconds, EQ, 1200);
+        assert_cmpint (timer.banked_break (), EQ, 0);
+    }
+
+    private void test_banked_break_disabled () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        var timer = manual_timer ();
+        settings.bank_breaks = false;
+
+        timer.seconds = 3600;
+        timer.next_mode ();
+        timer.seconds = 300;
+        timer.next_mode ();
+        assert_cmpint (timer.banked_break (), EQ, 0);
+
+        timer.seconds = 3600;
+        timer.next_mode ();
+        assert_cmpint (timer.seconds, EQ, 900);
+    }
+
+    private void test_break_rounding () {
+        Services.BreakRounding none = NONE;
+        Services.BreakRounding minute = MINUTE;
+        Services.BreakRounding five_minutes = FIVE_MINUTES;
+
+        assert_cmpint (none.round (443), EQ, 443);
+        assert_cmpint (minute.round (0), EQ, 0);
+        assert_cmpint (minute.round (60), EQ, 60);
+        assert_cmpint (minute.round (61), EQ, 120);
+        assert_cmpint (five_minutes.round (300), EQ, 300);
+        assert_cmpint (five_minutes.round (301), EQ, 600);
+    }
+
+    private void test_suggested_break_rounded () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        var timer = manual_timer ();
+
+        // A quarter of 1771 seconds is 442 seconds, 7m 22s
+        assert_cmpint (Services.Timer.suggested_break (1771), EQ, 442);
+        settings.break_rounding = MINUTE;
+        assert_cmpint (Services.Timer.suggested_break (1771), EQ, 480);
+        settings.break_rounding = FIVE_MINUTES;
+        assert_cmpint (Services.Timer.suggested_break (1771), EQ, 600);
+
+        // Only the suggestion is rounded, the break taken is saved as it was
+        var statistics = new Services.Statistics ();
+        timer.seconds = 1771;
+        timer.next_mode ();
+        assert_cmpint (timer.seconds, EQ, 600);
+        timer.seconds = 163;
+        timer.next_mode ();
+        assert_cmpint (statistics.today.breaktime, EQ, 437);
+    }
+
+    private void test_nudge () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        var timer = manual_timer ();
+        settings.send_notifications = true;
+
+        bool done = false;
+        timer.done.connect (() => done = true);
+
+        timer.seconds = 3600;
+        timer.next_mode ();
+        timer.start ();
+        assert_false (Services.Alarm.should_nudge (timer));
+
+        // The last second of the break goes by
+        timer.seconds = 1;
+        Thread.usleep (1100000);
+        assert_false (timer.tick ());
+        assert_true (done);
+        assert_true (Services.Alarm.should_nudge (timer));
+
+        settings.send_notifications = false;
+        assert_false (Services.Alarm.should_nudge (timer));
+        settings.send_notifications = true;
+
+        // Work was resumed before the reminder was due
+        timer.next_mode ();
+        assert_false (Services.Alarm.should_nudge (timer));
+        timer.start ();
+        assert_false (Services.Alarm.should_nudge (timer));
+    }
+
+    // Bounds that cannot be met are replaced by the defaults of a minute and an hour
+    private void test_break_bounds_fallback () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.break_policy = PERCENTAGE;
+        settings.break_percentage = 25;
+        settings.break_rounding = NONE;
+        settings.min_break_seconds = 1800;
+        settings.max_break_seconds = 120;
+
+        assert_cmpint (Services.Timer.suggested_break (240), EQ, 60);
+        assert_cmpint (Services.Timer.suggested_break (3600), EQ, 900);
+        assert_cmpint (Services.Timer.suggested_break (10 * 3600), EQ, 3600);
+    }
+
+    private void test_play_cue () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.play_sounds = true;
+        assert_true (Services.Alarm.should_play_cue (true));
+        assert_false (Services.Alarm.should_play_cue (false));
+
+        settings.play_sounds = false;
+        assert_false (Services.Alarm.should_play_cue (true));
+        assert_false (Services.Alarm.should_play_cue (false));
+    }
+
+    private void test_auto_start_break () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        var timer = manual_timer ();
+        settings.auto_start_break = true;
+
+        bool started = false;
+        bool waiting = false;
+        timer.break_started.connect (() => started = true);
+        timer.break_waiting.connect (() => waiting = true);
+
+        timer.seconds = 3600;
+        timer.next_mode ();
+        assert_true (timer.mode == BREAK);
+        assert_true (timer.running);
+        assert_true (started);
+        assert_false (waiting);
+    }
+
+    private void test_manual_break () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var timer = manual_timer ();
+
+        bool started = false;
+        bool waiting = false;
+        timer.break_started.connect (() => started = true);
+        timer.break_waiting.connect (() => waiting = true);
+
+        timer.seconds = 3600;
+        timer.next_mode ();
+        assert_true (timer.mode == BREAK);
+        assert_false (timer.running);
+        assert_true (timer.break_pending ());
+        assert_true (waiting);
+        assert_false (started);
+    }
+
+    // The work done until midnight is saved as a session that ends at midnight
+    private void test_split_at_midnight () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        var statistics = new Services.Statistics ();
+        var timer = manual_timer ();
+        settings.split_at_midnight = true;
+
+        var started = new DateTime.now_utc ();
+        timer.start ();
+
+        DateTime midnight = TimeUtils.normalize_to_local_midnight (
+            TimeUtils.normalize_to_local_midnight (new DateTime.now_local ()).add_hours (36)
+        );
+        timer.split_at_midnight (midnight.add_hours (1));
+
+        unowned List<Models.Session> last = statistics.today.sessions.last ();
+        assert_nonnull (last);
+        Models.Session session = last.data;
+        assert_true (session.start.add_seconds (session.duration).equal (midnight));
+        assert_cmpint ((int) (session.start.difference (started) / TimeSpan.SECOND), LE, 1);
+        assert_cmpint (statistics.today.worktime, EQ, session.duration);
+
+        timer.stop ();
+    }
+
+    private void test_start_on_launch () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.autostart_on_launch = true;
+        settings.startup_phase = WORK;
+
+        var timer = new Services.Timer ();
+        timer.restore_startup_phase ();
+        timer.start_on_launch ();
+        assert_true (timer.running);
+        assert_true (timer.phase == WORK);
+        timer.stop ();
+    }
+
+    private void test_start_on_launch_disabled () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.autostart_on_launch = false;
+        settings.startup_phase = WORK;
+
+        var timer = new Services.Timer ();
+        timer.start_on_launch ();
+        assert_false (timer.running);
+
+        // Opening idle never starts the timer
+        settings.autostart_on_launch = true;
+        settings.startup_phase = IDLE;
+        timer.start_on_launch ();
+        assert_false (timer.running);
+
+        // Neither does opening in the break stage
+        settings.startup_phase = BREAK;
+        timer.restore_startup_phase ();
+        timer.start_on_launch ();
+        assert_false (timer.running);
+    }
+
+    // The monitor is made without connecting to the system bus, the signals are sent by the test
+    private Services.SleepMonitor unconnected_monitor (Services.Timer timer) {
+        return (Services.SleepMonitor) Object.new (typeof (Services.SleepMonitor), "timer", timer);
+    }
+
+    private void test_sleep_not_counted () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var statistics = new Services.Statistics ();
+        var timer = manual_timer ();
+        var monitor = unconnected_monitor (timer);
+
+        timer.start ();
+        timer.seconds = 100;
+
+        monitor.on_sleep ();
+        assert_false (timer.running);
+        Thread.usleep (2100000);
+        monitor.on_wake ();
+        assert_true (timer.running);
+
+        assert_true (timer.tick ());
+        assert_cmpint (timer.seconds, EQ, 100);
+        // The time asleep is not paused time either
+        assert_cmpint (statistics.today.pausetime, EQ, 0);
+        timer.stop ();
+    }
+
+    // A timer that was not running before the suspend is not started on wake
+    private void test_sleep_while_stopped () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var timer = manual_timer ();
+        var monitor = unconnected_monitor (timer);
+
+        monitor.on_sleep ();
+        monitor.on_wake ();
+        assert_false (timer.running);
+    }
+
+    private void test_earned_break_preview () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        var timer = manual_timer ();
+
+        assert_cmpint (timer.earned_break_preview (), EQ, 0);
+        timer.seconds = 239;
+        assert_cmpint (timer.earned_break_preview (), EQ, 0);
+        timer.seconds = 1200;
+        assert_cmpint (timer.earned_break_preview (), EQ, 300);
+        timer.seconds = 3600;
+        assert_cmpint (timer.earned_break_preview (), EQ, 900);
+        timer.seconds = 20 * 3600;
+        assert_cmpint (timer.earned_break_preview (), EQ, 3600);
+
+        settings.break_policy = FLOWTIME;
+        timer.seconds = 1200;
+        assert_cmpint (timer.earned_break_preview (), EQ, 0);
+        timer.seconds = 1500;
+        assert_cmpint (timer.earned_break_preview (), EQ, 300);
+        timer.seconds = 3600;
+        assert_cmpint (timer.earned_break_preview (), EQ, 600);
+
+        // The preview is the break the stage is changed to
+        timer.next_mode ();
+        assert_cmpint (timer.seconds, EQ, 600);
+        assert_cmpint (timer.earned_break_preview (), EQ, 0);
+    }
+
+    private void test_phase () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var timer = manual_timer ();
+        int notified = 0;
+        timer.notify["phase"].connect (() => notified++);
+        assert_true (timer.phase == IDLE);
+
+        timer.start ();
+        assert_true (timer.running);
+        assert_true (timer.phase == WORK);
+        assert_cmpint (notified, GE, 1);
+
+        notified = 0;
+        timer.stop ();
+        assert_false (timer.running);
+        assert_true (timer.phase == IDLE);
+        assert_cmpint (notified, GE, 1);
+
+        timer.seconds = 3600;
+        timer.next_mode ();
+        assert_true (timer.phase == IDLE);
+
+        notified = 0;
+        timer.start ();
+        assert_true (timer.phase == BREAK);
+        assert_cmpint (notified, GE, 1);
+        timer.stop ();
+    }
+
+    private void test_coarse_ticks () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        var timer = manual_timer ();
+        settings.tick_interval = 10;
+
+        // Ticks less than a second apart count the time between them once it adds up to seconds
+        timer.start ();
+        for (int i = 0; i < 5; i++) {
+            Thread.usleep (700000);
+            timer.tick ();
+        }
+        assert_cmpint (timer.seconds, EQ, 3);
+
+        // A single late tick counts all the time since the last one
+        Thread.usleep (2600000);
+        timer.tick ();
+        assert_cmpint (timer.seconds, EQ, 6);
+        timer.stop ();
+    }
+
+    private uint today_sessions () {
+        var statistics = new Services.Statistics ();
+        return statistics.find_day (new DateTime.now_local ()).sessions.length ();
+    }
+
+    private void test_resume_within_grace () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        var timer = manual_timer ();
+        settings.session_grace = 30;
+
+        timer.seconds = 3600;
+        timer.next_mode ();
+        assert_true (timer.mode == BREAK);
+
+        // Going back to work right away continues the session, without a break
+        timer.next_mode ();
+        assert_true (timer.mode == WORK);
+        assert_cmpint (timer.seconds, EQ, 3600);
+
+        timer.seconds += 600;
+        timer.next_mode ();
+
+        var statistics = new Services.Statistics ();
+        Models.Day today = statistics.find_day (new DateTime.now_local ());
+        assert_cmpuint (today_sessions (), EQ, 1);
+        assert_cmpint (today.sessions.first ().data.duration, EQ, 4200);
+        assert_cmpint (today.worktime, EQ, 4200);
+        assert_cmpint (today.breaks, EQ, 0);
+    }
+
+    private void test_resume_after_grace () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        var timer = manual_timer ();
+        settings.session_grace = 1;
+
+        timer.seconds = 3600;
+        timer.next_mode ();
+        Thread.usleep (1100000);
+
+        timer.next_mode ();
+        assert_true (timer.mode == WORK);
+        assert_cmpint (timer.seconds, EQ, 0);
+
+        timer.seconds = 600;
+        timer.next_mode ();
+        assert_cmpuint (today_sessions (), EQ, 2);
+    }
+
+    private void test_worktime_this_hour () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var timer = manual_timer ();
+        assert_cmpint (timer.worktime_this_hour (), EQ, 0);
+
+        var before_hour = new DateTime.local (2023, 3, 1, 10, 59, 50);
+        timer.add_hour_worktime (before_hour, 30);
+        assert_cmpint (timer.worktime_in_hour (before_hour), EQ, 30);
+
+        // Only the seconds of the tick after the top of the hour are counted in the new hour
+        var after_hour = new DateTime.local (2023, 3, 1, 11, 0, 20);
+        timer.add_hour_worktime (after_hour, 30);
+        assert_cmpint (timer.worktime_in_hour (after_hour), EQ, 20);
+        assert_cmpint (timer.worktime_in_hour (before_hour), EQ, 0);
+
+        timer.add_hour_worktime (after_hour.add_minutes (30), 60);
+        assert_cmpint (timer.worktime_in_hour (after_hour.add_minutes (59)), EQ, 80);
+
+        // Nothing was counted in the next hour yet
+        assert_cmpint (timer.worktime_in_hour (new DateTime.local (2023, 3, 1, 12, 0, 1)), EQ, 0);
+    }
+
+    private void test_next_summary () {
+        const int NINE_PM = 21 * 60;
+
+        var morning = new DateTime.local (2023, 3, 1, 10, 0, 0);
+        DateTime next = Services.Alarm.next_summary (morning, NINE_PM);
+        assert_true (next.equal (new DateTime.local (2023, 3, 1, 21, 0, 0)));
+
+        // Once the summary was sent, the next one is on the next day
+        next = Services.Alarm.next_summary (next, NINE_PM);
+        assert_true (next.equal (new DateTime.local (2023, 3, 2, 21, 0, 0)));
+
+        // The day daylight saving time starts is an hour shorter, but the clock still shows nine
+        var spring_night = new DateTime.local (2023, 3, 25, 22, 0, 0);
+        next = Services.Alarm.next_summary (spring_night, NINE_PM);
+        assert_true (next.equal (new DateTime.local (2023, 3, 26, 21, 0, 0)));
+    }
+
+    private void test_summary_content () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.daily_goal = 60;
+        settings.send_notifications = true;
+        settings.summary_on_idle_days = false;
+
+        var statistics = new Services.Statistics ();
+        var day = new Models.Day.unsaved (days_ago (1));
+        day.worktime = 3600;
+        day.breaktime = day.earned_break () / 2;
+
+        string summary = statistics.summary_text (day);
+        assert_true (summary.has_prefix ("You worked %s".printf (day.worktime_display)));
+        assert_true (summary.contains ("took 50% of your earned breaks"));
+        assert_true (summary.has_suffix ("Daily goal met."));
+
+        day.worktime = 1800;
+        assert_true (statistics.summary_text (day).has_suffix ("Daily goal not met."));
+        assert_true (Services.Alarm.should_send_summary (day));
+
+        // Days without time are skipped unless asked for, and nothing is sent without notifications
+        var idle = new Models.Day.unsaved (days_ago (1));
+        assert_false (Services.Alarm.should_send_summary (idle));
+        settings.summary_on_idle_days = true;
+        assert_true (Services.Alarm.should_send_summary (idle));
+        settings.send_notifications = false;
+        assert_false (Services.Alarm.should_send_summary (day));
+    }
+
+    private void test_startup_last_used () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.startup_phase = LAST_USED;
+
+        // The stage the timer is in is kept for the next launch
+        var used_timer = manual_timer ();
+        used_timer.seconds = 3600;
+        used_timer.next_mode ();
+        assert_true (settings.last_mode == BREAK);
+
+        var timer = manual_timer ();
+        timer.restore_startup_phase ();
+        assert_true (timer.mode == BREAK);
+        assert_cmpint (timer.seconds, EQ, settings.min_break_seconds);
+        assert_false (timer.running);
+
+        settings.last_mode = WORK;
+        var work_timer = manual_timer ();
+        work_timer.restore_startup_phase ();
+        assert_true (work_timer.mode == WORK);
+    }
+
+    private void test_startup_phase_mode () {
+        Services.StartupPhase work = WORK;
+        Services.StartupPhase break_phase = BREAK;
+        Services.StartupPhase idle = IDLE;
+
+        assert_true (work.mode (BREAK) == WORK);
+        assert_true (break_phase.mode (WORK) == BREAK);
+        assert_true (idle.mode (BREAK) == WORK);
+    }
+
+    private void test_remaining_break () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var timer = manual_timer ();
+        timer.seconds = 3600;
+        assert_cmpint (timer.remaining_break (), EQ, 0);
+
+        timer.next_mode ();
+        assert_cmpint (timer.remaining_break (), EQ, 900);
+
+        // The time since the last tick is counted while the break runs
+        timer.start ();
+        Thread.usleep (1200000);
+        assert_cmpint (timer.remaining_break (), EQ, 899);
+        timer.tick ();
+        assert_cmpint (timer.remaining_break (), EQ, 899);
+        timer.stop ();
+    }
+
+    private void test_remaining_break_completed () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var timer = manual_timer ();
+        timer.seconds = 3600;
+        timer.next_mode ();
+
+        bool done = false;
+        timer.done.connect (() => done = true);
+
+        // The break is over before the timer ticks again, and what is left never goes below zero
+        timer.start ();
+        timer.seconds = 1;
+        Thread.usleep (2100000);
+        assert_cmpint (timer.remaining_break (), EQ, 0);
+        timer.tick ();
+        assert_true (done);
+        assert_cmpint (timer.remaining_break (), EQ, 0);
+    }
+
+    private void test_remaining_break_banked () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        var timer = manual_timer ();
+        settings.bank_breaks = true;
+
+        timer.seconds = 3600;
+        timer.next_mode ();
+        // The whole break is skipped, so it is added to the next one
+        timer.next_mode ();
+
+        timer.seconds = 3600;
+        timer.next_mode ();
+        assert_cmpint (timer.remaining_break (), EQ, 1800);
+    }
+
+    private void test_soft_cap () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        var timer = manual_timer ();
+        settings.soft_cap = 1;
+
+        int reached = 0;
+        timer.soft_cap_reached.connect (() => reached++);
+
+        timer.start ();
+        timer.seconds = 58;
+        Thread.usleep (1100000);
+        timer.tick ();
+        assert_false (timer.over_soft_cap);
+
+        // The warning is given once the cap is crossed, while the timer keeps counting
+        Thread.usleep (1000000);
+        timer.tick ();
+        assert_true (timer.over_soft_cap);
+        assert_true (timer.running);
+        assert_cmpint (reached, EQ, 1);
+
+        Thread.usleep (1000000);
+        timer.tick ();
+        assert_cmpint (reached, EQ, 1);
+
+        // Taking a break resets it, so the next work stage is warned about again
+        timer.next_mode ();
+        assert_false (timer.over_soft_cap);
+        timer.next_mode ();
+
+        timer.start ();
+        timer.seconds = 59;
+        Thread.usleep (1100000);
+        timer.tick ();
+        assert_true (timer.over_soft_cap);
+        assert_cmpint (reached, EQ, 2);
+        timer.stop ();
+    }
+
+    private void test_soft_cap_disabled () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        var timer = manual_timer ();
+        settings.soft_cap = 0;
+
+        timer.start ();
+        timer.seconds = 24 * 3600;
+        Thread.usleep (1100000);
+        timer.tick ();
+        assert_false (timer.over_soft_cap);
+        timer.stop ();
+    }
+
+    // A tick checks the reminders against the seconds of the stage, however many were counted
+    private void work_until (TickingTimer timer, int seconds) {
+        timer.seconds = seconds;
+        timer.tick ();
+    }
+
+    private void test_reminder_escalation () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        var timer = manual_timer ();
+        settings.soft_cap = 60;
+        settings.reminder_interval = 10;
+        settings.firm_reminder_interval = 5;
+        settings.reminder_escalation = 2;
+
+        Services.ReminderLevel[] reminders = {};
+        timer.break_reminder.connect ((level) => reminders += level);
+
+        timer.start ();
+        work_until (timer, 3599);
+        assert_true (timer.reminder_level == NONE);
+        work_until (timer, 3600);
+        assert_true (timer.reminder_level == GENTLE);
+        work_until (timer, 4199);
+        assert_cmpint (reminders.length, EQ, 0);
+
+        // The first ignored reminder stays gentle, the second one becomes firm
+        work_until (timer, 4200);
+        assert_cmpint (reminders.length, EQ, 1);
+        assert_true (reminders[0] == GENTLE);
+        work_until (timer, 4800);
+        assert_cmpint (reminders.length, EQ, 2);
+        assert_true (reminders[1] == FIRM);
+        assert_true (timer.reminder_level == FIRM);
+
+        // Firm reminders are given more often
+        work_until (timer, 5099);
+        assert_cmpint (reminders.length, EQ, 2);
+        work_until (timer, 5100);
+        assert_cmpint (reminders.length, EQ, 3);
+        assert_true (reminders[2] == FIRM);
+
+        // Taking a break starts over from gentle reminders
+        timer.next_mode ();
+        assert_true (timer.reminder_level == NONE);
+        timer.next_mode ();
+
+        timer.start ();
+        work_until (timer, 3600);
+        work_until (timer, 4200);
+        assert_cmpint (reminders.length, EQ, 4);
+        assert_true (reminders[3] == GENTLE);
+        timer.stop ();
+    }
+
+    private void test_reminders_disabled () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        var timer = manual_timer ();
+        settings.soft_cap = 60;
+        settings.reminder_interval = 0;
+
+        int reminders = 0;
+        timer.break_reminder.connect (() => reminders++);
+
+        timer.start ();
+        work_until (timer, 3600);
+        work_until (timer, 10 * 3600);
+        assert_cmpint (reminders, EQ, 0);
+        assert_true (timer.reminder_level == GENTLE);
+        timer.stop ();
+    }
+
+    private void check_break_confirmation (bool auto_start_break, bool confirm_break_start, bool confirmed) {
+        var settings = new Services.Settings ();
+        var timer = manual_timer ();
+        settings.auto_start_break = auto_start_break;
+        settings.confirm_break_start = confirm_break_start;
+
+        assert_false (timer.start_needs_confirmation ());
+        timer.seconds = 3600;
+        timer.next_mode ();
+        assert_true (timer.start_needs_confirmation () == confirmed);
+
+        // A break that was already started is resumed without asking again
+        if (!timer.running) {
+            timer.start ();
+        }
+        timer.stop ();
+        timer.seconds--;
+        assert_false (timer.start_needs_confirmation ());
+    }
+
+    private void test_break_confirmation () {
+        if (!isolate ()) {
+            return;
+        }
+        check_break_confirmation (false, true, true);
+    }
+
+    private void test_break_confirmation_disabled () {
+        if (!isolate ()) {
+            return;
+        }
+        check_break_confirmation (false, false, false);
+    }
+
+    // Automatic breaks are already running, so only breaks started by hand are confirmed
+    private void test_break_confirmation_auto_start () {
+        if (!isolate ()) {
+            return;
+        }
+        check_break_confirmation (true, true, false);
+        check_break_confirmation (true, false, false);
+    }
+
+    private void test_timer_display () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.timer_display = MINUTES_SECONDS;
+
+        var timer = new Services.Timer ();
+        timer.seconds = 4530;
+        assert_cmpstr (timer.formatted_time, EQ, "75:30");
+
+        // The time shown changes with the setting, without waiting for the next tick
+        settings.timer_display = HOURS_MINUTES;
+        assert_cmpstr (timer.formatted_time, EQ, "01:15");
+        settings.timer_display = HOURS_MINUTES_SECONDS;
+        assert_cmpstr (timer.formatted_time, EQ, "01:15:30");
+    }
+
+    // Time tracked while the sample history is shown would be lost
+    private void test_sample_mode_stops_timer () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var statistics = new Services.Statistics ();
+        var timer = manual_timer ();
+        timer.start ();
+
+        statistics.load_sample ();
+        assert_false (timer.running);
+        timer.start ();
+        assert_false (timer.running);
+
+        statistics.unload_sample ();
+        timer.start ();
+        assert_true (timer.running);
+        timer.stop ();
+    }
+
+    public static int main (string[] args) {
+        Test.init (ref args);
+
+        Test.add_func ("/timer/break-for", test_break_for);
+        Test.add_func ("/timer/time-until-break", test_time_until_break);
+        Test.add_func ("/timer/time-until-break/in-break", test_time_until_break_in_break);
+        Test.add_func ("/timer/banked-break", test_banked_break);
+        Test.add_func ("/timer/banked-break/disabled", test_banked_break_disabled);
+        Test.add_func ("/timer/break-rounding", test_break_rounding);
+        Test.add_func ("/timer/break-rounding/suggested", test_suggested_break_rounded);
+        Test.add_func ("/alarm/nudge", test_nudge);
+        Test.add_func ("/timer/suggested-break/bounds-fallback", test_break_bounds_fallback);
+        Test.add_func ("/alarm/play-cue", test_play_cue);
+        Test.add_func ("/timer/auto-start-break", test_auto_start_break);
+        Test.add_func ("/timer/auto-start-break/manual", test_manual_break);
+        Test.add_func ("/timer/split-at-midnight", test_split_at_midnight);
+        Test.add_func ("/timer/start-on-launch", test_start_on_launch);
+        Test.add_func ("/timer/start-on-launch/disabled", test_start_on_launch_disabled);
+        Test.add_func ("/sleep-monitor/not-counted", test_sleep_not_counted);
+        Test.add_func ("/sleep-monitor/while-stopped", test_sleep_while_stopped);
+        Test.add_func ("/timer/earned-break-preview", test_earned_break_preview);
+        Test.add_func ("/timer/phase", test_phase);
+        Test.add_func ("/timer/coarse-ticks", test_coarse_ticks);
+        Test.add_func ("/timer/session-grace/within", test_resume_within_grace);
+        Test.add_func ("/timer/session-grace/after", test_resume_after_grace);
+        Test.add_func ("/timer/worktime-this-hour", test_worktime_this_hour);
+        Test.add_func ("/alarm/next-summary", test_next_summary);
+        Test.add_func ("/alarm/summary-content", test_summary_content);
+        Test.add_func ("/timer/startup-phase/last-used", test_startup_last_used);
+        Test.add_func ("/timer/startup-phase/mode", test_startup_phase_mode);
+        Test.add_func ("/timer/remaining-break", test_remaining_break);
+        Test.add_func ("/timer/remaining-break/completed", test_remaining_break_completed);
+        Test.add_func ("/timer/remaining-break/banked", test_remaining_break_banked);
+        Test.add_func ("/timer/soft-cap", test_soft_cap);
+        Test.add_func ("/timer/soft-cap/disabled", test_soft_cap_disabled);
+        Test.add_func ("/timer/reminder-escalation", test_reminder_escalation);
+        Test.add_func ("/timer/reminder-escalation/disabled", test_reminders_disabled);
+        Test.add_func ("/timer/break-confirmation", test_break_confirmation);
+        Test.add_func ("/timer/break-confirmation/disabled", test_break_confirmation_disabled);
+        Test.add_func ("/timer/break-confirmation/auto-start", test_break_confirmation_auto_start);
+        Test.add_func ("/timer/display", test_timer_display);
+        Test.add_func ("/timer/sample-mode", test_sample_mode_stops_timer);
+
+        return Test.run ();
+    }
+}
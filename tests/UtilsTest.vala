@@ -0,0 +1,167 @@
+/* UtilsTest.vala
+ *
+ * Copyright 2023 Diego Iván <diegoivan.mae@gmail.com>
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+namespace Flowtime.Tests {
+    private void test_same_day () {
+        var morning = new DateTime.local (2023, 3, 1, 0, 0, 0);
+        var night = new DateTime.local (2023, 3, 1, 23, 59, 59);
+        var next_morning = new DateTime.local (2023, 3, 2, 0, 0, 0);
+
+        assert_true (TimeUtils.same_day (morning, night));
+        assert_false (TimeUtils.same_day (night, next_morning));
+    }
+
+    // The tests run in Europe/Berlin, where 2023-03-26 lasts 23 hours and 2023-10-29 lasts 25
+    private void test_same_day_dst () {
+        var spring_midnight = new DateTime.local (2023, 3, 26, 0, 0, 0);
+        assert_true (TimeUtils.same_day (spring_midnight, spring_midnight.add_hours (23).add_seconds (-1)));
+        assert_false (TimeUtils.same_day (spring_midnight, spring_midnight.add_hours (23)));
+
+        var autumn_midnight = new DateTime.local (2023, 10, 29, 0, 0, 0);
+        assert_true (TimeUtils.same_day (autumn_midnight, autumn_midnight.add_hours (24)));
+        assert_false (TimeUtils.same_day (autumn_midnight, autumn_midnight.add_hours (25)));
+    }
+
+    private void test_days_between () {
+        var from = new DateTime.local (2023, 3, 1, 23, 0, 0);
+
+        assert_cmpint (TimeUtils.days_between (from, from.add_hours (2)), EQ, 1);
+        assert_cmpint (TimeUtils.days_between (from, new DateTime.local (2023, 3, 8, 1, 0, 0)), EQ, 7);
+        assert_cmpint (TimeUtils.days_between (from, new DateTime.local (2023, 3, 31, 0, 0, 0)), EQ, 30);
+        assert_cmpint (TimeUtils.days_between (from, new DateTime.local (2023, 2, 28, 12, 0, 0)), EQ, -1);
+    }
+
+    private void test_days_between_dst () {
+        var spring_evening = new DateTime.local (2023, 3, 25, 23, 30, 0);
+        assert_cmpint (TimeUtils.days_between (spring_evening, spring_evening.add_hours (1)), EQ, 1);
+        assert_cmpint (TimeUtils.days_between (spring_evening, spring_evening.add_hours (24)), EQ, 2);
+
+        var autumn_morning = new DateTime.local (2023, 10, 29, 0, 30, 0);
+        assert_cmpint (TimeUtils.days_between (autumn_morning, autumn_morning.add_hours (24)), EQ, 0);
+        assert_cmpint (TimeUtils.days_between (autumn_morning, autumn_morning.add_hours (25)), EQ, 1);
+    }
+
+    private void test_midnight_dst () {
+        var spring_noon = new DateTime.local (2023, 3, 26, 12, 0, 0);
+        var spring_midnight = TimeUtils.normalize_to_local_midnight (spring_noon);
+        assert_true (spring_midnight.equal (new DateTime.local (2023, 3, 26, 0, 0, 0)));
+        assert_cmpint ((int) (spring_noon.difference (spring_midnight) / TimeSpan.HOUR), EQ, 11);
+
+        // A late time of the long day is still attributed to that day
+        var autumn_night = new DateTime.local (2023, 10, 29, 0, 0, 0).add_hours (24);
+        var autumn_midnight = TimeUtils.normalize_to_local_midnight (autumn_night);
+        assert_true (autumn_midnight.equal (new DateTime.local (2023, 10, 29, 0, 0, 0)));
+
+        // The week that contains the short day starts on its Monday all the same
+        var week = TimeUtils.week_start (spring_noon, 1);
+        assert_true (week.equal (new DateTime.local (2023, 3, 20, 0, 0, 0)));
+    }
+
+    private void test_suggested_break () {
+        var settings = new Services.Settings ();
+        settings.break_policy = PERCENTAGE;
+        settings.break_percentage = 25;
+        settings.min_break_seconds = 120;
+        settings.max_break_seconds = 1800;
+
+        int earned_after = settings.break_policy.earned_after ();
+        assert_cmpint (Services.Timer.suggested_break (earned_after - 1), EQ, 0);
+        assert_cmpint (Services.Timer.suggested_break (earned_after), EQ, 120);
+        assert_cmpint (Services.Timer.suggested_break (3600), EQ, 15 * 60);
+        assert_cmpint (Services.Timer.suggested_break (10 * 3600), EQ, 1800);
+    }
+
+    private void test_format_time_rounded () {
+        if (!isolate ()) {
+            return;
+        }
+
+        var settings = new Services.Settings ();
+        settings.round_to_minutes = true;
+        assert_cmpstr (TimeUtils.format_time (8153), EQ, "2h 16m");
+        assert_cmpstr (TimeUtils.format_time (8129), EQ, "2h 15m");
+        assert_cmpstr (TimeUtils.format_time (959), EQ, "16m");
+        assert_cmpstr (TimeUtils.format_time (29), EQ, "0m");
+
+        var day = new Models.Day.for_date (new DateTime.local (2023, 3, 1, 0, 0, 0));
+        day.worktime = 8153;
+        day.breaktime = 959;
+        assert_cmpstr (day.worktime_display, EQ, "2h 16m");
+        assert_cmpstr (day.breaktime_display, EQ, "16m");
+
+        // Only the labels are rounded
+        assert_cmpint (day.worktime, EQ, 8153);
+
+        settings.round_to_minutes = false;
+        assert_cmpstr (TimeUtils.format_time (8153), EQ, "2:15 hours");
+        assert_cmpstr (day.worktime_display, EQ, "2:15 hours");
+    }
+
+    private void test_format_elapsed () {
+        assert_cmpstr (TimeUtils.format_elapsed (0, MINUTES_SECONDS), EQ, "00:00");
+        assert_cmpstr (TimeUtils.format_elapsed (754, MINUTES_SECONDS), EQ, "12:34");
+        assert_cmpstr (TimeUtils.format_elapsed (3599, MINUTES_SECONDS), EQ, "59:59");
+        // Without the hours, the minutes go past 60
+        assert_cmpstr (TimeUtils.format_elapsed (4530, MINUTES_SECONDS), EQ, "75:30");
+
+        assert_cmpstr (TimeUtils.format_elapsed (754, HOURS_MINUTES), EQ, "00:12");
+        assert_cmpstr (TimeUtils.format_elapsed (3599, HOURS_MINUTES), EQ, "00:59");
+        assert_cmpstr (TimeUtils.format_elapsed (4530, HOURS_MINUTES), EQ, "01:15");
+        assert_cmpstr (TimeUtils.format_elapsed (36000, HOURS_MINUTES), EQ, "10:00");
+
+        assert_cmpstr (TimeUtils.format_elapsed (754, HOURS_MINUTES_SECONDS), EQ, "00:12:34");
+        assert_cmpstr (TimeUtils.format_elapsed (3600, HOURS_MINUTES_SECONDS), EQ, "01:00:00");
+        assert_cmpstr (TimeUtils.format_elapsed (4530, HOURS_MINUTES_SECONDS), EQ, "01:15:30");
+    }
+
+    private void test_parse_date () {
+        var morning = new DateTime.local (2023, 3, 1, 10, 0, 0);
+
+        assert_true (TimeUtils.parse_date ("2023-03-01T10:00:00+01:00").equal (morning));
+        assert_true (TimeUtils.parse_date ("2023-03-01T09:00:00Z").equal (morning));
+        assert_true (TimeUtils.parse_date ("2023-03-01T10:00:00.000+01:00").equal (morning));
+
+        // Without a time zone the time is local, and without a time it is local midnight
+        assert_true (TimeUtils.parse_date ("2023-03-01T10:00:00").equal (morning));
+        assert_true (TimeUtils.parse_date ("2023-03-01").equal (new DateTime.local (2023, 3, 1, 0, 0, 0)));
+        assert_true (TimeUtils.parse_date ("2023-07-01").equal (new DateTime.local (2023, 7, 1, 0, 0, 0)));
+
+        assert_null (TimeUtils.parse_date (null));
+        assert_null (TimeUtils.parse_date (""));
+        assert_null (TimeUtils.parse_date ("yesterday"));
+        assert_null (TimeUtils.parse_date ("2023-13-40"));
+    }
+
+    // The tests run with LC_ALL=C, which names the days in English and formats dates as 03/01/23
+    private void test_relative_date_label () {
+        var now = new DateTime.local (2023, 3, 8, 9, 30, 0);
+        var midnight = new DateTime.local (2023, 3, 8, 0, 0, 0);
+
+        assert_cmpstr (TimeUtils.relative_date_label (midnight, now), EQ, "Today");
+        assert_cmpstr (TimeUtils.relative_date_label (midnight.add_hours (-1), now), EQ, "Yesterday");
+        assert_cmpstr (TimeUtils.relative_date_label (midnight.add_days (-2), now), EQ, "Monday");
+        assert_cmpstr (TimeUtils.relative_date_label (midnight.add_days (-6), now), EQ, "Thursday");
+        assert_cmpstr (TimeUtils.relative_date_label (midnight.add_days (-7), now), EQ, "03/01/23");
+    }
+
+    public static int main (string[] args) {
+        Test.init (ref args);
+
+        Test.add_func ("/time-utils/same-day", test_same_day);
+        Test.add_func ("/time-utils/same-day-dst", test_same_day_dst);
+        Test.add_func ("/time-utils/days-between", test_days_between);
+        Test.add_func ("/time-utils/days-between-dst", test_days_between_dst);
+        Test.add_func ("/time-utils/midnight-dst", test_midnight_dst);
+        Test.add_func ("/time-utils/format-time-rounded", test_format_time_rounded);
+        Test.add_func ("/time-utils/format-elapsed", test_format_elapsed);
+        Test.add_func ("/time-utils/parse-date", test_parse_date);
+        Test.add_func ("/time-utils/relative-date-label", test_relative_date_label);
+        Test.add_func ("/timer/suggested-break", test_suggested_break);
+
+        return Test.run ();
+    }
+}